@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use genevo::population::GenomeBuilder;
+use genevo::prelude::FitnessFunction;
+use islab3::{input, solve, Context, InitHourDist, RandomScheduleBuilder, SolverParams};
+
+/// The problem in `constraints.json`, far larger than the built-in examples.
+fn constraints() -> islab3::Problem {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("constraints.json");
+    input::load_problem(&path).expect("constraints.json loads")
+}
+
+fn fitness(c: &mut Criterion) {
+    let context = Context::new(constraints());
+    let mut rng = genevo::random::get_rng([1; 32]);
+    let genome = RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(0, &mut rng);
+
+    c.bench_function("fitness_of", |b| b.iter(|| (&context).fitness_of(&genome)));
+}
+
+fn short_solve(c: &mut Criterion) {
+    let problem = constraints();
+    let params = SolverParams {
+        population_size: 50,
+        generation_limit: 10,
+        seed: Some([1; 32]),
+        ..SolverParams::default()
+    };
+
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+    group.bench_function("10 generations of 50", |b| {
+        b.iter(|| solve(&problem, params.clone()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fitness, short_solve);
+criterion_main!(benches);
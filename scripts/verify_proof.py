@@ -0,0 +1,189 @@
+#!/usr/bin/env python3
+"""Independently checks a proof written by `is-lab3 --export-proof <path>`.
+
+Every check is recomputed from the problem and the lessons alone and compared with what
+the proof claims. Exits 0 if the schedule is feasible and the proof is consistent, 1 otherwise.
+
+usage: verify_proof.py <proof.json>
+"""
+import json
+import sys
+from collections import Counter, defaultdict
+
+
+def recompute(proof):
+    hours, weeks = proof["hours_per_week"], proof["weeks"]
+    problem, lessons = proof["problem"], proof["lessons"]
+    suitable = {s["subject"]: set(s["lecturers"]) for s in problem["subjects"]}
+    budgets = {l["lecturer"]: l["budget_per_week"] for l in problem["lecturers"]}
+    unavailable = {l["lecturer"]: set(l.get("unavailable_hours", [])) for l in problem["lecturers"]}
+    caps = {l["lecturer"]: l["max_hours_per_day"] for l in problem["lecturers"]
+            if l.get("max_hours_per_day") is not None}
+    default_cap = problem.get("default_max_hours_per_day")
+    run_caps = {l["lecturer"]: l["max_consecutive"] for l in problem["lecturers"]
+                if l.get("max_consecutive") is not None}
+
+    scheduled = Counter((l["group"], l["subject"], l["week"]) for l in lessons)
+    coverage = [
+        {"group": g["group"], "subject": s["subject"], "week": week,
+         "required": s["hours"], "scheduled": scheduled[(g["group"], s["subject"], week)]}
+        for g in problem["groups"] for s in g["subjects"] for week in range(weeks)
+    ]
+    required = {(c["group"], c["subject"], c["week"]) for c in coverage}
+    uncovered = sum(c["required"] != c["scheduled"] for c in coverage)
+    uncovered += sum(count for key, count in scheduled.items() if key not in required)
+
+    eligibility = [
+        {"lesson": l["id"], "subject": l["subject"], "lecturer": l["lecturer"],
+         "eligible": l["lecturer"] in suitable.get(l["subject"], set())}
+        for l in lessons
+    ]
+    # No lesson may take a forbidden period of any day.
+    forbidden = set(problem.get("forbidden_periods", []))
+    availability = [
+        {"lesson": l["id"], "lecturer": l["lecturer"], "hour": l["hour"],
+         "available": l["hour"] % hours not in unavailable.get(l["lecturer"], set())
+         and l["hour"] % proof["periods_per_day"] not in forbidden}
+        for l in lessons
+    ]
+    hour_range = [
+        {"lesson": l["id"], "week": l["week"], "hour": l["hour"],
+         "in_week": 0 <= l["hour"] and l["hour"] // hours == l["week"] < weeks}
+        for l in lessons
+    ]
+
+    def occupancy(key):
+        cells = defaultdict(list)
+        for l in lessons:
+            cells[(l[key], l["hour"])].append(l["id"])
+        return [{key: k, "hour": hour, "lessons": ids} for (k, hour), ids in sorted(cells.items())]
+
+    assigned = Counter((l["lecturer"], l["week"]) for l in lessons)
+    budget = [
+        {"lecturer": lecturer, "week": week, "assigned": assigned[(lecturer, week)], "budget": hours}
+        for lecturer, hours in sorted(budgets.items()) for week in range(weeks)
+    ] + [
+        {"lecturer": lecturer, "week": week, "assigned": count, "budget": 0}
+        for (lecturer, week), count in sorted(assigned.items()) if lecturer not in budgets
+    ]
+
+    group_occupancy, lecturer_occupancy = occupancy("group"), occupancy("lecturer")
+    checks = {
+        "coverage": (coverage, uncovered),
+        "eligibility": (eligibility, sum(not e["eligible"] for e in eligibility)),
+        "availability": (availability, sum(not a["available"] for a in availability)),
+        "hour_range": (hour_range, sum(not h["in_week"] for h in hour_range)),
+        "group_occupancy": (group_occupancy, sum(len(c["lessons"]) > 1 for c in group_occupancy)),
+        "lecturer_occupancy": (lecturer_occupancy, sum(len(c["lessons"]) > 1 for c in lecturer_occupancy)),
+        "lecturer_budget": (budget, sum(b["assigned"] > b["budget"] for b in budget)),
+    }
+    # Without rooms every lesson is in room 0, which is not a clash.
+    if proof.get("room_count", 0) > 0:
+        room_occupancy = occupancy("room")
+        checks["room_occupancy"] = (room_occupancy, sum(len(c["lessons"]) > 1 for c in room_occupancy))
+        sizes = {g["group"]: g.get("size") for g in problem["groups"]}
+        capacities = {r["room"]: r["capacity"] for r in problem["rooms"]}
+        room_capacity = []
+        for l in lessons:
+            size, capacity = sizes.get(l["group"]), capacities.get(l["room"])
+            room_capacity.append(
+                {"lesson": l["id"], "group": l["group"], "room": l["room"], "size": size,
+                 "capacity": capacity, "fits": size is None or capacity is None or size <= capacity})
+        checks["room_capacity"] = (room_capacity, sum(not c["fits"] for c in room_capacity))
+    if caps or default_cap is not None:
+        periods = proof["periods_per_day"]
+        daily = defaultdict(set)
+        for l in lessons:
+            daily[(l["lecturer"], l["hour"] // periods)].add(l["hour"])
+        daily_hours = [
+            {"lecturer": lecturer, "day": day, "hours": sorted(hours),
+             "cap": caps.get(lecturer, default_cap)}
+            for (lecturer, day), hours in sorted(daily.items())
+            if caps.get(lecturer, default_cap) is not None
+        ]
+        checks["lecturer_daily_hours"] = (daily_hours, sum(len(d["hours"]) > d["cap"] for d in daily_hours))
+    if run_caps:
+        periods = proof["periods_per_day"]
+        daily = defaultdict(set)
+        for l in lessons:
+            daily[(l["lecturer"], l["hour"] // periods)].add(l["hour"])
+        consecutive_periods = []
+        for (lecturer, day), hours in sorted(daily.items()):
+            if lecturer not in run_caps:
+                continue
+            longest_run = run = 0
+            for hour in sorted(hours):
+                run = run + 1 if hour - 1 in hours else 1
+                longest_run = max(longest_run, run)
+            consecutive_periods.append(
+                {"lecturer": lecturer, "day": day, "hours": sorted(hours),
+                 "longest_run": longest_run, "cap": run_caps[lecturer]})
+        checks["lecturer_consecutive_periods"] = (
+            consecutive_periods, sum(c["longest_run"] > c["cap"] for c in consecutive_periods))
+    # Double lessons pair off the hours of a consecutive subject in lesson order, per week.
+    consecutive = {(g["group"], s["subject"]) for g in problem["groups"] for s in g["subjects"]
+                   if s.get("consecutive")}
+    if consecutive:
+        by_requirement = defaultdict(list)
+        for l in lessons:
+            if (l["group"], l["subject"]) in consecutive:
+                by_requirement[(l["group"], l["subject"], l["week"])].append(l)
+        double_lessons = []
+        for halves in by_requirement.values():
+            for a, b in zip(halves[::2], halves[1::2]):
+                periods = proof["periods_per_day"]
+                adjacent = a["hour"] // periods == b["hour"] // periods and a["hour"] + 1 == b["hour"]
+                double_lessons.append({"lessons": [a["id"], b["id"]], "adjacent": adjacent})
+        checks["double_lessons"] = (double_lessons, sum(not d["adjacent"] for d in double_lessons))
+    # Identical pins need a matching lesson each.
+    if problem.get("pinned"):
+        unclaimed = Counter((l["group"], l["subject"], l["lecturer"], l["hour"]) for l in lessons)
+        pinned_lessons = []
+        for pin in problem["pinned"]:
+            key = (pin["group"], pin["subject"], pin["lecturer"], pin["hour"])
+            kept = unclaimed[key] > 0
+            unclaimed[key] -= kept
+            pinned_lessons.append({**pin, "kept": kept})
+        checks["pinned_lessons"] = (pinned_lessons, sum(not p["kept"] for p in pinned_lessons))
+    return checks
+
+
+def key(instance):
+    return json.dumps(instance, sort_keys=True)
+
+
+def verify(proof):
+    errors = []
+    ids = [l["id"] for l in proof["lessons"]]
+    if ids != list(range(len(ids))):
+        errors.append("lesson ids are not 0..n in order")
+    claimed = proof["checks"]
+    for name, (instances, violations) in recompute(proof).items():
+        if name not in claimed:
+            errors.append(f"{name}: missing from the proof")
+            continue
+        if sorted(map(key, instances)) != sorted(map(key, claimed[name]["instances"])):
+            errors.append(f"{name}: listed instances do not match the lessons")
+        if claimed[name]["violations"] != violations:
+            errors.append(f"{name}: claims {claimed[name]['violations']} violations, found {violations}")
+        if violations:
+            errors.append(f"{name}: {violations} violations")
+    if not proof.get("feasible"):
+        errors.append("the proof does not claim feasibility")
+    return errors
+
+
+def main():
+    if len(sys.argv) != 2:
+        sys.exit(__doc__.strip().splitlines()[-1])
+    with open(sys.argv[1]) as file:
+        errors = verify(json.load(file))
+    for error in errors:
+        print(f"rejected: {error}")
+    if errors:
+        sys.exit(1)
+    print("accepted: the schedule satisfies every hard constraint")
+
+
+if __name__ == "__main__":
+    main()
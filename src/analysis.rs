@@ -0,0 +1,832 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::{
+    i18n::{Labels, Lang},
+    Context, Genome, GroupId, LecturerId, PartialScore, Problem, RoomId, SubjectId, TimeSlot,
+    PERIODS_PER_DAY,
+};
+
+/// Lessons that only certain lecturers can teach, more of them than the hours those
+/// lecturers can take them at, so two of them must collide whatever the schedule looks like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LecturerConflict {
+    pub lecturers: Vec<LecturerId>, // one, or with a `group` each lecturer of its lessons.
+    pub group: Option<GroupId>, // the group whose hours the lessons compete for, if they fit each lecturer alone.
+    pub lessons: Vec<(GroupId, SubjectId, usize)>, // (group, subject, hours) that only these lecturers can teach.
+    pub forced_hours: usize,
+    pub open_hours: usize, // the most of the lessons the lecturers' open hours and daily caps fit.
+}
+
+/// Finds lessons that are structurally over-subscribed before solving: those of a lecturer
+/// that is the only option for more of them than their available hours fit, within their
+/// daily caps and periods in a row, and those of a group whose only options are several
+/// lecturers who each fit their share, but not all of them in the hours of the group.
+pub fn unavoidable_lecturer_conflicts(problem: &Problem) -> Vec<LecturerConflict> {
+    let mut forced: BTreeMap<LecturerId, Vec<(GroupId, SubjectId, usize)>> = BTreeMap::new();
+    for (group, subjects) in &problem.group_requirements {
+        for (subject, hours) in subjects {
+            if let Some([lecturer]) = problem.subject_requirements.get(subject).map(Vec::as_slice) {
+                forced
+                    .entry(*lecturer)
+                    .or_default()
+                    .push((*group, *subject, *hours));
+            }
+        }
+    }
+    let hours_of = |lessons: &[(GroupId, SubjectId, usize)]| -> usize {
+        lessons.iter().map(|(_, _, hours)| hours).sum()
+    };
+
+    let mut conflicts = vec![];
+    for (&lecturer, lessons) in &forced {
+        let forced_hours = hours_of(lessons);
+        let open_hours = open_hours(problem, &[(lecturer, forced_hours)]);
+        if open_hours < forced_hours {
+            let mut lessons = lessons.clone();
+            lessons.sort();
+            conflicts.push(LecturerConflict {
+                lecturers: vec![lecturer],
+                group: None,
+                lessons,
+                forced_hours,
+                open_hours,
+            });
+        }
+    }
+
+    let mut by_group: BTreeMap<GroupId, BTreeMap<LecturerId, Vec<_>>> = BTreeMap::new();
+    for (&lecturer, lessons) in &forced {
+        for &lesson in lessons {
+            by_group
+                .entry(lesson.0)
+                .or_default()
+                .entry(lecturer)
+                .or_default()
+                .push(lesson);
+        }
+    }
+    for (group, by_lecturer) in by_group {
+        if by_lecturer.len() < 2 {
+            continue;
+        }
+        let demand: Vec<_> = by_lecturer
+            .iter()
+            .map(|(&lecturer, lessons)| (lecturer, hours_of(lessons)))
+            .collect();
+        // Only what sharing the group's hours costs, each lecturer's own shortfall is above.
+        let apart: usize = demand
+            .iter()
+            .map(|&demand| open_hours(problem, &[demand]))
+            .sum();
+        let open_hours = open_hours(problem, &demand);
+        if open_hours < apart {
+            let mut lessons: Vec<_> = by_lecturer.into_values().flatten().collect();
+            lessons.sort();
+            conflicts.push(LecturerConflict {
+                lecturers: demand.iter().map(|(lecturer, _)| *lecturer).collect(),
+                group: Some(group),
+                forced_hours: hours_of(&lessons),
+                lessons,
+                open_hours,
+            });
+        }
+    }
+    conflicts
+}
+
+/// The most of `demand`, hours of a week per lecturer, that fit into distinct hours of the
+/// week at which their lecturer is available, within each lecturer's daily cap and periods
+/// in a row. A maximum flow from the lecturers over their days to the hours.
+fn open_hours(problem: &Problem, demand: &[(LecturerId, usize)]) -> usize {
+    let hours = problem.total_hours;
+    let days = hours.div_ceil(PERIODS_PER_DAY);
+    let day_node = |index: usize, day: usize| 1 + demand.len() + index * days + day;
+    let hour_node = |hour: usize| 1 + demand.len() * (1 + days) + hour;
+    let sink = hour_node(hours);
+    let mut capacity = vec![vec![0; sink + 1]; sink + 1];
+    for (index, &(lecturer, forced)) in demand.iter().enumerate() {
+        capacity[0][1 + index] = forced;
+        for day in 0..days {
+            let available = (0..PERIODS_PER_DAY)
+                .map(|period| (period, day * PERIODS_PER_DAY + period))
+                .filter(|&(_, hour)| hour < hours && problem.is_available(lecturer, hour))
+                .map(|(period, _)| period)
+                .fold(0u32, |periods, period| periods | 1 << period);
+            capacity[1 + index][day_node(index, day)] = day_capacity(problem, lecturer, available);
+            for period in 0..PERIODS_PER_DAY {
+                if available & 1 << period != 0 {
+                    capacity[day_node(index, day)][hour_node(day * PERIODS_PER_DAY + period)] = 1;
+                }
+            }
+        }
+    }
+    for hour in 0..hours {
+        capacity[hour_node(hour)][sink] = 1;
+    }
+    max_flow(&mut capacity, 0, sink)
+}
+
+/// The most periods of `available`, a bitset of a day's periods, `lecturer` can teach on
+/// one day, within their daily cap and without more periods in a row than their cap on them.
+fn day_capacity(problem: &Problem, lecturer: LecturerId, available: u32) -> usize {
+    let cap = problem.daily_cap(lecturer).unwrap_or(usize::MAX);
+    let run_cap = problem
+        .max_consecutive
+        .get(&lecturer)
+        .copied()
+        .unwrap_or(usize::MAX);
+    (0..1u32 << PERIODS_PER_DAY)
+        .filter(|periods| periods & !available == 0 && crate::overrun(*periods, run_cap) == 0)
+        .map(|periods| (periods.count_ones() as usize).min(cap))
+        .max()
+        .unwrap_or(0)
+}
+
+/// The value of a maximum flow from `source` to `sink` over the residual `capacity` matrix,
+/// by depth-first augmenting paths, which is quick enough for flows of a week's hours.
+fn max_flow(capacity: &mut [Vec<usize>], source: usize, sink: usize) -> usize {
+    fn augment(
+        capacity: &mut [Vec<usize>],
+        node: usize,
+        sink: usize,
+        limit: usize,
+        visited: &mut [bool],
+    ) -> usize {
+        if node == sink {
+            return limit;
+        }
+        visited[node] = true;
+        for next in 0..capacity.len() {
+            if visited[next] || capacity[node][next] == 0 {
+                continue;
+            }
+            let pushed = augment(
+                capacity,
+                next,
+                sink,
+                limit.min(capacity[node][next]),
+                visited,
+            );
+            if pushed > 0 {
+                capacity[node][next] -= pushed;
+                capacity[next][node] += pushed;
+                return pushed;
+            }
+        }
+        0
+    }
+
+    let mut flow = 0;
+    loop {
+        let mut visited = vec![false; capacity.len()];
+        match augment(capacity, source, sink, usize::MAX, &mut visited) {
+            0 => return flow,
+            pushed => flow += pushed,
+        }
+    }
+}
+
+/// A lecturer teaching more hours on one day than their `Problem::daily_cap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyOverload {
+    pub lecturer: LecturerId,
+    pub day: usize, // counted on across weeks, like `TimeSlot::day`.
+    pub hours: usize,
+    pub cap: usize,
+}
+
+/// Every day a lecturer of `genome` teaches beyond their cap. Lessons at the same hour count
+/// once, their clash is a separate violation.
+pub fn daily_overloads(context: &Context, genome: &Genome) -> Vec<DailyOverload> {
+    let mut hours: BTreeMap<(LecturerId, usize), BTreeSet<usize>> = BTreeMap::new();
+    for dna in genome {
+        let (_, lecturer, _, slot) = dna.0;
+        hours
+            .entry((lecturer, usize::from(slot.day)))
+            .or_default()
+            .insert(slot.index());
+    }
+    hours
+        .into_iter()
+        .filter_map(|((lecturer, day), hours)| {
+            let cap = context.problem.daily_cap(lecturer)?;
+            (hours.len() > cap).then_some(DailyOverload {
+                lecturer,
+                day,
+                hours: hours.len(),
+                cap,
+            })
+        })
+        .collect()
+}
+
+/// A hard constraint a lesson of a solved schedule breaks, see `diagnose`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    pub group: GroupId,
+    pub subject: SubjectId,
+    pub lecturer: LecturerId,
+    pub room: RoomId,
+    pub slot: TimeSlot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    GroupClash,
+    LecturerClash,
+    LecturerUnavailable,
+    OverAllocatedLecturer,
+    RoomClash,
+    RoomTooSmall,
+    MovedPin,
+    SplitDoubleLesson,
+    DailyOverload { hours: usize, cap: usize }, // of the lesson's lecturer on its day.
+    LongRun { periods: usize, cap: usize }, // the longest the lecturer teaches in a row that day.
+}
+
+impl Violation {
+    /// A sentence naming the lesson and what's wrong with it, with names from `problem`.
+    pub fn describe(&self, problem: &Problem, labels: &Labels) -> String {
+        let group = problem.group_label(self.group);
+        let subject = problem.subject_label(self.subject);
+        let lecturer = format!(
+            "{} {}",
+            labels.lecturer,
+            problem.lecturer_label(self.lecturer)
+        );
+        let room = format!("{} {}", labels.room, self.room);
+        let day = format!("{} {}", labels.day, self.slot.day);
+        let broken = match self.kind {
+            ViolationKind::GroupClash => labels.group_busy.to_string(),
+            ViolationKind::LecturerClash => format!("{lecturer} {}", labels.already_teaches),
+            ViolationKind::LecturerUnavailable => format!("{lecturer} {}", labels.unavailable),
+            ViolationKind::OverAllocatedLecturer => {
+                format!("{lecturer} {}", labels.out_of_weekly_hours)
+            }
+            ViolationKind::RoomClash => format!("{room} {}", labels.room_taken),
+            ViolationKind::RoomTooSmall => format!("{room} {}", labels.room_too_small),
+            ViolationKind::MovedPin => {
+                format!(
+                    "{} {lecturer} {}",
+                    labels.taught_by, labels.pinned_elsewhere
+                )
+            }
+            ViolationKind::SplitDoubleLesson => labels.split_double_lesson.to_string(),
+            ViolationKind::DailyOverload { hours, cap } => format!(
+                "{lecturer} {} {hours} {} {} {day}, {} {cap}",
+                labels.teaches, labels.hours, labels.on, labels.over_cap
+            ),
+            ViolationKind::LongRun { periods, cap } => format!(
+                "{lecturer} {} {periods} {} {} {day}, {} {cap}",
+                labels.teaches, labels.periods_in_a_row, labels.on, labels.over_cap
+            ),
+        };
+        format!(
+            "{} {group}, {} {subject} {} {day}, {} {}: {broken}",
+            labels.group, labels.subject, labels.at, labels.period, self.slot.period
+        )
+    }
+}
+
+/// Every hard constraint the lessons of `genome` break, by the same checks the fitness
+/// counts, in gene order. Of two clashing lessons, only the later is listed. After them
+/// come the lecturer days over a daily cap or with too many periods in a row, each listed
+/// once at the first lesson past the cap.
+pub fn diagnose(context: &Context, genome: &Genome) -> Vec<Violation> {
+    let mut violations = vec![];
+    let violation = |index: usize, kind| {
+        let (group, subject) = context.group_subjects[index];
+        let (_, lecturer, room, slot) = genome[index].0;
+        Violation {
+            kind,
+            group,
+            subject,
+            lecturer,
+            room,
+            slot,
+        }
+    };
+    let mut untaken = BTreeSet::new();
+    context.check_genes(
+        genome,
+        |_| true,
+        |index, check| {
+            let kinds = [
+                (check.group_clash, ViolationKind::GroupClash),
+                (check.lecturer_busy, ViolationKind::LecturerClash),
+                (
+                    check.lecturer_unavailable,
+                    ViolationKind::LecturerUnavailable,
+                ),
+                (check.over_allocated, ViolationKind::OverAllocatedLecturer),
+                (check.room_taken, ViolationKind::RoomClash),
+                (check.room_too_small, ViolationKind::RoomTooSmall),
+                (check.moved_pin, ViolationKind::MovedPin),
+                (check.split_pair, ViolationKind::SplitDoubleLesson),
+            ];
+            for (_, kind) in kinds.into_iter().filter(|(broken, _)| *broken) {
+                violations.push(violation(index, kind));
+            }
+            if check.lecturer_busy || check.lecturer_unavailable || check.over_allocated {
+                untaken.insert(index);
+            }
+        },
+    );
+
+    // Like the fitness, only lessons their lecturer can take count towards the caps.
+    let mut days: BTreeMap<(LecturerId, u8), BTreeMap<u8, usize>> = BTreeMap::new();
+    for (index, dna) in genome.iter().enumerate() {
+        if !untaken.contains(&index) {
+            let (_, lecturer, _, slot) = dna.0;
+            days.entry((lecturer, slot.day))
+                .or_default()
+                .insert(slot.period, index);
+        }
+    }
+
+    let problem = &context.problem;
+    for ((lecturer, _), periods) in &days {
+        if let Some(cap) = problem.daily_cap(*lecturer) {
+            if let Some(&index) = periods.values().nth(cap) {
+                let kind = ViolationKind::DailyOverload {
+                    hours: periods.len(),
+                    cap,
+                };
+                violations.push(violation(index, kind));
+            }
+        }
+        if let Some(&cap) = problem.max_consecutive.get(lecturer) {
+            let (mut longest, mut run, mut past_cap) = (0, 0, None);
+            for (&period, &index) in periods {
+                let follows = period > 0 && periods.contains_key(&(period - 1));
+                run = if follows { run + 1 } else { 1 };
+                longest = longest.max(run);
+                if run == cap + 1 && past_cap.is_none() {
+                    past_cap = Some(index);
+                }
+            }
+            if let Some(index) = past_cap {
+                let kind = ViolationKind::LongRun {
+                    periods: longest,
+                    cap,
+                };
+                violations.push(violation(index, kind));
+            }
+        }
+    }
+    violations
+}
+
+/// Checks that every gene of a solved genome is a valid assignment for its lesson.
+pub fn validate_genome(context: &Context, genome: &Genome) -> Vec<String> {
+    let problem = &context.problem;
+    let group_subjects = &context.group_subjects;
+    if genome.len() != group_subjects.len() {
+        return vec![format!(
+            "genome has {} genes, expected {}",
+            genome.len(),
+            group_subjects.len()
+        )];
+    }
+
+    let mut errors = vec![];
+    for (index, ((group, subject), dna)) in group_subjects.iter().zip(genome).enumerate() {
+        let (gene_subject, lecturer, room, slot) = dna.0;
+        if gene_subject != *subject {
+            errors.push(format!(
+                "gene {index}: subject {gene_subject} instead of {subject} for group {group}"
+            ));
+        }
+        let suitable = problem
+            .subject_requirements
+            .get(subject)
+            .is_some_and(|lecturers| lecturers.contains(&lecturer));
+        if !suitable {
+            errors.push(format!(
+                "gene {index}: lecturer {lecturer} cannot teach subject {subject}"
+            ));
+        }
+        if usize::from(slot.period) >= PERIODS_PER_DAY
+            || slot.index() >= problem.total_hours * problem.weeks
+        {
+            errors.push(format!("gene {index}: {slot} is out of range"));
+        }
+        if room >= problem.room_count.max(1) {
+            errors.push(format!("gene {index}: room {room} is out of range"));
+        }
+    }
+    errors
+}
+
+/// How much each gene adds to the fitness of `genome`, i.e. the drop in fitness if that
+/// single gene was left unscheduled. Negative values mark genes that cost more than they earn.
+pub fn gene_contributions(genome: &Genome, context: &Context) -> Vec<i64> {
+    let fitness = context.fitness_ignoring(genome, None);
+    (0..genome.len())
+        .map(|index| fitness - context.fitness_ignoring(genome, Some(index)))
+        .collect()
+}
+
+/// Scores only the genes at `indices`, as if the rest of `genome` was not scheduled, so the
+/// interaction between a few specific genes can be checked without the others interfering.
+pub fn evaluate_genes(genome: &Genome, indices: &[usize], context: &Context) -> PartialScore {
+    context.score_genes(genome, |index| indices.contains(&index))
+}
+
+/// Fitness of every single-gene neighbour of `genome` as CSV, one row per alternative
+/// (suitable lecturer, hour of the gene's week), with the change against `genome` itself.
+pub fn landscape_csv(context: &Context, genome: &Genome) -> String {
+    let group_subjects = &context.group_subjects;
+    let fitness = context.fitness_ignoring(genome, None);
+    let mut csv = String::from("gene,group,subject,lecturer,hour,fitness,delta\n");
+    let mut neighbour = genome.clone();
+    for (index, dna) in genome.iter().enumerate() {
+        let (group, subject) = group_subjects[index];
+        let week = context.problem.total_hours;
+        let week_start = dna.0 .3.week(context.problem.days()) * week;
+        for &lecturer in &context.problem.subject_requirements[&subject] {
+            for hour in week_start..week_start + week {
+                if (lecturer, hour) == (dna.0 .1, dna.0 .3.index()) {
+                    continue;
+                }
+                neighbour[index].0 .1 = lecturer;
+                neighbour[index].0 .3 = TimeSlot::from_index(hour);
+                let changed = context.fitness_ignoring(&neighbour, None);
+                csv.push_str(&format!(
+                    "{index},{group},{subject},{lecturer},{hour},{changed},{}\n",
+                    changed - fitness
+                ));
+            }
+        }
+        neighbour[index] = *dna;
+    }
+    csv
+}
+
+/// Required hours compared to the hours available to cover them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Utilization {
+    pub demand: usize,
+    pub capacity: usize,
+}
+
+impl Utilization {
+    /// Demand as a rounded percentage of capacity, `None` without any capacity.
+    pub fn percent(&self) -> Option<f64> {
+        (self.capacity > 0).then(|| (self.demand as f64 / self.capacity as f64 * 100.0).round())
+    }
+
+    /// The percentage and how tight it is, in the language of `labels`.
+    pub fn describe(&self, labels: &Labels) -> String {
+        let Some(percent) = self.percent() else {
+            return format!(
+                "{} {}, {}",
+                self.demand, labels.hours_required, labels.no_capacity
+            );
+        };
+        let kind = match self.demand.cmp(&self.capacity) {
+            std::cmp::Ordering::Less => labels.slack,
+            std::cmp::Ordering::Equal => labels.tight,
+            std::cmp::Ordering::Greater => labels.over_capacity,
+        };
+        format!("{percent}% ({kind})")
+    }
+}
+
+impl std::fmt::Display for Utilization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.describe(Lang::En.labels()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CapacityReport {
+    pub overall: Utilization, // all group hours against all lecturer budgets.
+    pub lecturers: Vec<(LecturerId, Utilization)>, // hours only this lecturer can teach against their budget.
+}
+
+/// Reports how tightly the lecturer budgets cover the hours the groups need.
+pub fn capacity_utilization(problem: &Problem) -> CapacityReport {
+    let demand = problem
+        .group_requirements
+        .values()
+        .flatten()
+        .map(|(_, hours)| hours)
+        .sum();
+    let capacity = problem.lecturer_requirements.values().sum();
+
+    let mut forced: BTreeMap<LecturerId, usize> = problem
+        .lecturer_requirements
+        .keys()
+        .map(|lecturer| (*lecturer, 0))
+        .collect();
+    for (subject, hours) in problem.group_requirements.values().flatten() {
+        if let Some([lecturer]) = problem.subject_requirements.get(subject).map(Vec::as_slice) {
+            *forced.entry(*lecturer).or_default() += hours;
+        }
+    }
+
+    CapacityReport {
+        overall: Utilization { demand, capacity },
+        lecturers: forced
+            .into_iter()
+            .map(|(lecturer, demand)| {
+                let capacity = problem
+                    .lecturer_requirements
+                    .get(&lecturer)
+                    .copied()
+                    .unwrap_or_default();
+                (lecturer, Utilization { demand, capacity })
+            })
+            .collect(),
+    }
+}
+
+/// How much of their time the lecturers and groups of a schedule spend in lessons.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScheduleUsage {
+    pub lecturers: BTreeMap<LecturerId, Utilization>, // lessons taught against the budget of all weeks.
+    pub groups: BTreeMap<GroupId, Utilization>,       // hours with a lesson against all hours.
+    pub slots: Utilization, // the groups' hours with a lesson against all of their hours.
+    pub free_half_days: BTreeMap<GroupId, usize>, // mornings and afternoons without a lesson.
+}
+
+/// Sums up how the final schedule uses lecturer budgets and group hours. A group with two
+/// lessons at once occupies that hour only once, a lecturer teaching both still uses two.
+/// Days split into a morning and an afternoon of `PERIODS_PER_DAY / 2` periods each.
+pub fn schedule_usage(context: &Context, genome: &Genome) -> ScheduleUsage {
+    let problem = &context.problem;
+    let hours = problem.total_hours * problem.weeks;
+    let mut lecturers: BTreeMap<_, _> = problem
+        .lecturer_requirements
+        .iter()
+        .map(|(&lecturer, &budget)| {
+            let utilization = Utilization {
+                demand: 0,
+                capacity: budget * problem.weeks,
+            };
+            (lecturer, utilization)
+        })
+        .collect();
+    let mut occupied: BTreeMap<GroupId, BTreeSet<usize>> = problem
+        .group_requirements
+        .keys()
+        .map(|&group| (group, BTreeSet::new()))
+        .collect();
+    for (&(group, _), dna) in context.group_subjects.iter().zip(genome) {
+        let (_, lecturer, _, slot) = dna.0;
+        lecturers
+            .entry(lecturer)
+            .or_insert(Utilization {
+                demand: 0,
+                capacity: 0,
+            })
+            .demand += 1;
+        occupied.entry(group).or_default().insert(slot.index());
+    }
+
+    let half_days = problem.days() * problem.weeks * 2;
+    let free_half_days = occupied
+        .iter()
+        .map(|(&group, slots)| {
+            let busy: BTreeSet<_> = slots
+                .iter()
+                .map(|&hour| {
+                    let slot = TimeSlot::from_index(hour);
+                    (slot.day, usize::from(slot.period) < PERIODS_PER_DAY / 2)
+                })
+                .collect();
+            (group, half_days - busy.len())
+        })
+        .collect();
+    let groups: BTreeMap<_, _> = occupied
+        .into_iter()
+        .map(|(group, slots)| {
+            let utilization = Utilization {
+                demand: slots.len(),
+                capacity: hours,
+            };
+            (group, utilization)
+        })
+        .collect();
+    let slots = Utilization {
+        demand: groups.values().map(|usage| usage.demand).sum(),
+        capacity: groups.len() * hours,
+    };
+    ScheduleUsage {
+        lecturers,
+        groups,
+        slots,
+        free_half_days,
+    }
+}
+
+/// A stable FNV-1a hash of the problem, for telling runs on different inputs apart. It
+/// covers everything that affects solving, display names aside.
+pub fn fingerprint(problem: &Problem) -> u64 {
+    let mut groups: Vec<_> = problem.group_requirements.iter().collect();
+    groups.sort();
+    let mut lecturers: Vec<_> = problem.lecturer_requirements.iter().collect();
+    lecturers.sort();
+    let mut subjects: Vec<_> = problem.subject_requirements.iter().collect();
+    subjects.sort();
+    let unavailable: BTreeMap<_, BTreeSet<_>> = problem
+        .lecturer_unavailable
+        .iter()
+        .map(|(lecturer, hours)| (lecturer, hours.iter().collect()))
+        .collect();
+    let preferences: BTreeMap<_, BTreeMap<_, _>> = problem
+        .lecturer_preferences
+        .iter()
+        .map(|(lecturer, hours)| (lecturer, hours.iter().collect()))
+        .collect();
+    let disliked: BTreeMap<_, BTreeSet<_>> = problem
+        .group_disliked_hours
+        .iter()
+        .map(|(group, hours)| (group, hours.iter().collect()))
+        .collect();
+    let mut pinned = problem.pinned.clone();
+    pinned.sort();
+
+    let canonical = format!(
+        "{groups:?}|{lecturers:?}|{subjects:?}|{}|{}|{}|{:?}|{:?}|{unavailable:?}|{:?}|{:?}|{:?}\
+         |{:?}|{pinned:?}|{:?}|{:?}|{:?}|{:?}|{preferences:?}|{:?}|{:?}|{}|{:?}|{disliked:?}|{}|{:?}|{:?}|{:?}",
+        problem.weeks,
+        problem.total_hours,
+        problem.room_count,
+        sorted(&problem.group_sizes),
+        sorted(&problem.room_capacities),
+        sorted(&problem.max_hours_per_day),
+        problem.default_max_hours_per_day,
+        sorted(&problem.max_consecutive),
+        problem.forbidden_periods.iter().collect::<BTreeSet<_>>(),
+        problem.weights,
+        sorted(&problem.group_weights),
+        sorted(&problem.lecturer_suitability),
+        problem.consecutive.iter().collect::<BTreeSet<_>>(),
+        problem.rotate_subjects.iter().collect::<BTreeSet<_>>(),
+        sorted(&problem.lab_lectures),
+        problem.lecture_before_lab,
+        problem.must_be_first.iter().collect::<BTreeSet<_>>(),
+        problem.room_change_needs_gap,
+        sorted(&problem.group_daily_min),
+        sorted(&problem.group_daily_max),
+        sorted(&problem.subject_difficulty),
+    );
+    canonical.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The entries of `map` in key order.
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> BTreeMap<&K, &V> {
+    map.iter().collect()
+}
+
+/// Sets of lecturers with identical suitable subjects and suitabilities, budgets,
+/// unavailable hours, daily and in-a-row caps and hour preferences. Swapping two of them
+/// throughout a schedule leaves its fitness unchanged. Pinned lecturers are never part of
+/// one, as a swap would move their pins.
+pub fn interchangeable_lecturers(problem: &Problem) -> Vec<Vec<LecturerId>> {
+    let pinned: BTreeSet<_> = problem
+        .pinned
+        .iter()
+        .map(|&(_, _, lecturer, _)| lecturer)
+        .collect();
+    let mut subjects: BTreeMap<LecturerId, Vec<SubjectId>> = BTreeMap::new();
+    for (&subject, lecturers) in &problem.subject_requirements {
+        for &lecturer in lecturers
+            .iter()
+            .filter(|lecturer| !pinned.contains(lecturer))
+        {
+            subjects.entry(lecturer).or_default().push(subject);
+        }
+    }
+
+    let mut sets: BTreeMap<_, Vec<LecturerId>> = BTreeMap::new();
+    for (lecturer, mut taught) in subjects {
+        taught.sort();
+        let taught: Vec<_> = taught
+            .into_iter()
+            .map(|subject| (subject, problem.suitability(subject, lecturer)))
+            .collect();
+        let budget = problem
+            .lecturer_requirements
+            .get(&lecturer)
+            .copied()
+            .unwrap_or_default();
+        let unavailable: BTreeSet<usize> = problem
+            .lecturer_unavailable
+            .get(&lecturer)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect();
+        let preferences: BTreeMap<usize, i32> = problem
+            .lecturer_preferences
+            .get(&lecturer)
+            .into_iter()
+            .flatten()
+            .filter(|(_, &preference)| preference != 0)
+            .map(|(&hour, &preference)| (hour, preference))
+            .collect();
+        let key = (
+            taught,
+            budget,
+            unavailable,
+            problem.daily_cap(lecturer),
+            problem.max_consecutive.get(&lecturer).copied(),
+            preferences,
+        );
+        sets.entry(key).or_default().push(lecturer);
+    }
+    sets.into_values().filter(|set| set.len() > 1).collect()
+}
+
+/// Relabels each interchangeable set so its lecturers first appear in ascending order,
+/// mapping all symmetric variants of a schedule to the same genome.
+pub fn break_symmetry(genome: &mut Genome, sets: &[Vec<LecturerId>]) {
+    for set in sets {
+        let mut order: Vec<LecturerId> = vec![];
+        for dna in genome.iter() {
+            let lecturer = dna.0 .1;
+            if set.contains(&lecturer) && !order.contains(&lecturer) {
+                order.push(lecturer);
+            }
+        }
+        for dna in genome.iter_mut() {
+            if let Some(position) = order.iter().position(|&lecturer| lecturer == dna.0 .1) {
+                dna.0 .1 = set[position];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Group 0 with two hours of subject 0, only lecturer 0 teaches, and two hours of
+    /// subject 1, only lecturer 1 teaches, both lecturers available on the first day only.
+    fn first_day_problem() -> Problem {
+        let mut problem = Problem::new(
+            [(0, vec![(0, 2), (1, 2)])].into(),
+            [(0, 2), (1, 2)].into(),
+            [(0, vec![0]), (1, vec![1])].into(),
+        );
+        let later: HashSet<_> = (PERIODS_PER_DAY..problem.total_hours).collect();
+        problem.lecturer_unavailable = [(0, later.clone()), (1, later)].into();
+        problem
+    }
+
+    #[test]
+    fn lessons_that_fit_are_not_conflicts() {
+        assert!(unavoidable_lecturer_conflicts(&first_day_problem()).is_empty());
+    }
+
+    #[test]
+    fn daily_caps_overload_a_lecturer() {
+        let mut problem = first_day_problem();
+        problem.group_requirements.insert(0, vec![(0, 3)]);
+        problem.max_hours_per_day.insert(0, 2);
+        assert_eq!(
+            unavoidable_lecturer_conflicts(&problem),
+            [LecturerConflict {
+                lecturers: vec![0],
+                group: None,
+                lessons: vec![(0, 0, 3)],
+                forced_hours: 3,
+                open_hours: 2,
+            }]
+        );
+
+        problem.max_hours_per_day.clear();
+        problem.max_consecutive.insert(0, 1);
+        assert_eq!(unavoidable_lecturer_conflicts(&problem)[0].open_hours, 2);
+    }
+
+    #[test]
+    fn lecturers_sharing_a_group_overload_its_hours() {
+        let mut problem = first_day_problem();
+        problem.forbidden_periods.insert(0);
+        assert_eq!(
+            unavoidable_lecturer_conflicts(&problem),
+            [LecturerConflict {
+                lecturers: vec![0, 1],
+                group: Some(0),
+                lessons: vec![(0, 0, 2), (0, 1, 2)],
+                forced_hours: 4,
+                open_hours: 3,
+            }]
+        );
+
+        // In different groups the lessons no longer compete for the same hours.
+        problem.group_requirements = [(0, vec![(0, 2)]), (1, vec![(1, 2)])].into();
+        assert!(unavoidable_lecturer_conflicts(&problem).is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use genevo::prelude::FitnessFunction;
+
+use crate::{Context, Genome};
+
+/// Fitness values computed so far, keyed by a hash of the genome, with lookup counts.
+#[derive(Debug, Default)]
+pub struct FitnessCache {
+    values: Mutex<HashMap<u64, i64>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+/// How often a `FitnessCache` saved an evaluation.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheStats {
+    /// Share of lookups answered from the cache, in percent.
+    pub fn hit_rate(self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 * 100.0 / lookups as f64
+    }
+}
+
+impl FitnessCache {
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The fitness of `&Context`, remembered per genome so that individuals carried over by
+/// elitism aren't scored again. A `Mutex` rather than a `RefCell` guards the values, as
+/// genevo evaluates the population on several threads.
+#[derive(Debug, Clone, Copy)]
+pub struct CachingFitness<'a>(pub &'a Context, pub &'a FitnessCache);
+
+impl FitnessFunction<Genome, i64> for CachingFitness<'_> {
+    fn fitness_of(&self, genome: &Genome) -> i64 {
+        let mut hasher = DefaultHasher::new();
+        genome.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let cache = self.1;
+        if let Some(&fitness) = cache.values.lock().unwrap().get(&key) {
+            cache.hits.fetch_add(1, Ordering::Relaxed);
+            return fitness;
+        }
+        // Computed outside the lock, so that other threads aren't kept waiting meanwhile.
+        let fitness = self.0.fitness_of(genome);
+        cache.misses.fetch_add(1, Ordering::Relaxed);
+        cache.values.lock().unwrap().insert(key, fitness);
+        fitness
+    }
+
+    fn average(&self, values: &[i64]) -> i64 {
+        self.0.average(values)
+    }
+
+    fn highest_possible_fitness(&self) -> i64 {
+        self.0.highest_possible_fitness()
+    }
+
+    fn lowest_possible_fitness(&self) -> i64 {
+        self.0.lowest_possible_fitness()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use genevo::population::GenomeBuilder;
+
+    use super::*;
+    use crate::{dataset, InitHourDist, RandomScheduleBuilder};
+
+    #[test]
+    fn cached_fitness_agrees_with_the_context() {
+        let context = Context::new(dataset::medium_example());
+        let cache = FitnessCache::default();
+        let caching = CachingFitness(&context, &cache);
+        let mut rng = genevo::random::get_rng([3; 32]);
+        let genomes: Vec<_> = (0..20)
+            .map(|index| {
+                RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(index, &mut rng)
+            })
+            .collect();
+
+        for genome in genomes.iter().chain(&genomes) {
+            assert_eq!(caching.fitness_of(genome), (&context).fitness_of(genome));
+        }
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 20,
+                misses: 20
+            }
+        );
+    }
+}
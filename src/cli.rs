@@ -0,0 +1,636 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use islab3::feasible::Dedup;
+use islab3::hyper::Crossover;
+use islab3::i18n::Lang;
+use islab3::ics::Template;
+use islab3::solver::{
+    Evaluation, Selection, SolverParams, TieBreak, DEFAULT_MIGRATION_INTERVAL, MIN_POPULATION_SIZE,
+};
+use islab3::{FitnessWeights, InitHourDist, Mutate};
+
+const USAGE: &str = "usage: is-lab3 [selftest] [options], see --help";
+
+const HELP: &str = "usage: is-lab3 [selftest] [options]
+
+input:
+  selftest                      solve the small example with a fixed seed and hard constraint
+                                weights only, and check it reaches the highest fitness
+  --example {small,medium}      solve a built-in example instead of a constraints file
+  --constraints <path>          read the problem from this file (default constraints.json)
+  --weeks <n>                   repeat the week n times, lecturer hours are a per-week budget
+  --max-hours-per-day <n>       daily cap for lecturers without one in the constraints file
+  --max-period-per-day <n>      forbid the periods of every day after period n
+  --weights <name=n,...>        override fitness weights: lesson, group_clash, lecturer_clash,
+                                over_allocated_lecturer, room_clash, gaps, split_pair,
+                                daily_overload, moved_pin, preference, repeated_subject,
+                                long_run, suitability, early_start, rotation,
+                                lab_before_lecture, not_first, disliked_hour,
+                                room_change, daily_band, stacked_difficulty
+  --fitness-config <path>       read fitness weights from JSON, as --print-fitness-config
+                                writes them, before applying --weights
+  --print-fitness-config        print the fitness weights the run uses as JSON
+  --strict                      don't solve a problem that can't have a feasible schedule
+
+genetic algorithm:
+  --population-size <n>         individuals per generation, at least 6 (default 200)
+  --generations <n>             generation limit (default 100)
+  --mutation-rate <rate>        share of genes mutated per child, 0 to 1 (default 0.2)
+  --mutation-base <rate>        same as --mutation-rate, the rate adaptive mutation returns to
+  --mutation-max <rate>         raise the mutation rate up to this while the average fitness
+                                stagnates
+  --mutation-patience <n>       generations without a better average before the rate rises,
+                                with --mutation-max (default 5)
+  --seed <n>                    fixed seed for a reproducible run
+  --selection {maximize,tournament}
+                                how parents are picked (default maximize)
+  --selection-ratio <ratio>     parent sets selected per individual, above 0 up to 1
+                                (default 0.85)
+  --selection-count <n>         individuals per parent set, from 2 to the population size
+                                (default 20)
+  --tournament-size <n>         individuals per tournament, from 1 to the population size
+                                (default 4)
+  --elitism-ratio <ratio>       share of the population the best offspring replace each
+                                generation, above 0 up to 1, the rest being the best of the
+                                old population (default 0.85)
+  --stop-on-feasible            stop as soon as all hard constraints are satisfied
+  --soft-deadline <n>           weigh only hard constraints for the first n generations, the
+                                soft terms too after them, fewer than --generations and not
+                                with --islands
+  --tie-break <name,...>        among schedules of the best fitness report the one with the
+                                fewest gaps, or that finishes the groups' days earliest
+                                (finish), trying the objectives in order
+  --patience <n>                stop when the best fitness hasn't improved for n generations
+  --patience-epsilon <n>        with --patience, gains in the best fitness of at most n
+                                don't count as improvements (default 0)
+  --restarts <n>                run the whole search n times, seeded apart, and keep the best
+                                (default 1)
+  --islands <k>                 split the population into k islands that evolve apart and
+                                exchange their best schedules, not with --patience or
+                                --restarts (default 1)
+  --migration-interval <n>      generations between exchanges with --islands (default 10)
+  --feasibility-sweep <n>       instead of a schedule, solve n times, seeded apart, and print
+                                the median generation a feasible schedule took and how many
+                                runs never found one, not with --islands
+  --accept <path>               instead of solving from scratch, search around this schedule,
+                                written with --output-json and breaking no hard constraint,
+                                and report its fittest variation that breaks none either, not
+                                with --islands, --restarts or --feasibility-sweep
+  --variations <n>              with --accept, search around the schedule n times, seeded
+                                apart, and list how each variation differs (default 1)
+  --evaluation {auto,serial,parallel}
+                                threads for evaluation and breeding (default auto)
+  --mutate {both,hour,lecturer} which gene fields mutation changes (default both)
+  --init-hour-dist {uniform,spread}
+                                how the initial population picks hours (default uniform)
+  --crossover {single,uniform,multi}
+                                crossover operator, the first one with --hyper-heuristic
+                                (default uniform)
+  --crossover-points <n>        cuts of the multi-point crossover, at least 1 (default 3)
+  --break-symmetry              relabel interchangeable lecturers in offspring
+  --hyper-heuristic             pick the crossover per generation from its recent gains
+  --cache-fitness               reuse the fitness of genomes seen before and report the hit rate
+  --repair                      after solving, move lessons in a room too small or already
+                                taken to the smallest free room at that hour that fits
+
+output:
+  --lang {en,uk}                language of the printed schedule (default en)
+  -q, --quiet                   print only the schedule and errors, no progress or warnings
+  -v, --verbose                 also print debug diagnostics such as capacity and mutation rates
+                                (RUST_LOG overrides both)
+  --gene-contributions          rank the genes of the result by their fitness impact
+  --sessions                    list a group's lessons of a subject in periods in a row, with
+                                the same lecturer and room, as one line
+  --summary                     report lecturer, group and overall hour utilization, also in
+                                --output-json
+  --collect-feasible            list every distinct feasible schedule seen during the run
+  --dedup {exact,hour-permutations}
+                                when collected schedules count as duplicates (default exact)
+  --robustness                  report which assignments all feasible schedules agree on
+  --sqlite <path>               record the run and its generations in a SQLite database
+  --stats-csv <path>            write the best and average fitness per generation as CSV
+  --output-csv <path>           write the schedule as group,day,period,start,end,subject,
+                                lecturer,room CSV
+  --output-dir <dir>            write both printed tables to by_group.txt and by_lecturer.txt,
+                                and as CSV to by_group.csv and by_lecturer.csv, in dir
+  --output-json <path>          write the schedule and its fitness as JSON
+  --output-ics <path>           write the schedule as an iCalendar file
+  --week-start <YYYY-MM-DD>     date of the first calendar week (default this week's Monday)
+  --template <name>             clock times of the periods in the calendar, CSV, dashboard and
+                                FET exports: back-to-back (default, 45 minutes from 08:00),
+                                ukrainian-pair-schedule (pairs of two 45 minute halves from
+                                08:30) or school (45 minutes from 08:30 with 10 minute breaks)
+  --export-fet <path>           write the schedule as FET XML
+  --output-dot <path>           write which lecturers teach which groups as a Graphviz graph
+  --export-dashboard <path>     write linked group and lecturer views as JSON
+  --export-cnf <path>           write the hard constraints as DIMACS CNF
+  --export-proof <path>         write JSON evidence that the schedule is feasible
+  --lecturer-report <path>      write a JSON workload record per lecturer
+  --landscape <path>            write the fitness of every single-gene change as CSV
+  -h, --help                    show this help";
+
+/// A built-in problem to solve instead of a constraints file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Example {
+    Small,
+    Medium,
+}
+
+impl FromStr for Example {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "small" => Ok(Example::Small),
+            "medium" => Ok(Example::Medium),
+            other => Err(format!(
+                "unsupported example `{other}`, expected `small` or `medium`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub lang: Lang,
+    pub quiet: bool,   // log only errors to stderr.
+    pub verbose: bool, // log debug diagnostics to stderr.
+    pub example: Option<Example>,
+    pub constraints: Option<PathBuf>, // `constraints.json` in the working directory if `None`.
+    pub weeks: usize,
+    pub max_hours_per_day: Option<usize>, // overrides the problem's default daily cap.
+    pub max_period_per_day: Option<usize>, // the last period of every day lessons may take.
+    pub weights: Vec<(String, i64)>,      // overrides the problem's fitness weights by name.
+    pub fitness_config: Option<PathBuf>,  // JSON fitness weights replacing the problem's.
+    pub print_fitness_config: bool,       // print the effective fitness weights as JSON.
+    pub population_size: Option<usize>,
+    pub generations: Option<u64>,
+    pub mutation_rate: Option<f64>, // the base rate with adaptive mutation.
+    pub mutation_max: Option<f64>,  // enables adaptive mutation, up to this rate.
+    pub mutation_patience: Option<u64>, // stagnant generations before the rate rises.
+    pub selection: Selection,
+    pub selection_ratio: Option<f64>,
+    pub elitism_ratio: Option<f64>, // share of the population replaced by offspring.
+    pub selection_count: Option<usize>,
+    pub tournament_size: Option<usize>,
+    pub seed: Option<u64>, // fixed seed for a reproducible run, random otherwise.
+    pub selftest: bool,    // run the small example with a fixed seed and check the result.
+    pub strict: bool,      // refuse to solve a problem without a feasible schedule.
+    pub gene_contributions: bool, // rank the genes of the final genome by their fitness impact.
+    pub summary: bool,     // report how the final schedule uses lecturers and groups.
+    pub sessions: bool,    // list consecutive lessons of a subject as one by-group line.
+    pub collect_feasible: bool, // report every distinct feasible genome seen during the run.
+    pub output_csv: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>, // directory for both tables, as text and CSV.
+    pub output_json: Option<PathBuf>,
+    pub output_ics: Option<PathBuf>,
+    pub week_start: Option<NaiveDate>, // first day of the `--output-ics` calendar.
+    pub template: Template,            // clock times of the periods in the exports.
+    pub export_fet: Option<PathBuf>,
+    pub output_dot: Option<PathBuf>,
+    pub export_dashboard: Option<PathBuf>,
+    pub export_cnf: Option<PathBuf>,
+    pub export_proof: Option<PathBuf>, // JSON evidence that the schedule meets every hard constraint.
+    pub lecturer_report: Option<PathBuf>, // JSON workload record per lecturer.
+    pub landscape: Option<PathBuf>, // CSV of the fitness of every single-gene change to the result.
+    pub robustness: bool, // report which assignments all collected feasible schedules agree on.
+    pub sqlite: Option<PathBuf>, // database to record the run and its generations in.
+    pub stats_csv: Option<PathBuf>, // best and average fitness per generation.
+    pub evaluation: Evaluation,
+    pub dedup: Dedup, // when collected feasible schedules count as duplicates.
+    pub stop_on_feasible: bool, // stop as soon as all hard constraints are satisfied.
+    pub patience: Option<u64>, // stop after this many generations without improvement.
+    pub soft_deadline: Option<u64>, // generations weighing hard constraints only.
+    pub tie_break: Vec<TieBreak>, // objectives picking among equally fit schedules.
+    pub patience_epsilon: Option<u64>, // gains of at most this don't reset the patience.
+    pub restarts: usize, // independent runs, the best of which is kept.
+    pub islands: usize, // sub-populations exchanging their best genomes.
+    pub migration_interval: u64, // generations between exchanges between islands.
+    pub feasibility_sweep: Option<usize>, // runs to time reaching feasibility over.
+    pub accept: Option<PathBuf>, // a schedule to search for variations of.
+    pub variations: usize, // runs searching around the accepted schedule.
+    pub mutate: Mutate,
+    pub init_hour_dist: InitHourDist,
+    pub crossover: Crossover,
+    pub crossover_points: Option<usize>, // cuts of the multi-point crossover.
+    pub break_symmetry: bool, // canonically relabel interchangeable lecturers in offspring.
+    pub hyper_heuristic: bool, // pick the crossover operator per generation from its recent gains.
+    pub cache_fitness: bool,  // reuse the fitness of genomes seen before, reporting the hit rate.
+    pub repair: bool,         // move lessons out of rooms too small or taken after solving.
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            lang: Lang::default(),
+            quiet: false,
+            verbose: false,
+            example: None,
+            constraints: None,
+            weeks: 1,
+            max_hours_per_day: None,
+            max_period_per_day: None,
+            weights: Vec::new(),
+            fitness_config: None,
+            print_fitness_config: false,
+            population_size: None,
+            generations: None,
+            mutation_rate: None,
+            mutation_max: None,
+            mutation_patience: None,
+            selection: Selection::default(),
+            selection_ratio: None,
+            elitism_ratio: None,
+            selection_count: None,
+            tournament_size: None,
+            seed: None,
+            selftest: false,
+            strict: false,
+            gene_contributions: false,
+            summary: false,
+            sessions: false,
+            collect_feasible: false,
+            output_csv: None,
+            output_dir: None,
+            output_json: None,
+            output_ics: None,
+            week_start: None,
+            template: Template::default(),
+            export_fet: None,
+            output_dot: None,
+            export_dashboard: None,
+            export_cnf: None,
+            export_proof: None,
+            landscape: None,
+            lecturer_report: None,
+            stop_on_feasible: false,
+            patience: None,
+            soft_deadline: None,
+            tie_break: vec![],
+            patience_epsilon: None,
+            restarts: 1,
+            feasibility_sweep: None,
+            accept: None,
+            variations: 1,
+            islands: 1,
+            migration_interval: DEFAULT_MIGRATION_INTERVAL,
+            robustness: false,
+            sqlite: None,
+            stats_csv: None,
+            evaluation: Evaluation::default(),
+            dedup: Dedup::default(),
+            mutate: Mutate::default(),
+            init_hour_dist: InitHourDist::default(),
+            crossover: Crossover::default(),
+            crossover_points: None,
+            break_symmetry: false,
+            hyper_heuristic: false,
+            cache_fitness: false,
+            repair: false,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the process arguments, exiting with a usage message on error.
+    pub fn parse() -> Self {
+        match Self::try_parse(std::env::args().skip(1)) {
+            Ok(args) => args,
+            Err(error) => {
+                eprintln!("error: {error}\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    fn try_parse(mut iter: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = Args::default();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--lang" => args.lang = value_of(&arg, iter.next())?.parse()?,
+                "--weeks" => {
+                    args.weeks = parse_number(&arg, iter.next())?;
+                    if args.weeks == 0 {
+                        return Err("`--weeks` must be at least 1".to_string());
+                    }
+                }
+                "--max-hours-per-day" => {
+                    args.max_hours_per_day = Some(parse_number(&arg, iter.next())?)
+                }
+                "--max-period-per-day" => {
+                    args.max_period_per_day = Some(parse_number(&arg, iter.next())?)
+                }
+                "--weights" => {
+                    for pair in value_of(&arg, iter.next())?.split(',') {
+                        args.weights.push(parse_weight(pair)?);
+                    }
+                }
+                "--fitness-config" => {
+                    args.fitness_config = Some(value_of(&arg, iter.next())?.into())
+                }
+                "--print-fitness-config" => args.print_fitness_config = true,
+                "--example" => args.example = Some(value_of(&arg, iter.next())?.parse()?),
+                "--constraints" => args.constraints = Some(value_of(&arg, iter.next())?.into()),
+                "--population-size" => {
+                    let size: usize = parse_number(&arg, iter.next())?;
+                    if size < MIN_POPULATION_SIZE {
+                        return Err(format!(
+                            "`--population-size` must be at least {MIN_POPULATION_SIZE}"
+                        ));
+                    }
+                    args.population_size = Some(size);
+                }
+                "--generations" => {
+                    let generations: u64 = parse_number(&arg, iter.next())?;
+                    if generations == 0 {
+                        return Err("`--generations` must be at least 1".to_string());
+                    }
+                    args.generations = Some(generations);
+                }
+                "--mutation-rate" | "--mutation-base" => {
+                    args.mutation_rate = Some(parse_rate(&arg, iter.next())?)
+                }
+                "--mutation-max" => args.mutation_max = Some(parse_rate(&arg, iter.next())?),
+                "--mutation-patience" => {
+                    let patience: u64 = parse_number(&arg, iter.next())?;
+                    if patience == 0 {
+                        return Err("`--mutation-patience` must be at least 1".to_string());
+                    }
+                    args.mutation_patience = Some(patience);
+                }
+                "--selection" => args.selection = value_of(&arg, iter.next())?.parse()?,
+                "--selection-ratio" => {
+                    let value = value_of(&arg, iter.next())?;
+                    match value.parse() {
+                        Ok(ratio) if ratio > 0.0 && ratio <= 1.0 => {
+                            args.selection_ratio = Some(ratio)
+                        }
+                        _ => {
+                            return Err(format!(
+                            "`--selection-ratio` expects a number above 0 up to 1, got `{value}`"
+                        ))
+                        }
+                    }
+                }
+                "--elitism-ratio" => {
+                    let value = value_of(&arg, iter.next())?;
+                    match value.parse() {
+                        Ok(ratio) if ratio > 0.0 && ratio <= 1.0 => {
+                            args.elitism_ratio = Some(ratio)
+                        }
+                        _ => {
+                            return Err(format!(
+                                "`--elitism-ratio` expects a number above 0 up to 1, got `{value}`"
+                            ))
+                        }
+                    }
+                }
+                "--selection-count" => {
+                    let count: usize = parse_number(&arg, iter.next())?;
+                    if count < 2 {
+                        return Err("`--selection-count` must be at least 2".to_string());
+                    }
+                    args.selection_count = Some(count);
+                }
+                "--tournament-size" => {
+                    let size: usize = parse_number(&arg, iter.next())?;
+                    if size == 0 {
+                        return Err("`--tournament-size` must be at least 1".to_string());
+                    }
+                    args.tournament_size = Some(size);
+                }
+                "--seed" => args.seed = Some(parse_number(&arg, iter.next())?),
+                "selftest" => args.selftest = true,
+                "--strict" => args.strict = true,
+                "--gene-contributions" => args.gene_contributions = true,
+                "--summary" => args.summary = true,
+                "--sessions" => args.sessions = true,
+                "-q" | "--quiet" => args.quiet = true,
+                "-v" | "--verbose" => args.verbose = true,
+                "--collect-feasible" => args.collect_feasible = true,
+                // Robustness is computed from the collected feasible schedules.
+                "--robustness" => {
+                    args.robustness = true;
+                    args.collect_feasible = true;
+                }
+                "--stop-on-feasible" => args.stop_on_feasible = true,
+                "--patience" => {
+                    let patience: u64 = parse_number(&arg, iter.next())?;
+                    if patience == 0 {
+                        return Err("`--patience` must be at least 1".to_string());
+                    }
+                    args.patience = Some(patience);
+                }
+                "--soft-deadline" => {
+                    let deadline: u64 = parse_number(&arg, iter.next())?;
+                    if deadline == 0 {
+                        return Err("`--soft-deadline` must be at least 1".to_string());
+                    }
+                    args.soft_deadline = Some(deadline);
+                }
+                "--tie-break" => {
+                    args.tie_break = value_of(&arg, iter.next())?
+                        .split(',')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?;
+                }
+                "--patience-epsilon" => {
+                    args.patience_epsilon = Some(parse_number(&arg, iter.next())?);
+                }
+                "--islands" => {
+                    args.islands = parse_number(&arg, iter.next())?;
+                    if args.islands == 0 {
+                        return Err("`--islands` must be at least 1".to_string());
+                    }
+                }
+                "--feasibility-sweep" => {
+                    let runs = parse_number(&arg, iter.next())?;
+                    if runs == 0 {
+                        return Err("`--feasibility-sweep` must be at least 1".to_string());
+                    }
+                    args.feasibility_sweep = Some(runs);
+                }
+                "--accept" => args.accept = Some(value_of(&arg, iter.next())?.into()),
+                "--variations" => {
+                    args.variations = parse_number(&arg, iter.next())?;
+                    if args.variations == 0 {
+                        return Err("`--variations` must be at least 1".to_string());
+                    }
+                }
+                "--migration-interval" => {
+                    args.migration_interval = parse_number(&arg, iter.next())?;
+                    if args.migration_interval == 0 {
+                        return Err("`--migration-interval` must be at least 1".to_string());
+                    }
+                }
+                "--restarts" => {
+                    args.restarts = parse_number(&arg, iter.next())?;
+                    if args.restarts == 0 {
+                        return Err("`--restarts` must be at least 1".to_string());
+                    }
+                }
+                "--hyper-heuristic" => args.hyper_heuristic = true,
+                "--cache-fitness" => args.cache_fitness = true,
+                "--repair" => args.repair = true,
+                "--break-symmetry" => args.break_symmetry = true,
+                "--init-hour-dist" => args.init_hour_dist = value_of(&arg, iter.next())?.parse()?,
+                "--crossover" => args.crossover = value_of(&arg, iter.next())?.parse()?,
+                "--crossover-points" => {
+                    let points: usize = parse_number(&arg, iter.next())?;
+                    if points == 0 {
+                        return Err("`--crossover-points` must be at least 1".to_string());
+                    }
+                    args.crossover_points = Some(points);
+                }
+                "--mutate" => args.mutate = value_of(&arg, iter.next())?.parse()?,
+                "--sqlite" => args.sqlite = Some(value_of(&arg, iter.next())?.into()),
+                "--stats-csv" => args.stats_csv = Some(value_of(&arg, iter.next())?.into()),
+                "--evaluation" => args.evaluation = value_of(&arg, iter.next())?.parse()?,
+                "--dedup" => args.dedup = value_of(&arg, iter.next())?.parse()?,
+                "--export-dashboard" => {
+                    args.export_dashboard = Some(value_of(&arg, iter.next())?.into())
+                }
+                "--export-cnf" => args.export_cnf = Some(value_of(&arg, iter.next())?.into()),
+                "--output-csv" => args.output_csv = Some(value_of(&arg, iter.next())?.into()),
+                "--output-dir" => args.output_dir = Some(value_of(&arg, iter.next())?.into()),
+                "--output-json" => args.output_json = Some(value_of(&arg, iter.next())?.into()),
+                "--output-ics" => args.output_ics = Some(value_of(&arg, iter.next())?.into()),
+                "--week-start" => {
+                    let value = value_of(&arg, iter.next())?;
+                    let date = value.parse().map_err(|_| {
+                        format!("`--week-start` expects a YYYY-MM-DD date, got `{value}`")
+                    })?;
+                    args.week_start = Some(date);
+                }
+                "--template" => args.template = value_of(&arg, iter.next())?.parse()?,
+                "--export-proof" => args.export_proof = Some(value_of(&arg, iter.next())?.into()),
+                "--export-fet" => args.export_fet = Some(value_of(&arg, iter.next())?.into()),
+                "--output-dot" => args.output_dot = Some(value_of(&arg, iter.next())?.into()),
+                "--lecturer-report" => {
+                    args.lecturer_report = Some(value_of(&arg, iter.next())?.into())
+                }
+                "--landscape" => args.landscape = Some(value_of(&arg, iter.next())?.into()),
+                "-h" | "--help" => {
+                    println!("{HELP}");
+                    std::process::exit(0);
+                }
+                other => return Err(format!("unexpected argument `{other}`")),
+            }
+        }
+
+        if args.accept.is_some() {
+            if args.islands > 1 || args.restarts > 1 || args.feasibility_sweep.is_some() {
+                return Err(
+                    "`--accept` cannot be combined with `--islands`, `--restarts` or \
+                     `--feasibility-sweep`"
+                        .to_string(),
+                );
+            }
+        } else if args.variations > 1 {
+            return Err("`--variations` needs `--accept`".to_string());
+        }
+        let defaults = SolverParams::default();
+        let population_size = args.population_size.unwrap_or(defaults.population_size);
+        if args.islands > 1 {
+            if population_size / args.islands < MIN_POPULATION_SIZE {
+                return Err(format!(
+                    "`--islands` leaves fewer than {MIN_POPULATION_SIZE} individuals per island \
+                     of a population of {population_size}"
+                ));
+            }
+            if args.patience.is_some() || args.restarts > 1 || args.soft_deadline.is_some() {
+                return Err(
+                    "`--islands` cannot be combined with `--patience`, `--restarts` or \
+                            `--soft-deadline`"
+                        .to_string(),
+                );
+            }
+            if args.feasibility_sweep.is_some() {
+                return Err("`--islands` cannot be combined with `--feasibility-sweep`".to_string());
+            }
+        }
+        for (flag, value) in [
+            ("--selection-count", args.selection_count),
+            ("--tournament-size", args.tournament_size),
+        ] {
+            if value.is_some_and(|value| value > population_size) {
+                return Err(format!(
+                    "`{flag}` must be at most the population size, {population_size}"
+                ));
+            }
+        }
+        if let Some(max) = args.mutation_max {
+            let base = args.mutation_rate.unwrap_or(defaults.mutation_rate);
+            if max < base {
+                return Err(format!(
+                    "`--mutation-max` must be at least the base mutation rate, {base}"
+                ));
+            }
+        } else if args.mutation_patience.is_some() {
+            return Err("`--mutation-patience` requires `--mutation-max`".to_string());
+        }
+        let generations = args.generations.unwrap_or(defaults.generation_limit);
+        if args
+            .soft_deadline
+            .is_some_and(|deadline| deadline >= generations)
+        {
+            return Err(format!(
+                "`--soft-deadline` must be below the generation limit, {generations}"
+            ));
+        }
+        if args.patience_epsilon.is_some() && args.patience.is_none() {
+            return Err("`--patience-epsilon` requires `--patience`".to_string());
+        }
+        if args.quiet && args.verbose {
+            return Err("`--quiet` and `--verbose` cannot be combined".to_string());
+        }
+        if args.example.is_some() && args.constraints.is_some() {
+            return Err("`--example` and `--constraints` cannot be combined".to_string());
+        }
+        if args.selftest && (args.example.is_some() || args.constraints.is_some()) {
+            return Err(
+                "`selftest` always solves the small example, drop `--example` and `--constraints`"
+                    .to_string(),
+            );
+        }
+        Ok(args)
+    }
+}
+
+fn value_of(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("`{flag}` requires a value"))
+}
+
+/// A mutation rate from 0 to 1.
+fn parse_rate(flag: &str, value: Option<String>) -> Result<f64, String> {
+    let value = value_of(flag, value)?;
+    match value.parse() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!(
+            "`{flag}` expects a number from 0 to 1, got `{value}`"
+        )),
+    }
+}
+
+/// A `name=value` pair of `--weights`.
+fn parse_weight(pair: &str) -> Result<(String, i64), String> {
+    let (name, value) = pair
+        .split_once('=')
+        .ok_or_else(|| format!("`--weights` expects name=value pairs, got `{pair}`"))?;
+    let weight = value
+        .parse()
+        .map_err(|_| format!("weight `{name}` expects a non-negative integer, got `{value}`"))?;
+    FitnessWeights::default()
+        .set(name, weight)
+        .map_err(|error| error.to_string())?;
+    Ok((name.to_string(), weight))
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value_of(flag, value)?;
+    value
+        .parse()
+        .map_err(|_| format!("`{flag}` expects a non-negative integer, got `{value}`"))
+}
@@ -0,0 +1,299 @@
+use std::fmt::Write;
+
+use crate::{Context, PERIODS_PER_DAY};
+
+/// Clauses over DIMACS variables, which are numbered from 1.
+#[derive(Debug, Default)]
+struct Cnf {
+    variables: usize,
+    clauses: Vec<Vec<i64>>,
+}
+
+impl Cnf {
+    fn new_variable(&mut self) -> i64 {
+        self.variables += 1;
+        self.variables as i64
+    }
+
+    /// At most `k` of `literals` are true, using the sequential counter encoding so the
+    /// size stays linear in `literals.len() * k`.
+    fn at_most(&mut self, literals: &[i64], k: usize) {
+        let n = literals.len();
+        if n <= k {
+            return;
+        }
+        if k == 0 {
+            self.clauses
+                .extend(literals.iter().map(|literal| vec![-literal]));
+            return;
+        }
+
+        // `counter[i][j]` is true if at least `j + 1` of the first `i + 1` literals are true.
+        let counter: Vec<Vec<i64>> = (0..n - 1)
+            .map(|_| (0..k).map(|_| self.new_variable()).collect())
+            .collect();
+
+        self.clauses.push(vec![-literals[0], counter[0][0]]);
+        for bit in &counter[0][1..] {
+            self.clauses.push(vec![-bit]);
+        }
+        for i in 1..n - 1 {
+            self.clauses.push(vec![-literals[i], counter[i][0]]);
+            self.clauses.push(vec![-counter[i - 1][0], counter[i][0]]);
+            for j in 1..k {
+                self.clauses
+                    .push(vec![-literals[i], -counter[i - 1][j - 1], counter[i][j]]);
+                self.clauses.push(vec![-counter[i - 1][j], counter[i][j]]);
+            }
+            self.clauses
+                .push(vec![-literals[i], -counter[i - 1][k - 1]]);
+        }
+        self.clauses
+            .push(vec![-literals[n - 1], -counter[n - 2][k - 1]]);
+    }
+}
+
+/// Encodes the hard constraints as a DIMACS CNF formula that is satisfiable exactly when a
+/// schedule satisfying all of them exists.
+///
+/// Variable `x(gene, lecturer, hour)` means the lesson of that gene is taught by that
+/// suitable lecturer at that hour of its week; the mapping is listed in `c var` comments.
+/// The clauses say that every lesson gets exactly one (lecturer, hour), a group and a
+/// lecturer are in at most one lesson per hour, and a lecturer teaches at most their
+/// budget per week, at most their daily cap per day and no more periods in a row than
+/// their `max_consecutive`, and the second half of a double lesson directly follows the
+/// first on the same day. Instead of variables per room, if the problem has rooms there
+/// are at most `room_count` lessons per hour, and per group size at most as many lessons
+/// of groups that big as rooms seating them. Lecturers get no variables for the hours they
+/// are unavailable at, and a pinned lesson only gets its pinned choice.
+pub fn to_dimacs(context: &Context) -> String {
+    let problem = &context.problem;
+    let group_subjects = &context.group_subjects;
+    let genes_per_week = group_subjects.len() / problem.weeks;
+    let week_hours = problem.total_hours;
+    let hours = week_hours * problem.weeks;
+    let lecturers = problem.lecturer_count();
+
+    let mut cnf = Cnf::default();
+    let mut mapping = String::new();
+    let mut by_group_hour = vec![vec![]; problem.group_count() * hours];
+    let mut by_lecturer_hour = vec![vec![]; lecturers * hours];
+    let mut by_hour = vec![vec![]; hours];
+    let mut by_gene_hour = vec![vec![]; group_subjects.len() * hours];
+
+    for (index, (group, subject)) in group_subjects.iter().enumerate() {
+        let week = index / genes_per_week;
+        let mut choices = vec![];
+        for lecturer in &problem.subject_requirements[subject] {
+            for hour in week * week_hours..(week + 1) * week_hours {
+                let moves_pin = context
+                    .pin(index)
+                    .is_some_and(|(pinned, slot)| (pinned, slot.index()) != (*lecturer, hour));
+                if !problem.is_available(*lecturer, hour % week_hours) || moves_pin {
+                    continue;
+                }
+                let variable = cnf.new_variable();
+                writeln!(
+                    mapping,
+                    "c var {variable} gene {index} lecturer {lecturer} hour {hour}"
+                )
+                .unwrap();
+                choices.push(variable);
+                by_group_hour[group * hours + hour].push(variable);
+                by_lecturer_hour[lecturer * hours + hour].push(variable);
+                by_hour[hour].push(variable);
+                by_gene_hour[index * hours + hour].push(variable);
+            }
+        }
+        cnf.clauses.push(choices.clone());
+        cnf.at_most(&choices, 1);
+    }
+
+    // The first half of a double lesson at some hour needs the second at the next period.
+    for &first in &context.pairs {
+        for hour in 0..hours {
+            let next: &[i64] = if (hour + 1) % PERIODS_PER_DAY == 0 {
+                &[]
+            } else {
+                &by_gene_hour[(first + 1) * hours + hour + 1]
+            };
+            for &variable in &by_gene_hour[first * hours + hour] {
+                let mut clause = vec![-variable];
+                clause.extend(next);
+                cnf.clauses.push(clause);
+            }
+        }
+    }
+
+    for literals in by_group_hour.iter().chain(&by_lecturer_hour) {
+        cnf.at_most(literals, 1);
+    }
+
+    if problem.room_count > 0 {
+        for literals in &by_hour {
+            cnf.at_most(literals, problem.room_count);
+        }
+        // A group fits every room seating at least its size, so the rooms one group fits
+        // include those of any larger group and a room per lesson exists exactly when, for
+        // every size, the lessons of groups at least that big fit in the rooms seating them.
+        let mut sizes: Vec<usize> = problem.group_sizes.values().copied().collect();
+        sizes.sort();
+        sizes.dedup();
+        for size in sizes {
+            let rooms = (0..problem.room_count)
+                .filter(|room| {
+                    problem
+                        .room_capacities
+                        .get(room)
+                        .is_none_or(|capacity| *capacity >= size)
+                })
+                .count();
+            let large: Vec<bool> = (0..problem.group_count())
+                .map(|group| {
+                    problem
+                        .group_sizes
+                        .get(&group)
+                        .is_some_and(|group_size| *group_size >= size)
+                })
+                .collect();
+            for hour in 0..hours {
+                let literals: Vec<i64> = (0..problem.group_count())
+                    .filter(|group| large[*group])
+                    .flat_map(|group| by_group_hour[group * hours + hour].iter().copied())
+                    .collect();
+                cnf.at_most(&literals, rooms);
+            }
+        }
+    }
+
+    for lecturer in 0..lecturers {
+        let budget = problem
+            .lecturer_requirements
+            .get(&lecturer)
+            .copied()
+            .unwrap_or_default();
+        for week in 0..problem.weeks {
+            let literals: Vec<i64> = (week * week_hours..(week + 1) * week_hours)
+                .flat_map(|hour| by_lecturer_hour[lecturer * hours + hour].iter().copied())
+                .collect();
+            cnf.at_most(&literals, budget);
+        }
+    }
+
+    for lecturer in 0..lecturers {
+        let Some(cap) = problem.daily_cap(lecturer) else {
+            continue;
+        };
+        for day_start in (0..hours).step_by(PERIODS_PER_DAY) {
+            let literals: Vec<i64> = (day_start..day_start + PERIODS_PER_DAY)
+                .flat_map(|hour| by_lecturer_hour[lecturer * hours + hour].iter().copied())
+                .collect();
+            cnf.at_most(&literals, cap);
+        }
+    }
+
+    // A lecturer has at most one lesson per hour, so at most `cap` of the lessons in each
+    // window of `cap + 1` periods of a day keep them from teaching all of those periods.
+    for (&lecturer, &cap) in &problem.max_consecutive {
+        if cap >= PERIODS_PER_DAY || lecturer >= lecturers {
+            continue;
+        }
+        for day_start in (0..hours).step_by(PERIODS_PER_DAY) {
+            for start in day_start..=day_start + PERIODS_PER_DAY - (cap + 1) {
+                let literals: Vec<i64> = (start..=start + cap)
+                    .flat_map(|hour| by_lecturer_hour[lecturer * hours + hour].iter().copied())
+                    .collect();
+                cnf.at_most(&literals, cap);
+            }
+        }
+    }
+
+    let mut dimacs = mapping;
+    writeln!(dimacs, "p cnf {} {}", cnf.variables, cnf.clauses.len()).unwrap();
+    for clause in &cnf.clauses {
+        for literal in clause {
+            write!(dimacs, "{literal} ").unwrap();
+        }
+        writeln!(dimacs, "0").unwrap();
+    }
+    dimacs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Problem;
+
+    /// The clauses of a DIMACS file, checking them against its `p cnf` line.
+    fn parse(dimacs: &str) -> Vec<Vec<i64>> {
+        let mut header = None;
+        let mut clauses = vec![];
+        for line in dimacs.lines().filter(|line| !line.starts_with('c')) {
+            if let Some(counts) = line.strip_prefix("p cnf ") {
+                header = Some(counts.to_string());
+                continue;
+            }
+            let literals: Vec<i64> = line.split(' ').map(|x| x.parse().unwrap()).collect();
+            assert_eq!(literals.last(), Some(&0), "clauses end in 0");
+            clauses.push(literals[..literals.len() - 1].to_vec());
+        }
+        let header = header.expect("a `p cnf` line");
+        let (variables, count) = header.split_once(' ').unwrap();
+        let highest = clauses.iter().flatten().map(|x| x.abs()).max().unwrap_or(0);
+        assert!(highest <= variables.parse().unwrap());
+        assert_eq!(clauses.len(), count.parse::<usize>().unwrap());
+        clauses
+    }
+
+    /// Whether some assignment satisfies all `clauses`, by DPLL branching on the literals of
+    /// the shortest clause, which propagates unit clauses first.
+    fn satisfiable(clauses: &[Vec<i64>]) -> bool {
+        let Some(shortest) = clauses.iter().min_by_key(|clause| clause.len()) else {
+            return true;
+        };
+        let Some(&literal) = shortest.first() else {
+            return false;
+        };
+        [literal, -literal].into_iter().any(|literal| {
+            let rest: Vec<Vec<i64>> = clauses
+                .iter()
+                .filter(|clause| !clause.contains(&literal))
+                .map(|clause| clause.iter().copied().filter(|x| *x != -literal).collect())
+                .collect();
+            satisfiable(&rest)
+        })
+    }
+
+    /// One group with `hours` lessons of a subject only lecturer 0 teaches, in one day.
+    fn one_day_problem(hours: usize, budget: usize) -> Problem {
+        let mut problem = Problem::new(
+            [(0, vec![(0, hours)])].into(),
+            [(0, budget)].into(),
+            [(0, vec![0])].into(),
+        );
+        problem.total_hours = PERIODS_PER_DAY;
+        problem
+    }
+
+    #[test]
+    fn feasible_problems_are_satisfiable() {
+        let problem = one_day_problem(2, 2);
+        let clauses = parse(&to_dimacs(&Context::new(problem)));
+        assert!(satisfiable(&clauses));
+    }
+
+    #[test]
+    fn infeasible_problems_are_unsatisfiable() {
+        // Two lessons against a budget of one.
+        let problem = one_day_problem(2, 1);
+        assert!(!satisfiable(&parse(&to_dimacs(&Context::new(problem)))));
+
+        // A whole day of lessons for a lecturer capped at one period fewer in a row.
+        let mut problem = one_day_problem(PERIODS_PER_DAY, PERIODS_PER_DAY);
+        assert!(satisfiable(&parse(&to_dimacs(&Context::new(
+            problem.clone()
+        )))));
+        problem.max_consecutive = [(0, PERIODS_PER_DAY - 1)].into();
+        assert!(!satisfiable(&parse(&to_dimacs(&Context::new(problem)))));
+    }
+}
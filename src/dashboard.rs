@@ -0,0 +1,77 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{json, Value};
+
+use crate::ics::Template;
+use crate::{Context, Genome};
+
+/// Builds a single JSON document with the group-centric and lecturer-centric views of a
+/// schedule. Every lesson gets a class id (its gene index) listed once under `classes`, and
+/// both views refer to lessons only by that id, so a frontend can highlight a class in every
+/// view it appears in. Classes carry the `start` and `end` of their period in `template`, and
+/// groups, subjects and lecturers with a display name get a `*_name` field next to their id.
+pub fn to_dashboard(context: &Context, genome: &Genome, template: Template) -> Value {
+    let problem = &context.problem;
+    let group_subjects = &context.group_subjects;
+
+    let mut classes = vec![];
+    let mut by_group: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+    let mut by_lecturer: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+    for (id, ((group, subject), dna)) in group_subjects.iter().zip(genome).enumerate() {
+        let (_, lecturer, room, slot) = dna.0;
+        let hour = slot.index();
+        let (start, end) = template.period_times(slot.period);
+        let mut class = json!({
+            "id": id,
+            "group": group,
+            "subject": subject,
+            "lecturer": lecturer,
+            "room": room,
+            "hour": hour,
+            "day": slot.day,
+            "period": slot.period,
+            "start": start.format("%H:%M").to_string(),
+            "end": end.format("%H:%M").to_string(),
+        });
+        name(&mut class, "group", &problem.group_names, *group);
+        name(&mut class, "subject", &problem.subject_names, *subject);
+        name(&mut class, "lecturer", &problem.lecturer_names, lecturer);
+        classes.push(class);
+        by_group.entry(*group).or_default().push((hour, id));
+        by_lecturer.entry(lecturer).or_default().push((hour, id));
+    }
+
+    json!({
+        "classes": classes,
+        "views": {
+            "groups": view("group", &problem.group_names, by_group),
+            "lecturers": view("lecturer", &problem.lecturer_names, by_lecturer),
+        },
+    })
+}
+
+fn view(
+    key: &str,
+    names: &HashMap<usize, String>,
+    rows: BTreeMap<usize, Vec<(usize, usize)>>,
+) -> Value {
+    rows.into_iter()
+        .map(|(owner, mut slots)| {
+            slots.sort_unstable();
+            let slots: Vec<_> = slots
+                .into_iter()
+                .map(|(hour, class)| json!({ "hour": hour, "class": class }))
+                .collect();
+            let mut row = json!({ key: owner, "slots": slots });
+            name(&mut row, key, names, owner);
+            row
+        })
+        .collect()
+}
+
+/// Adds `<key>_name` to `object` if `id` has a display name.
+fn name(object: &mut Value, key: &str, names: &HashMap<usize, String>, id: usize) {
+    if let Some(name) = names.get(&id) {
+        object[format!("{key}_name")] = json!(name);
+    }
+}
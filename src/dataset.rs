@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use genevo::prelude::*;
+use genevo::random::get_rng;
+
+use crate::{LecturerId, Problem, SubjectId};
+
+const SEED: Seed = [7; 32];
+const GROUPS: usize = 10;
+const SUBJECTS: usize = 15;
+const LECTURERS: usize = 8;
+const SUBJECTS_PER_GROUP: usize = 4;
+const ROOMS: usize = 8;
+
+/// The small hand-written example, also used by `selftest`.
+pub fn small_example() -> Problem {
+    let group_requirements = vec![
+        (0_usize, vec![(0_usize, 2_usize), (1, 5), (2, 2), (3, 1)]), // 10
+        (1_usize, vec![(0_usize, 1_usize), (3, 2), (4, 6), (2, 1)]), // 10
+        (2_usize, vec![(0_usize, 1_usize), (2, 8), (3, 1)]),         // 10
+    ]
+    .into_iter()
+    .collect();
+    let lecturer_requirements = vec![(0_usize, 6_usize), (1, 6), (2, 10), (3, 4), (4, 4)]
+        .into_iter()
+        .collect();
+    let subject_requirements = vec![
+        (0_usize, vec![3_usize]),
+        (1, vec![0, 2]),
+        (2, vec![0, 1]),
+        (3, vec![4]),
+        (4, vec![1, 2]),
+    ]
+    .into_iter()
+    .collect();
+    Problem::new(
+        group_requirements,
+        lecturer_requirements,
+        subject_requirements,
+    )
+}
+
+/// A fixed, non-trivial problem generated from a constant seed, so benchmarks and tests can
+/// share one canonical instance that doesn't depend on `constraints.json`.
+///
+/// Subjects are spread round-robin over lecturers with one random backup each, every lesson
+/// is planned for its least loaded suitable lecturer, and budgets are that planned load plus
+/// a little slack, so a perfect schedule is not ruled out by budgets. Groups of 15 to 35
+/// students share rooms seating 20 to 55, enough for the about 6 lessons of each hour.
+pub fn medium_example() -> Problem {
+    let mut rng = get_rng(SEED);
+
+    let subject_requirements: HashMap<SubjectId, Vec<LecturerId>> = (0..SUBJECTS)
+        .map(|subject| {
+            let primary = subject % LECTURERS;
+            let secondary = (primary + rng.gen_range(1..LECTURERS)) % LECTURERS;
+            (subject, vec![primary, secondary])
+        })
+        .collect();
+
+    let mut planned_load = vec![0usize; LECTURERS];
+    let group_requirements = (0..GROUPS)
+        .map(|group| {
+            let mut subjects: Vec<SubjectId> = (0..SUBJECTS).collect();
+            let reqs = (0..SUBJECTS_PER_GROUP)
+                .map(|_| {
+                    let subject = subjects.swap_remove(rng.gen_range(0..subjects.len()));
+                    let hours = rng.gen_range(2..=4);
+                    let lecturer = subject_requirements[&subject]
+                        .iter()
+                        .min_by_key(|lecturer| planned_load[**lecturer])
+                        .unwrap();
+                    planned_load[*lecturer] += hours;
+                    (subject, hours)
+                })
+                .collect();
+            (group, reqs)
+        })
+        .collect();
+
+    let lecturer_requirements = planned_load
+        .into_iter()
+        .enumerate()
+        .map(|(lecturer, load)| (lecturer, load + 2))
+        .collect();
+
+    let mut problem = Problem::new(
+        group_requirements,
+        lecturer_requirements,
+        subject_requirements,
+    );
+    problem.room_count = ROOMS;
+    problem.group_sizes = (0..GROUPS)
+        .map(|group| (group, rng.gen_range(15..=35)))
+        .collect();
+    problem.room_capacities = (0..ROOMS).map(|room| (room, 20 + 5 * room)).collect();
+    problem
+}
@@ -0,0 +1,87 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::schedule::ScheduledLesson;
+use crate::{GroupId, LecturerId, Problem, SubjectId};
+
+/// Exports who teaches whom as a Graphviz DOT graph: lecturers in one column, groups in the
+/// other, and one edge per lecturer and group pair. Several subjects of the same pair share
+/// that edge, labeled with each subject and its hours over all weeks, e.g. `Algebra (2h), 5 (1h)`.
+/// Nodes are named as `problem` names them, by id if it has no name.
+pub fn to_dot(problem: &Problem, schedule: &[ScheduledLesson]) -> String {
+    let mut edges: BTreeMap<(LecturerId, GroupId), BTreeMap<SubjectId, usize>> = BTreeMap::new();
+    for lesson in schedule {
+        *edges
+            .entry((lesson.lecturer, lesson.group))
+            .or_default()
+            .entry(lesson.subject)
+            .or_default() += 1;
+    }
+    let lecturers: BTreeSet<_> = edges.keys().map(|(lecturer, _)| *lecturer).collect();
+    let groups: BTreeSet<_> = edges.keys().map(|(_, group)| *group).collect();
+
+    let mut dot = String::new();
+    writeln!(dot, "graph assignments {{").unwrap();
+    writeln!(dot, "  rankdir=LR;").unwrap();
+    writeln!(dot, "  subgraph lecturers {{").unwrap();
+    writeln!(dot, "    rank=same; node [shape=box];").unwrap();
+    for lecturer in lecturers {
+        let label = quoted(&problem.lecturer_label(lecturer));
+        writeln!(dot, "    L{lecturer} [label={label}];").unwrap();
+    }
+    writeln!(dot, "  }}").unwrap();
+    writeln!(dot, "  subgraph groups {{").unwrap();
+    writeln!(dot, "    rank=same; node [shape=ellipse];").unwrap();
+    for group in groups {
+        let label = quoted(&problem.group_label(group));
+        writeln!(dot, "    G{group} [label={label}];").unwrap();
+    }
+    writeln!(dot, "  }}").unwrap();
+    for ((lecturer, group), subjects) in edges {
+        let subjects: Vec<_> = subjects
+            .into_iter()
+            .map(|(subject, hours)| format!("{} ({}h)", problem.subject_label(subject), hours))
+            .collect();
+        let label = quoted(&subjects.join(", "));
+        writeln!(dot, "  L{lecturer} -- G{group} [label={label}];").unwrap();
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+/// A DOT quoted string.
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::TimeSlot;
+
+    #[test]
+    fn merges_the_subjects_of_a_lecturer_and_group() {
+        let lesson = |group, subject, lecturer, period| ScheduledLesson {
+            group,
+            slot: TimeSlot { day: 0, period },
+            subject,
+            lecturer,
+            room: 0,
+        };
+        let mut problem = Problem::new(HashMap::new(), HashMap::new(), HashMap::new());
+        problem.subject_names.insert(2, "Algebra".to_string());
+        problem.group_names.insert(1, "K-\"31\"".to_string());
+        let schedule = [
+            lesson(1, 2, 3, 0),
+            lesson(1, 2, 3, 1),
+            lesson(1, 5, 3, 2),
+            lesson(0, 5, 4, 0),
+        ];
+        let dot = to_dot(&problem, &schedule);
+        assert_eq!(dot.matches(" -- ").count(), 2);
+        assert!(dot.contains("  L3 -- G1 [label=\"Algebra (2h), 5 (1h)\"];\n"));
+        assert!(dot.contains("    G1 [label=\"K-\\\"31\\\"\"];\n"));
+    }
+}
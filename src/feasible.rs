@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::str::FromStr;
+
+use genevo::algorithm::EvaluatedPopulation;
+
+use crate::{Context, Dna, Genome, GroupId, LecturerId};
+
+/// When two collected genomes count as the same schedule.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Dedup {
+    /// Only identical genomes are duplicates.
+    #[default]
+    Exact,
+    /// Genomes that only differ in how a group's hours are shuffled among its lessons are
+    /// duplicates.
+    HourPermutations,
+}
+
+impl FromStr for Dedup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(Dedup::Exact),
+            "hour-permutations" => Ok(Dedup::HourPermutations),
+            other => Err(format!(
+                "unsupported dedup mode `{other}`, expected `exact` or `hour-permutations`"
+            )),
+        }
+    }
+}
+
+/// A representative genome that is equal for all genomes that only differ by permuting hours
+/// among the lessons of the same group: per group, the (subject, lecturer) pairs are sorted
+/// and paired with the group's sorted (hour, room) slots.
+pub fn canonical_form(context: &Context, genome: &Genome) -> Genome {
+    let group_subjects = &context.group_subjects;
+    let mut by_group: BTreeMap<GroupId, Vec<usize>> = BTreeMap::new();
+    for (index, (group, _)) in group_subjects.iter().enumerate() {
+        by_group.entry(*group).or_default().push(index);
+    }
+
+    let mut canonical = genome.clone();
+    for indices in by_group.into_values() {
+        let mut lessons: Vec<_> = indices
+            .iter()
+            .map(|i| (genome[*i].0 .0, genome[*i].0 .1))
+            .collect();
+        let mut slots: Vec<_> = indices
+            .iter()
+            .map(|i| (genome[*i].0 .3, genome[*i].0 .2))
+            .collect();
+        lessons.sort_unstable();
+        slots.sort_unstable();
+        for ((index, (subject, lecturer)), (hour, room)) in
+            indices.into_iter().zip(lessons).zip(slots)
+        {
+            canonical[index] = Dna((subject, lecturer, room, hour));
+        }
+    }
+    canonical
+}
+
+/// Collects every distinct genome that satisfies all hard constraints during a run.
+#[derive(Debug, Default)]
+pub struct FeasibleCollector {
+    dedup: Dedup,
+    seen: HashSet<Genome>,
+    pub found: Vec<(u64, Genome)>, // (generation first seen, genome) in discovery order.
+}
+
+impl FeasibleCollector {
+    pub fn new(dedup: Dedup) -> Self {
+        Self {
+            dedup,
+            ..Self::default()
+        }
+    }
+
+    pub fn collect(
+        &mut self,
+        context: &Context,
+        generation: u64,
+        population: &EvaluatedPopulation<Genome, i64>,
+    ) {
+        for genome in population.individuals().iter() {
+            if !context.is_feasible(genome) {
+                continue;
+            }
+            let key = match self.dedup {
+                Dedup::Exact => genome.clone(),
+                Dedup::HourPermutations => canonical_form(context, genome),
+            };
+            if self.seen.insert(key) {
+                self.found.push((generation, genome.clone()));
+            }
+        }
+    }
+}
+
+/// How much the collected solutions agree on one gene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneRobustness {
+    pub lecturers: BTreeSet<LecturerId>,
+    pub hours: BTreeSet<usize>,
+}
+
+impl GeneRobustness {
+    /// Every collected solution gives this lesson the same lecturer and hour.
+    pub fn is_robust(&self) -> bool {
+        self.lecturers.len() == 1 && self.hours.len() == 1
+    }
+}
+
+impl FeasibleCollector {
+    /// Per gene, the lecturers and hours used across all collected solutions.
+    pub fn robustness(&self) -> Vec<GeneRobustness> {
+        let genes = self.found.first().map_or(0, |(_, genome)| genome.len());
+        (0..genes)
+            .map(|index| GeneRobustness {
+                lecturers: self.found.iter().map(|(_, g)| g[index].0 .1).collect(),
+                hours: self
+                    .found
+                    .iter()
+                    .map(|(_, g)| g[index].0 .3.index())
+                    .collect(),
+            })
+            .collect()
+    }
+}
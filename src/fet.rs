@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::ics::Template;
+use crate::{Context, Genome, PERIODS_PER_DAY};
+
+/// The capacity FET gives rooms by default, for rooms the problem gives none.
+const DEFAULT_ROOM_CAPACITY: usize = 30000;
+
+/// Exports the problem and a solved genome in FET's `.fet` XML input format.
+///
+/// Days count on across weeks like `TimeSlot::day`, as `Day0`, `Day1` and so on, each of
+/// `PERIODS_PER_DAY` hours named by their clock times in `template`. Lecturers get their budget over all weeks as their target hours
+/// and their daily caps and caps on periods in a row as teacher constraints, groups their
+/// sizes. Each lesson is an activity locked to its scheduled hour and room, so FET shows the
+/// generated timetable as is. Subjects, teachers and student years carry their display
+/// names, or `S`, `L` and `G` prefixed ids without one, rooms are `R` prefixed ids.
+pub fn to_fet(context: &Context, genome: &Genome, template: Template) -> String {
+    let problem = &context.problem;
+    let group_subjects = &context.group_subjects;
+    let mut xml = String::new();
+
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(xml, r#"<fet version="6.9.0">"#).unwrap();
+    writeln!(xml, "<Institution_Name>is-lab3</Institution_Name>").unwrap();
+
+    writeln!(xml, "<Days_List>").unwrap();
+    let days = problem.days() * problem.weeks;
+    writeln!(xml, "<Number_of_Days>{days}</Number_of_Days>").unwrap();
+    for day in 0..days {
+        writeln!(xml, "<Day><Name>{}</Name></Day>", day_name(day)).unwrap();
+    }
+    writeln!(xml, "</Days_List>").unwrap();
+
+    writeln!(xml, "<Hours_List>").unwrap();
+    writeln!(xml, "<Number_of_Hours>{PERIODS_PER_DAY}</Number_of_Hours>").unwrap();
+    for period in 0..PERIODS_PER_DAY as u8 {
+        let hour = template.period_span(period);
+        writeln!(xml, "<Hour><Name>{hour}</Name></Hour>").unwrap();
+    }
+    writeln!(xml, "</Hours_List>").unwrap();
+
+    let mut subjects: Vec<_> = problem.subject_requirements.keys().collect();
+    subjects.sort();
+    writeln!(xml, "<Subjects_List>").unwrap();
+    for subject in subjects {
+        let subject = name(&problem.subject_names, 'S', *subject);
+        writeln!(xml, "<Subject><Name>{subject}</Name></Subject>").unwrap();
+    }
+    writeln!(xml, "</Subjects_List>").unwrap();
+
+    let mut lecturers: Vec<_> = problem.lecturer_requirements.iter().collect();
+    lecturers.sort();
+    writeln!(xml, "<Teachers_List>").unwrap();
+    for (lecturer, hours) in &lecturers {
+        writeln!(
+            xml,
+            "<Teacher><Name>{}</Name><Target_Number_of_Hours>{}</Target_Number_of_Hours></Teacher>",
+            name(&problem.lecturer_names, 'L', **lecturer),
+            *hours * problem.weeks
+        )
+        .unwrap();
+    }
+    writeln!(xml, "</Teachers_List>").unwrap();
+
+    let mut groups: Vec<_> = problem.group_requirements.keys().collect();
+    groups.sort();
+    writeln!(xml, "<Students_List>").unwrap();
+    for group in groups {
+        writeln!(
+            xml,
+            "<Year><Name>{}</Name><Number_of_Students>{}</Number_of_Students></Year>",
+            name(&problem.group_names, 'G', *group),
+            problem.group_sizes.get(group).copied().unwrap_or(0)
+        )
+        .unwrap();
+    }
+    writeln!(xml, "</Students_List>").unwrap();
+
+    // FET activity ids start at 1.
+    writeln!(xml, "<Activities_List>").unwrap();
+    for (index, ((group, subject), dna)) in group_subjects.iter().zip(genome).enumerate() {
+        writeln!(
+            xml,
+            "<Activity><Teacher>{}</Teacher><Subject>{}</Subject><Students>{}</Students>\
+             <Duration>1</Duration><Total_Duration>1</Total_Duration><Id>{}</Id>\
+             <Activity_Group_Id>0</Activity_Group_Id><Active>true</Active></Activity>",
+            name(&problem.lecturer_names, 'L', dna.0 .1),
+            name(&problem.subject_names, 'S', *subject),
+            name(&problem.group_names, 'G', *group),
+            index + 1
+        )
+        .unwrap();
+    }
+    writeln!(xml, "</Activities_List>").unwrap();
+
+    writeln!(xml, "<Buildings_List>").unwrap();
+    writeln!(xml, "</Buildings_List>").unwrap();
+    writeln!(xml, "<Rooms_List>").unwrap();
+    for room in 0..problem.room_count {
+        writeln!(
+            xml,
+            "<Room><Name>R{room}</Name><Building></Building><Capacity>{}</Capacity>\
+             <Virtual>false</Virtual></Room>",
+            problem
+                .room_capacities
+                .get(&room)
+                .copied()
+                .unwrap_or(DEFAULT_ROOM_CAPACITY)
+        )
+        .unwrap();
+    }
+    writeln!(xml, "</Rooms_List>").unwrap();
+
+    writeln!(xml, "<Time_Constraints_List>").unwrap();
+    writeln!(
+        xml,
+        "<ConstraintBasicCompulsoryTime><Weight_Percentage>100</Weight_Percentage>\
+         <Active>true</Active></ConstraintBasicCompulsoryTime>"
+    )
+    .unwrap();
+    for (lecturer, _) in &lecturers {
+        let teacher = name(&problem.lecturer_names, 'L', **lecturer);
+        if let Some(cap) = problem.daily_cap(**lecturer) {
+            writeln!(
+                xml,
+                "<ConstraintTeacherMaxHoursDaily><Weight_Percentage>100</Weight_Percentage>\
+                 <Teacher_Name>{teacher}</Teacher_Name><Maximum_Hours_Daily>{cap}\
+                 </Maximum_Hours_Daily><Active>true</Active></ConstraintTeacherMaxHoursDaily>"
+            )
+            .unwrap();
+        }
+        if let Some(cap) = problem.max_consecutive.get(lecturer) {
+            writeln!(
+                xml,
+                "<ConstraintTeacherMaxHoursContinuously><Weight_Percentage>100\
+                 </Weight_Percentage><Teacher_Name>{teacher}</Teacher_Name>\
+                 <Maximum_Hours_Continuously>{cap}</Maximum_Hours_Continuously>\
+                 <Active>true</Active></ConstraintTeacherMaxHoursContinuously>"
+            )
+            .unwrap();
+        }
+    }
+    for (index, dna) in genome.iter().enumerate() {
+        let slot = dna.0 .3;
+        writeln!(
+            xml,
+            "<ConstraintActivityPreferredStartingTime><Weight_Percentage>100</Weight_Percentage>\
+             <Activity_Id>{}</Activity_Id><Preferred_Day>{}</Preferred_Day>\
+             <Preferred_Hour>{}</Preferred_Hour><Permanently_Locked>true</Permanently_Locked>\
+             <Active>true</Active></ConstraintActivityPreferredStartingTime>",
+            index + 1,
+            day_name(slot.day.into()),
+            template.period_span(slot.period)
+        )
+        .unwrap();
+    }
+    writeln!(xml, "</Time_Constraints_List>").unwrap();
+
+    writeln!(xml, "<Space_Constraints_List>").unwrap();
+    writeln!(
+        xml,
+        "<ConstraintBasicCompulsorySpace><Weight_Percentage>100</Weight_Percentage>\
+         <Active>true</Active></ConstraintBasicCompulsorySpace>"
+    )
+    .unwrap();
+    if problem.room_count > 0 {
+        for (index, dna) in genome.iter().enumerate() {
+            writeln!(
+                xml,
+                "<ConstraintActivityPreferredRoom><Weight_Percentage>100</Weight_Percentage>\
+                 <Activity_Id>{}</Activity_Id><Room>R{}</Room>\
+                 <Permanently_Locked>true</Permanently_Locked><Active>true</Active>\
+                 </ConstraintActivityPreferredRoom>",
+                index + 1,
+                dna.0 .2
+            )
+            .unwrap();
+        }
+    }
+    writeln!(xml, "</Space_Constraints_List>").unwrap();
+    writeln!(xml, "</fet>").unwrap();
+
+    xml
+}
+
+fn day_name(day: usize) -> String {
+    format!("Day{day}")
+}
+
+/// The display name of `id` escaped for XML, or the id behind `prefix` without one.
+fn name(names: &HashMap<usize, String>, prefix: char, id: usize) -> String {
+    match names.get(&id) {
+        Some(name) => name
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+        None => format!("{prefix}{id}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dataset, Dna, TimeSlot};
+
+    /// The element names of `xml` as `/`-separated paths, in document order, checking that
+    /// every element is closed in the order it was opened.
+    fn element_paths(xml: &str) -> Vec<String> {
+        let mut open: Vec<&str> = vec![];
+        let mut paths = vec![];
+        for tag in xml
+            .split('<')
+            .skip(1)
+            .map(|rest| &rest[..rest.find('>').unwrap()])
+        {
+            if tag.starts_with('?') {
+                continue;
+            }
+            match tag.strip_prefix('/') {
+                Some(name) => assert_eq!(open.pop(), Some(name), "unbalanced </{name}>"),
+                None => {
+                    open.push(tag.split(' ').next().unwrap());
+                    paths.push(open.join("/"));
+                }
+            }
+        }
+        assert!(open.is_empty(), "unclosed {open:?}");
+        paths
+    }
+
+    #[test]
+    fn exports_days_rooms_sizes_and_locked_lessons() {
+        let mut problem = dataset::small_example();
+        problem.weeks = 2;
+        problem.room_count = 2;
+        problem.group_sizes = [(0, 25)].into();
+        problem.room_capacities = [(1, 40)].into();
+        problem.max_hours_per_day = [(2, 3)].into();
+        problem.max_consecutive = [(2, 2)].into();
+        let context = Context::new(problem);
+        let mut genome = vec![Dna((0, 3, 1, TimeSlot::default())); context.group_subjects.len()];
+        genome[0].0 .3 = TimeSlot { day: 7, period: 2 };
+
+        let xml = to_fet(&context, &genome, Template::UkrainianPairs);
+        let paths = element_paths(&xml);
+        let count = |path: &str| paths.iter().filter(|found| *found == path).count();
+        assert_eq!(count("fet/Days_List/Day"), 10);
+        assert_eq!(count("fet/Hours_List/Hour"), PERIODS_PER_DAY);
+        assert!(xml.contains("<Hour><Name>08:30-10:05</Name></Hour>"));
+        assert_eq!(count("fet/Rooms_List/Room"), 2);
+        assert_eq!(count("fet/Activities_List/Activity"), genome.len());
+        assert_eq!(
+            count("fet/Time_Constraints_List/ConstraintActivityPreferredStartingTime"),
+            genome.len()
+        );
+        assert_eq!(
+            count("fet/Space_Constraints_List/ConstraintActivityPreferredRoom"),
+            genome.len()
+        );
+        // Only lecturer 2 has a daily cap, no weekly budget is one.
+        assert_eq!(
+            count("fet/Time_Constraints_List/ConstraintTeacherMaxHoursDaily"),
+            1
+        );
+        assert!(xml.contains("<Teacher_Name>L2</Teacher_Name><Maximum_Hours_Daily>3<"));
+        assert!(xml.contains("<Maximum_Hours_Continuously>2</Maximum_Hours_Continuously>"));
+
+        assert!(xml.contains("<Name>G0</Name><Number_of_Students>25</Number_of_Students>"));
+        assert!(xml.contains("<Name>R1</Name><Building></Building><Capacity>40</Capacity>"));
+        assert!(xml.contains(
+            "<Activity_Id>1</Activity_Id><Preferred_Day>Day7</Preferred_Day>\
+             <Preferred_Hour>12:20-13:55</Preferred_Hour>"
+        ));
+        assert!(xml.contains("<Activity_Id>1</Activity_Id><Room>R1</Room>"));
+    }
+}
@@ -0,0 +1,169 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use genevo::genetic::{Children, Parents};
+use genevo::operator::prelude::{
+    MultiPointCrossBreeder, SinglePointCrossBreeder, UniformCrossBreeder,
+};
+use genevo::operator::{CrossoverOp, GeneticOperator};
+use genevo::prelude::*;
+
+use crate::{analysis, Genome, LecturerId};
+
+pub const OPERATORS: [&str; 3] = ["single-point", "multi-point", "uniform"];
+pub const DEFAULT_CROSSOVER_POINTS: usize = 3; // cuts of the multi-point crossover.
+const MIN_PROBABILITY: f64 = 0.1; // every operator keeps getting picked now and then.
+const DECAY: f64 = 0.8; // weight of the previous score, so recent generations count more.
+
+/// The crossover to breed with, or to start from with the hyper-heuristic.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Crossover {
+    SinglePoint,
+    MultiPoint,
+    #[default]
+    Uniform,
+}
+
+impl Crossover {
+    /// Index of the operator in `OPERATORS`.
+    fn index(self) -> usize {
+        match self {
+            Crossover::SinglePoint => 0,
+            Crossover::MultiPoint => 1,
+            Crossover::Uniform => 2,
+        }
+    }
+}
+
+impl FromStr for Crossover {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(Crossover::SinglePoint),
+            "multi" => Ok(Crossover::MultiPoint),
+            "uniform" => Ok(Crossover::Uniform),
+            other => Err(format!(
+                "unsupported crossover `{other}`, expected `single`, `uniform` or `multi`"
+            )),
+        }
+    }
+}
+
+/// A crossover that delegates to one of `OPERATORS`, switchable between generations through
+/// a shared index. Children are relabeled to break lecturer symmetry when that is enabled.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBreeder {
+    single_point: SinglePointCrossBreeder,
+    multi_point: MultiPointCrossBreeder,
+    uniform: UniformCrossBreeder,
+    current: Arc<AtomicUsize>,
+    symmetric_lecturers: Vec<Vec<LecturerId>>, // interchangeable sets to relabel children by.
+}
+
+impl AdaptiveBreeder {
+    /// Relabels children by `analysis::break_symmetry` over these interchangeable sets.
+    pub fn breaking_symmetry(self, symmetric_lecturers: Vec<Vec<LecturerId>>) -> Self {
+        Self {
+            symmetric_lecturers,
+            ..self
+        }
+    }
+}
+
+impl GeneticOperator for AdaptiveBreeder {
+    fn name() -> String {
+        "Adaptive-Cross-Breeder".to_string()
+    }
+}
+
+impl CrossoverOp<Genome> for AdaptiveBreeder {
+    fn crossover<R>(&self, parents: Parents<Genome>, rng: &mut R) -> Children<Genome>
+    where
+        R: Rng + Sized,
+    {
+        let mut children = match self.current.load(Ordering::Relaxed) {
+            0 => self.single_point.crossover(parents, rng),
+            1 => self.multi_point.crossover(parents, rng),
+            _ => self.uniform.crossover(parents, rng),
+        };
+        if !self.symmetric_lecturers.is_empty() {
+            for child in &mut children {
+                analysis::break_symmetry(child, &self.symmetric_lecturers);
+            }
+        }
+        children
+    }
+}
+
+/// Picks the crossover for the next generation by probability matching on how much each
+/// operator recently raised the average fitness.
+#[derive(Debug)]
+pub struct OperatorSelector {
+    current: Arc<AtomicUsize>,
+    scores: [f64; 3],
+    pub uses: [usize; 3],
+    pending: Option<usize>, // operator whose offspring the next evaluated generation shows.
+    last_average: Option<i64>,
+}
+
+impl OperatorSelector {
+    /// A selector and the breeder it controls, starting with `crossover`. The multi-point
+    /// crossover cuts the parents at `points` positions.
+    pub fn new(crossover: Crossover, points: usize) -> (Self, AdaptiveBreeder) {
+        let current = Arc::new(AtomicUsize::new(crossover.index()));
+        let breeder = AdaptiveBreeder {
+            single_point: SinglePointCrossBreeder::new(),
+            multi_point: MultiPointCrossBreeder::new(points),
+            uniform: UniformCrossBreeder::new(),
+            current: current.clone(),
+            symmetric_lecturers: vec![],
+        };
+        let selector = Self {
+            current,
+            scores: [0.0; 3],
+            uses: [0; 3],
+            pending: None,
+            last_average: None,
+        };
+        (selector, breeder)
+    }
+
+    /// Credits the operator that bred the generation just evaluated with its change in
+    /// average fitness, then chooses the operator for the next breeding step.
+    pub fn update<R>(&mut self, average_fitness: i64, rng: &mut R) -> usize
+    where
+        R: Rng + Sized,
+    {
+        if let (Some(operator), Some(last)) = (self.pending, self.last_average) {
+            let reward = (average_fitness - last).max(0) as f64;
+            self.scores[operator] = DECAY * self.scores[operator] + (1.0 - DECAY) * reward;
+        }
+        self.last_average = Some(average_fitness);
+
+        // The breeding of this step already used `current`, it is judged on the next update.
+        let used = self.current.load(Ordering::Relaxed);
+        self.uses[used] += 1;
+        self.pending = Some(used);
+
+        let total: f64 = self.scores.iter().sum();
+        let spread = 1.0 - MIN_PROBABILITY * OPERATORS.len() as f64;
+        let mut pointer = rng.gen::<f64>();
+        let mut next = OPERATORS.len() - 1;
+        for (operator, score) in self.scores.iter().enumerate() {
+            let share = if total > 0.0 {
+                score / total
+            } else {
+                1.0 / OPERATORS.len() as f64
+            };
+            pointer -= MIN_PROBABILITY + spread * share;
+            if pointer < 0.0 {
+                next = operator;
+                break;
+            }
+        }
+        self.current.store(next, Ordering::Relaxed);
+        next
+    }
+}
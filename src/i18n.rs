@@ -0,0 +1,249 @@
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Lang {
+    #[default]
+    En,
+    Uk,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Lang::En),
+            "uk" => Ok(Lang::Uk),
+            other => Err(format!(
+                "unsupported language `{other}`, expected `en` or `uk`"
+            )),
+        }
+    }
+}
+
+/// Output strings for a single language.
+#[derive(Debug)]
+pub struct Labels {
+    pub group: &'static str,
+    pub week: &'static str,
+    pub day: &'static str,
+    pub period: &'static str,
+    pub periods: &'static str,
+    pub subject: &'static str,
+    pub lecturer: &'static str,
+    pub by_groups: &'static str,
+    pub by_lecturers: &'static str,
+    pub final_result: &'static str,
+    pub generation: &'static str,
+    pub best_fitness: &'static str,
+    pub found_in_generation: &'static str,
+    pub processing_time: &'static str,
+    pub remaining_violations: &'static str,
+    pub room: &'static str,
+    pub at: &'static str,
+    pub group_busy: &'static str,
+    pub already_teaches: &'static str,
+    pub unavailable: &'static str,
+    pub out_of_weekly_hours: &'static str,
+    pub room_taken: &'static str,
+    pub room_too_small: &'static str,
+    pub taught_by: &'static str,
+    pub pinned_elsewhere: &'static str,
+    pub split_double_lesson: &'static str,
+    pub teaches: &'static str,
+    pub hours: &'static str,
+    pub periods_in_a_row: &'static str,
+    pub on: &'static str,
+    pub over_cap: &'static str,
+    pub utilization: &'static str,
+    pub of: &'static str,
+    pub hours_occupied: &'static str,
+    pub overall: &'static str,
+    pub group_hours_occupied: &'static str,
+    pub half_days_free: &'static str,
+    pub hours_required: &'static str,
+    pub no_capacity: &'static str,
+    pub slack: &'static str,
+    pub tight: &'static str,
+    pub over_capacity: &'static str,
+    pub selftest_pass: &'static str,
+    pub selftest_fail: &'static str,
+    pub fitness: &'static str,
+    pub found: &'static str,
+    pub distinct_feasible_schedules: &'static str,
+    pub genes_per_lesson: &'static str,
+    pub robustness_across: &'static str,
+    pub feasible_schedules: &'static str,
+    pub assignments_robust: &'static str,
+    pub gene: &'static str,
+    pub robust: &'static str,
+    pub flexible: &'static str,
+    pub lecturers: &'static str,
+    pub gene_contributions: &'static str,
+    pub stopped: &'static str,
+    pub stop_generation_limit: &'static str,
+    pub stop_optimum: &'static str,
+    pub stop_plateau: &'static str,
+    pub generations: &'static str,
+    pub feasible_in_median: &'static str,
+    pub never_feasible_in: &'static str,
+    pub stop_feasible: &'static str,
+    pub stop_cancelled: &'static str,
+    pub repair: &'static str,
+    pub moved_from: &'static str,
+    pub to: &'static str,
+    pub no_free_room: &'static str,
+}
+
+const EN: Labels = Labels {
+    group: "group",
+    week: "week",
+    day: "day",
+    period: "period",
+    periods: "periods",
+    subject: "subject",
+    lecturer: "lecturer",
+    by_groups: "Schedule ordered by groups",
+    by_lecturers: "Schedule ordered by lecturers",
+    final_result: "Final result after",
+    generation: "generation",
+    best_fitness: "best solution with fitness",
+    found_in_generation: "found in generation",
+    processing_time: "processing_time",
+    remaining_violations: "Remaining violations",
+    room: "room",
+    at: "at",
+    group_busy: "the group already has a lesson",
+    already_teaches: "already teaches",
+    unavailable: "is unavailable",
+    out_of_weekly_hours: "is out of weekly hours",
+    room_taken: "is already taken",
+    room_too_small: "is too small for the group",
+    taught_by: "taught by",
+    pinned_elsewhere: "here, but pinned elsewhere",
+    split_double_lesson: "the double lesson's first half isn't in the period before",
+    teaches: "teaches",
+    hours: "hours",
+    periods_in_a_row: "periods in a row",
+    on: "on",
+    over_cap: "over their cap of",
+    utilization: "Utilization",
+    of: "of",
+    hours_occupied: "hours occupied",
+    overall: "overall",
+    group_hours_occupied: "group hours occupied",
+    half_days_free: "half-days free",
+    hours_required: "hours required",
+    no_capacity: "no capacity (infeasible)",
+    slack: "slack",
+    tight: "tight",
+    over_capacity: "over capacity",
+    selftest_pass: "selftest: PASS",
+    selftest_fail: "selftest: FAIL",
+    fitness: "fitness",
+    found: "Found",
+    distinct_feasible_schedules: "distinct feasible schedules",
+    genes_per_lesson: "lecturer@hour per lesson",
+    robustness_across: "Robustness across",
+    feasible_schedules: "feasible schedules",
+    assignments_robust: "assignments are robust",
+    gene: "gene",
+    robust: "robust",
+    flexible: "flexible",
+    lecturers: "lecturers",
+    gene_contributions: "Gene contributions, most problematic first",
+    stopped: "stopped",
+    stop_generation_limit: "reached the generation limit",
+    stop_optimum: "reached the highest possible fitness",
+    stop_plateau: "no improvement in the last",
+    generations: "generations",
+    feasible_in_median: "feasible in median",
+    never_feasible_in: "never feasible in",
+    stop_feasible: "found a feasible schedule",
+    stop_cancelled: "stopped by the caller",
+    repair: "repair",
+    moved_from: "moved from",
+    to: "to",
+    no_free_room: "no free room fits the group, it stays in",
+};
+
+const UK: Labels = Labels {
+    group: "група",
+    week: "тиждень",
+    day: "день",
+    period: "пара",
+    periods: "пар",
+    subject: "предмет",
+    lecturer: "викладач",
+    by_groups: "Розклад за групами",
+    by_lecturers: "Розклад за викладачами",
+    final_result: "Остаточний результат за",
+    generation: "покоління",
+    best_fitness: "найкращий розв'язок із пристосованістю",
+    found_in_generation: "знайдено в поколінні",
+    processing_time: "час обробки",
+    remaining_violations: "Залишені порушення",
+    room: "аудиторія",
+    at: "у",
+    group_busy: "група вже має заняття",
+    already_teaches: "уже викладає",
+    unavailable: "недоступний",
+    out_of_weekly_hours: "вичерпав тижневі години",
+    room_taken: "уже зайнята",
+    room_too_small: "замала для групи",
+    taught_by: "веде",
+    pinned_elsewhere: "тут, але закріплене деінде",
+    split_double_lesson: "перша половина здвоєного заняття не в попередній парі",
+    teaches: "викладає",
+    hours: "год.",
+    periods_in_a_row: "пар поспіль",
+    on: "у",
+    over_cap: "більше за ліміт",
+    utilization: "Завантаженість",
+    of: "з",
+    hours_occupied: "год. зайнято",
+    overall: "загалом",
+    group_hours_occupied: "групових год. зайнято",
+    half_days_free: "півднів вільно",
+    hours_required: "год. потрібно",
+    no_capacity: "немає місткості (нездійсненно)",
+    slack: "із запасом",
+    tight: "впритул",
+    over_capacity: "понад місткість",
+    selftest_pass: "самоперевірка: ПРОЙДЕНО",
+    selftest_fail: "самоперевірка: ПРОВАЛЕНО",
+    fitness: "пристосованість",
+    found: "Знайдено",
+    distinct_feasible_schedules: "різних допустимих розкладів",
+    genes_per_lesson: "викладач@година для кожного заняття",
+    robustness_across: "Стійкість серед",
+    feasible_schedules: "допустимих розкладів",
+    assignments_robust: "призначень стійкі",
+    gene: "ген",
+    robust: "стійкий",
+    flexible: "гнучкий",
+    lecturers: "викладачі",
+    gene_contributions: "Внески генів, найпроблемніші спершу",
+    stopped: "зупинено",
+    stop_generation_limit: "досягнуто ліміту поколінь",
+    stop_optimum: "досягнуто найвищої можливої пристосованості",
+    stop_plateau: "без покращення за останні",
+    generations: "поколінь",
+    feasible_in_median: "допустимий розклад за медіаною",
+    never_feasible_in: "без допустимого розкладу за",
+    stop_feasible: "знайдено допустимий розклад",
+    stop_cancelled: "зупинено викликачем",
+    repair: "виправлення",
+    moved_from: "перенесено з",
+    to: "до",
+    no_free_room: "немає вільної аудиторії для групи, залишається",
+};
+
+impl Lang {
+    pub fn labels(self) -> &'static Labels {
+        match self {
+            Lang::En => &EN,
+            Lang::Uk => &UK,
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::schedule::ScheduledLesson;
+use crate::{Problem, TimeSlot};
+
+/// The clock times of the periods of a day.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Template {
+    /// Back-to-back 45 minute periods from 08:00.
+    #[default]
+    BackToBack,
+    /// University pairs from 08:30, two 45 minute halves with a 5 minute break between them,
+    /// and 20 minutes between pairs.
+    UkrainianPairs,
+    /// 45 minute school lessons from 08:30, with 10 minutes between them.
+    School,
+}
+
+impl Template {
+    /// When the first period starts, as (hour, minute), how many minutes each period takes
+    /// and how many the break after it.
+    fn layout(self) -> ((u32, u32), i64, i64) {
+        match self {
+            Template::BackToBack => ((8, 0), 45, 0),
+            Template::UkrainianPairs => ((8, 30), 95, 20),
+            Template::School => ((8, 30), 45, 10),
+        }
+    }
+
+    /// The start and end of `period` on any day.
+    pub fn period_times(self, period: u8) -> (NaiveTime, NaiveTime) {
+        let ((hour, minute), length, pause) = self.layout();
+        let first = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let start = first + Duration::minutes(i64::from(period) * (length + pause));
+        (start, start + Duration::minutes(length))
+    }
+
+    /// The clock times of `period` as `HH:MM-HH:MM`, for exports that only take text.
+    pub fn period_span(self, period: u8) -> String {
+        let (start, end) = self.period_times(period);
+        format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "back-to-back" => Ok(Template::BackToBack),
+            "ukrainian-pair-schedule" => Ok(Template::UkrainianPairs),
+            "school" => Ok(Template::School),
+            other => Err(format!(
+                "unsupported template `{other}`, expected `back-to-back`, \
+                 `ukrainian-pair-schedule` or `school`"
+            )),
+        }
+    }
+}
+
+/// The time block a slot is taught in, with `days` days a week (`Problem::days`). The days of
+/// a week are consecutive days from Monday `week_start` on, later weeks follow on the next
+/// calendar weeks (or the next but one, for weeks of more than seven days), and periods take
+/// the clock times of `template`. Distinct slots never overlap.
+pub fn time_block(
+    slot: TimeSlot,
+    days: usize,
+    week_start: NaiveDate,
+    template: Template,
+) -> (NaiveDateTime, NaiveDateTime) {
+    let (week, day) = (slot.week(days), usize::from(slot.day) % days);
+    let calendar_days = days.div_ceil(7) * 7;
+    let date = week_start + Duration::days((week * calendar_days + day) as i64);
+    let (start, end) = template.period_times(slot.period);
+    (date.and_time(start), date.and_time(end))
+}
+
+/// Exports a schedule as an iCalendar file with one event per lesson, see `time_block` for
+/// how slots map to times. Times are floating, i.e. local to whoever opens the calendar.
+/// Events name subjects, lecturers and groups as `problem` does, by id if it has no name,
+/// and have the lesson's room as their location if the problem has rooms.
+pub fn to_ics(
+    problem: &Problem,
+    schedule: &[ScheduledLesson],
+    week_start: NaiveDate,
+    template: Template,
+) -> String {
+    const FORMAT: &str = "%Y%m%dT%H%M%S";
+    let stamp = week_start.and_hms_opt(0, 0, 0).unwrap().format(FORMAT);
+    let mut ics = String::new();
+
+    write!(ics, "BEGIN:VCALENDAR\r\n").unwrap();
+    write!(ics, "VERSION:2.0\r\n").unwrap();
+    write!(ics, "PRODID:-//is-lab3//schedule//EN\r\n").unwrap();
+    for (index, lesson) in schedule.iter().enumerate() {
+        let (start, end) = time_block(lesson.slot, problem.days(), week_start, template);
+        write!(ics, "BEGIN:VEVENT\r\n").unwrap();
+        write!(ics, "UID:lesson-{index}-group-{}@is-lab3\r\n", lesson.group).unwrap();
+        write!(ics, "DTSTAMP:{stamp}Z\r\n").unwrap();
+        write!(ics, "DTSTART:{}\r\n", start.format(FORMAT)).unwrap();
+        write!(ics, "DTEND:{}\r\n", end.format(FORMAT)).unwrap();
+        write!(
+            ics,
+            "SUMMARY:Subject {}\\, lecturer {}\r\n",
+            text(&problem.subject_label(lesson.subject)),
+            text(&problem.lecturer_label(lesson.lecturer))
+        )
+        .unwrap();
+        write!(
+            ics,
+            "CATEGORIES:Group {}\r\n",
+            text(&problem.group_label(lesson.group))
+        )
+        .unwrap();
+        if problem.room_count > 0 {
+            write!(ics, "LOCATION:Room {}\r\n", lesson.room).unwrap();
+        }
+        write!(ics, "END:VEVENT\r\n").unwrap();
+    }
+    write!(ics, "END:VCALENDAR\r\n").unwrap();
+    ics
+}
+
+/// Escapes an iCalendar TEXT value.
+fn text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::DEFAULT_HOURS;
+
+    #[test]
+    fn distinct_slots_never_overlap() {
+        let monday = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        for template in [
+            Template::BackToBack,
+            Template::UkrainianPairs,
+            Template::School,
+        ] {
+            let blocks: Vec<_> = (0..DEFAULT_HOURS * 2)
+                .map(|index| time_block(TimeSlot::from_index(index), 5, monday, template))
+                .collect();
+            let first = monday.and_time(template.period_times(0).0);
+            assert_eq!(blocks[0].0, first, "the first slot starts the week");
+            assert_eq!(
+                blocks[DEFAULT_HOURS].0,
+                first + Duration::days(7),
+                "the second week starts on the next Monday"
+            );
+            for (a, first) in blocks.iter().enumerate() {
+                for second in &blocks[a + 1..] {
+                    assert!(first.1 <= second.0 || second.1 <= first.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_take_the_clock_times_of_the_template() {
+        let template: Template = "ukrainian-pair-schedule".parse().unwrap();
+        let times: Vec<_> = (0..4)
+            .map(|period| {
+                let (start, end) = template.period_times(period);
+                (
+                    start.format("%H:%M").to_string(),
+                    end.format("%H:%M").to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            times,
+            [
+                ("08:30", "10:05"),
+                ("10:25", "12:00"),
+                ("12:20", "13:55"),
+                ("14:15", "15:50")
+            ]
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+        );
+        assert!("pairs".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn writes_one_event_per_lesson() {
+        let monday = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        let lesson = |day, period, room| ScheduledLesson {
+            group: 1,
+            slot: TimeSlot { day, period },
+            subject: 2,
+            lecturer: 3,
+            room,
+        };
+        let mut problem = Problem::new(HashMap::new(), HashMap::new(), HashMap::new());
+        problem
+            .subject_names
+            .insert(2, "Algebra, part 1".to_string());
+        let schedule = [lesson(0, 0, 0), lesson(1, 2, 1)];
+        let ics = to_ics(&problem, &schedule, monday, Template::default());
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(!ics.contains("LOCATION:"), "without rooms, no locations");
+        assert!(
+            ics.contains("DTSTART:20240903T093000\r\n"),
+            "day 1 is Tuesday, its period 2 starts at 09:30"
+        );
+        assert!(ics.contains("SUMMARY:Subject Algebra\\, part 1\\, lecturer 3\r\n"));
+
+        problem.room_count = 2;
+        let ics = to_ics(&problem, &schedule, monday, Template::default());
+        assert!(ics.contains("LOCATION:Room 0\r\nEND:VEVENT"));
+        assert!(ics.contains("LOCATION:Room 1\r\nEND:VEVENT"));
+    }
+}
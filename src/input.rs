@@ -0,0 +1,787 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::{
+    Context, Dna, FitnessWeights, Genome, Problem, TimeSlot, DEFAULT_HOURS, PERIODS_PER_DAY,
+};
+
+/// The `schema_version` of the current constraints format. Version 1 files predate
+/// `total_hours` and are upgraded on load.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Why a constraints file could not be turned into a `Problem`. Paths inside the JSON are
+/// written like `groups_subjects_hours[2][1].hours`, empty for the top-level object.
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("{} does not exist", .0.display())]
+    NotFound(PathBuf),
+    #[error("no permission to read {}", .0.display())]
+    PermissionDenied(PathBuf),
+    #[error("cannot read {}: {1}", .0.display())]
+    Read(PathBuf, #[source] std::io::Error),
+    #[error("{} is not valid JSON: {1}", .0.display())]
+    Json(PathBuf, #[source] serde_json::Error),
+    #[error("missing field `{field}` in {}", describe(.path))]
+    MissingField { path: String, field: &'static str },
+    #[error("unsupported schema_version {version}, expected 1 to {SCHEMA_VERSION}")]
+    UnsupportedVersion { version: u64 },
+    #[error("unknown weight `{name}`, expected one of {}", FitnessWeights::NAMES.join(", "))]
+    UnknownWeight { name: String },
+    #[error("expected {expected} at {}", describe(.path))]
+    WrongType {
+        path: String,
+        expected: &'static str,
+    },
+    #[error(
+        "the lessons of group {group} in subject {subject} in week {week} don't match the \
+         problem's hours"
+    )]
+    LessonMismatch {
+        group: usize,
+        subject: usize,
+        week: usize,
+    },
+}
+
+fn describe(path: &str) -> &str {
+    if path.is_empty() {
+        "the top-level object"
+    } else {
+        path
+    }
+}
+
+/// Reads a constraints file in the `constraints.json` format.
+pub fn load_problem(path: &Path) -> Result<Problem, LoadError> {
+    parse_problem(&read_json(path)?)
+}
+
+/// Reads a schedule of `context` written with `--output-json`.
+pub fn load_schedule(path: &Path, context: &Context) -> Result<Genome, LoadError> {
+    parse_schedule(&read_json(path)?, context)
+}
+
+fn read_json(path: &Path) -> Result<Value, LoadError> {
+    let str = std::fs::read_to_string(path).map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => LoadError::NotFound(path.to_owned()),
+        std::io::ErrorKind::PermissionDenied => LoadError::PermissionDenied(path.to_owned()),
+        _ => LoadError::Read(path.to_owned(), error),
+    })?;
+    serde_json::from_str(&str).map_err(|error| LoadError::Json(path.to_owned(), error))
+}
+
+/// Turns the `lessons` of an `--output-json` document back into a genome of `context`. Each
+/// requirement's genes of a week take its lessons in that week in order of their slots, so
+/// every group needs as many lessons of each subject a week as the problem asks for.
+pub fn parse_schedule(value: &Value, context: &Context) -> Result<Genome, LoadError> {
+    let days = context.problem.days();
+    let mut lessons: HashMap<(usize, usize, usize), Vec<Dna>> = HashMap::new();
+    for (i, lesson) in array(field(value, "", "lessons")?, "lessons")?
+        .iter()
+        .enumerate()
+    {
+        let path = format!("lessons[{i}]");
+        let get = |name| number(field(lesson, &path, name)?, &format!("{path}.{name}"));
+        let period = |name| {
+            u8::try_from(get(name)?).map_err(|_| LoadError::WrongType {
+                path: format!("{path}.{name}"),
+                expected: "an integer from 0 to 255",
+            })
+        };
+        let slot = TimeSlot {
+            day: period("day")?,
+            period: period("period")?,
+        };
+        if usize::from(slot.period) >= PERIODS_PER_DAY {
+            return Err(LoadError::WrongType {
+                path: format!("{path}.period"),
+                expected: "a period of the day",
+            });
+        }
+        let (group, subject) = (get("group")?, get("subject")?);
+        lessons
+            .entry((group, subject, slot.week(days)))
+            .or_default()
+            .push(Dna((subject, get("lecturer")?, get("room")?, slot)));
+    }
+    // Latest first, so that popping takes the earliest.
+    for week in lessons.values_mut() {
+        week.sort_by_key(|dna| std::cmp::Reverse(dna.0 .3));
+    }
+
+    let genes_per_week = context.group_subjects.len() / context.problem.weeks;
+    let mut genome = Vec::with_capacity(context.group_subjects.len());
+    for (index, &(group, subject)) in context.group_subjects.iter().enumerate() {
+        let week = index / genes_per_week;
+        let dna = lessons
+            .get_mut(&(group, subject, week))
+            .and_then(Vec::pop)
+            .ok_or(LoadError::LessonMismatch {
+                group,
+                subject,
+                week,
+            })?;
+        genome.push(dna);
+    }
+    if let Some(&(group, subject, week)) = lessons
+        .iter()
+        .find(|(_, left)| !left.is_empty())
+        .map(|(key, _)| key)
+    {
+        return Err(LoadError::LessonMismatch {
+            group,
+            subject,
+            week,
+        });
+    }
+    Ok(genome)
+}
+
+/// Builds a problem from the `constraints.json` format, upgrading older versions first.
+///
+/// Required keys:
+/// - `schema_version`: 1 to `SCHEMA_VERSION`.
+/// - `groups_subjects_hours`: `{"subject", "hours"}` objects per group, with an optional
+///   `"consecutive": true` for double lessons.
+/// - `teachers_hours`: the hours per lecturer.
+/// - `subjects_teachers`: the suitable lecturers per subject, as ids or as `{"lecturer",
+///   "weight"}` objects for lecturers better (or, at 0, less) suited than those of weight 1.
+///
+/// Optional keys:
+/// - `room_count`: rooms, one per `room_capacities` entry without it.
+/// - `group_sizes`, `room_capacities`: students per group and seats per room.
+/// - `total_hours`: time slots per week, 20 without it.
+/// - `forbidden_periods`: periods of every day no lesson may take.
+/// - `max_period_per_day`: the last period lessons may take, forbidding those after it.
+/// - `lecturer_unavailable`: the hours of the week each lecturer can't teach at.
+/// - `max_hours_per_day`: each lecturer's cap on hours per day.
+/// - `default_max_hours_per_day`: the cap of lecturers beyond the end of `max_hours_per_day`.
+/// - `max_consecutive`: each lecturer's cap on periods in a row.
+/// - `lecturer_preferences`: `{"hour", "score"}` objects per lecturer, negative scores for
+///   hours they'd rather not teach at.
+/// - `pinned`: `{"group", "subject", "lecturer", "hour"}` objects for lessons fixed in advance.
+/// - `group_weights`: how many times over each group's lessons count towards fitness, 1 for
+///   groups beyond its end.
+/// - `rotate_subjects`: subjects whose lecturers should change from week to week.
+/// - `lab_lectures`: `{"lab", "lecture"}` subject pairs.
+/// - `lecture_before_lab`: with `true`, labs should follow their lectures on days with both.
+/// - `must_be_first`: `{"group", "subject"}` requirements whose lessons should start the day.
+/// - `group_disliked_hours`: the hours of the week each group would rather not have lessons at.
+/// - `room_change_needs_gap`: with `true`, lecturers should have a free period before a
+///   lesson in another room.
+/// - `group_daily_min`, `group_daily_max`: each group's band of hours on days with lessons.
+/// - `subject_difficulty`: how hard each subject is, 0 to 255, for keeping two hard ones
+///   from being back to back.
+/// - `weights`: `FitnessWeights` to override, by name.
+/// - `group_names`, `subject_names`, `lecturer_names`: display names by id.
+pub fn parse_problem(value: &Value) -> Result<Problem, LoadError> {
+    let version = field(value, "", "schema_version")?
+        .as_u64()
+        .ok_or_else(|| LoadError::WrongType {
+            path: "schema_version".to_string(),
+            expected: "a non-negative integer",
+        })?;
+    let value = &upgrade(value, version)?;
+    let mut consecutive = HashSet::new();
+    let group_requirements = array(
+        field(value, "", "groups_subjects_hours")?,
+        "groups_subjects_hours",
+    )?
+    .iter()
+    .enumerate()
+    .map(|(group, value)| {
+        let path = format!("groups_subjects_hours[{group}]");
+        let reqs = array(value, &path)?
+            .iter()
+            .enumerate()
+            .map(|(i, obj)| {
+                let path = format!("{path}[{i}]");
+                let a = number(field(obj, &path, "subject")?, &format!("{path}.subject"))?;
+                let b = number(field(obj, &path, "hours")?, &format!("{path}.hours"))?;
+                if let Some(value) = obj.get("consecutive") {
+                    let is_consecutive = value.as_bool().ok_or_else(|| LoadError::WrongType {
+                        path: format!("{path}.consecutive"),
+                        expected: "a boolean",
+                    })?;
+                    if is_consecutive {
+                        consecutive.insert((group, a));
+                    }
+                }
+
+                Ok((a, b))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((group, reqs))
+    })
+    .collect::<Result<_, _>>()?;
+
+    let lecturer_requirements = array(field(value, "", "teachers_hours")?, "teachers_hours")?
+        .iter()
+        .enumerate()
+        .map(|(lecturer, value)| {
+            let hours = number(value, &format!("teachers_hours[{lecturer}]"))?;
+            Ok((lecturer, hours))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut suitability = HashMap::new();
+    let subject_requirements = array(field(value, "", "subjects_teachers")?, "subjects_teachers")?
+        .iter()
+        .enumerate()
+        .map(|(subject, value)| {
+            let path = format!("subjects_teachers[{subject}]");
+            let reqs = array(value, &path)?
+                .iter()
+                .enumerate()
+                .map(|(i, obj)| {
+                    let path = format!("{path}[{i}]");
+                    if !obj.is_object() {
+                        return number(obj, &path);
+                    }
+                    let lecturer =
+                        number(field(obj, &path, "lecturer")?, &format!("{path}.lecturer"))?;
+                    let weight = field(obj, &path, "weight")?
+                        .as_u64()
+                        .and_then(|weight| u32::try_from(weight).ok())
+                        .ok_or_else(|| LoadError::WrongType {
+                            path: format!("{path}.weight"),
+                            expected: "a non-negative integer",
+                        })?;
+                    if weight != 1 {
+                        suitability.insert((subject, lecturer), weight);
+                    }
+                    Ok(lecturer)
+                })
+                .collect::<Result<_, _>>()?;
+            Ok((subject, reqs))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut problem = Problem::new(
+        group_requirements,
+        lecturer_requirements,
+        subject_requirements,
+    );
+    problem.consecutive = consecutive;
+    problem.lecturer_suitability = suitability;
+    if let Some(value) = value.get("total_hours") {
+        problem.total_hours = number(value, "total_hours")?;
+    }
+    if let Some(value) = value.get("group_sizes") {
+        problem.group_sizes = numbers(value, "group_sizes")?;
+    }
+    if let Some(value) = value.get("room_capacities") {
+        problem.room_capacities = numbers(value, "room_capacities")?;
+        problem.room_count = problem.room_capacities.len();
+    }
+    if let Some(value) = value.get("room_count") {
+        problem.room_count = number(value, "room_count")?;
+    }
+    if let Some(value) = value.get("forbidden_periods") {
+        problem.forbidden_periods = array(value, "forbidden_periods")?
+            .iter()
+            .enumerate()
+            .map(|(i, period)| number(period, &format!("forbidden_periods[{i}]")))
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(value) = value.get("max_period_per_day") {
+        problem.limit_periods_per_day(number(value, "max_period_per_day")?);
+    }
+    if let Some(value) = value.get("lecturer_unavailable") {
+        problem.lecturer_unavailable = array(value, "lecturer_unavailable")?
+            .iter()
+            .enumerate()
+            .map(|(lecturer, value)| {
+                let path = format!("lecturer_unavailable[{lecturer}]");
+                let hours = array(value, &path)?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, hour)| number(hour, &format!("{path}[{i}]")))
+                    .collect::<Result<HashSet<_>, _>>()?;
+                Ok((lecturer, hours))
+            })
+            .collect::<Result<_, _>>()?;
+        problem
+            .lecturer_unavailable
+            .retain(|_, hours| !hours.is_empty());
+    }
+    if let Some(value) = value.get("max_hours_per_day") {
+        problem.max_hours_per_day = numbers(value, "max_hours_per_day")?;
+    }
+    if let Some(value) = value.get("max_consecutive") {
+        problem.max_consecutive = numbers(value, "max_consecutive")?;
+    }
+    if let Some(value) = value.get("default_max_hours_per_day") {
+        problem.default_max_hours_per_day = Some(number(value, "default_max_hours_per_day")?);
+    }
+    if let Some(value) = value.get("lecturer_preferences") {
+        problem.lecturer_preferences = array(value, "lecturer_preferences")?
+            .iter()
+            .enumerate()
+            .map(|(lecturer, value)| {
+                let path = format!("lecturer_preferences[{lecturer}]");
+                let hours = array(value, &path)?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, obj)| {
+                        let path = format!("{path}[{i}]");
+                        let hour = number(field(obj, &path, "hour")?, &format!("{path}.hour"))?;
+                        let score = integer(field(obj, &path, "score")?, &format!("{path}.score"))?;
+                        Ok((hour, score))
+                    })
+                    .collect::<Result<HashMap<_, _>, _>>()?;
+                Ok((lecturer, hours))
+            })
+            .collect::<Result<_, _>>()?;
+        problem
+            .lecturer_preferences
+            .retain(|_, hours| !hours.is_empty());
+    }
+    if let Some(value) = value.get("pinned") {
+        problem.pinned = array(value, "pinned")?
+            .iter()
+            .enumerate()
+            .map(|(i, obj)| {
+                let path = format!("pinned[{i}]");
+                let get = |name| number(field(obj, &path, name)?, &format!("{path}.{name}"));
+                Ok((
+                    get("group")?,
+                    get("subject")?,
+                    get("lecturer")?,
+                    get("hour")?,
+                ))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(value) = value.get("group_weights") {
+        problem.group_weights = numbers(value, "group_weights")?
+            .into_iter()
+            .map(|(group, weight)| (group, weight as i64))
+            .collect();
+    }
+    if let Some(value) = value.get("rotate_subjects") {
+        problem.rotate_subjects = array(value, "rotate_subjects")?
+            .iter()
+            .enumerate()
+            .map(|(i, subject)| number(subject, &format!("rotate_subjects[{i}]")))
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(value) = value.get("lab_lectures") {
+        problem.lab_lectures = array(value, "lab_lectures")?
+            .iter()
+            .enumerate()
+            .map(|(i, obj)| {
+                let path = format!("lab_lectures[{i}]");
+                let get = |name| number(field(obj, &path, name)?, &format!("{path}.{name}"));
+                Ok((get("lab")?, get("lecture")?))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(value) = value.get("must_be_first") {
+        problem.must_be_first = array(value, "must_be_first")?
+            .iter()
+            .enumerate()
+            .map(|(i, obj)| {
+                let path = format!("must_be_first[{i}]");
+                let get = |name| number(field(obj, &path, name)?, &format!("{path}.{name}"));
+                Ok((get("group")?, get("subject")?))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(value) = value.get("group_disliked_hours") {
+        problem.group_disliked_hours = array(value, "group_disliked_hours")?
+            .iter()
+            .enumerate()
+            .map(|(group, value)| {
+                let path = format!("group_disliked_hours[{group}]");
+                let hours = array(value, &path)?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, hour)| number(hour, &format!("{path}[{i}]")))
+                    .collect::<Result<HashSet<_>, _>>()?;
+                Ok((group, hours))
+            })
+            .collect::<Result<_, _>>()?;
+        problem
+            .group_disliked_hours
+            .retain(|_, hours| !hours.is_empty());
+    }
+    if let Some(value) = value.get("group_daily_min") {
+        problem.group_daily_min = numbers(value, "group_daily_min")?;
+    }
+    if let Some(value) = value.get("group_daily_max") {
+        problem.group_daily_max = numbers(value, "group_daily_max")?;
+    }
+    if let Some(value) = value.get("subject_difficulty") {
+        problem.subject_difficulty = array(value, "subject_difficulty")?
+            .iter()
+            .enumerate()
+            .map(|(subject, value)| {
+                let path = format!("subject_difficulty[{subject}]");
+                let difficulty = number(value, &path)?;
+                let difficulty = u8::try_from(difficulty).map_err(|_| LoadError::WrongType {
+                    path,
+                    expected: "an integer from 0 to 255",
+                })?;
+                Ok((subject, difficulty))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(value) = value.get("room_change_needs_gap") {
+        problem.room_change_needs_gap = value.as_bool().ok_or_else(|| LoadError::WrongType {
+            path: "room_change_needs_gap".to_string(),
+            expected: "a boolean",
+        })?;
+    }
+    if let Some(value) = value.get("lecture_before_lab") {
+        problem.lecture_before_lab = value.as_bool().ok_or_else(|| LoadError::WrongType {
+            path: "lecture_before_lab".to_string(),
+            expected: "a boolean",
+        })?;
+    }
+    if let Some(value) = value.get("weights") {
+        let object = value.as_object().ok_or_else(|| LoadError::WrongType {
+            path: "weights".to_string(),
+            expected: "an object",
+        })?;
+        for (name, value) in object {
+            let weight = number(value, &format!("weights.{name}"))?;
+            *problem
+                .weights
+                .get_mut(name)
+                .ok_or_else(|| LoadError::UnknownWeight { name: name.clone() })? = weight as i64;
+        }
+    }
+    if let Some(value) = value.get("group_names") {
+        problem.group_names = strings(value, "group_names")?;
+    }
+    if let Some(value) = value.get("subject_names") {
+        problem.subject_names = strings(value, "subject_names")?;
+    }
+    if let Some(value) = value.get("lecturer_names") {
+        problem.lecturer_names = strings(value, "lecturer_names")?;
+    }
+    Ok(problem)
+}
+
+/// `value`, a file of schema version `version`, in the current format.
+fn upgrade(value: &Value, version: u64) -> Result<Value, LoadError> {
+    let mut value = value.clone();
+    match version {
+        // Weeks were always 20 hours long.
+        1 => value["total_hours"] = DEFAULT_HOURS.into(),
+        SCHEMA_VERSION => {}
+        version => return Err(LoadError::UnsupportedVersion { version }),
+    }
+    value["schema_version"] = SCHEMA_VERSION.into();
+    Ok(value)
+}
+
+fn field<'a>(value: &'a Value, path: &str, field: &'static str) -> Result<&'a Value, LoadError> {
+    let object = value.as_object().ok_or_else(|| LoadError::WrongType {
+        path: path.to_string(),
+        expected: "an object",
+    })?;
+    object.get(field).ok_or_else(|| LoadError::MissingField {
+        path: path.to_string(),
+        field,
+    })
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, LoadError> {
+    value.as_array().ok_or_else(|| LoadError::WrongType {
+        path: path.to_string(),
+        expected: "an array",
+    })
+}
+
+/// An array of numbers, keyed by their index.
+fn numbers(value: &Value, path: &str) -> Result<HashMap<usize, usize>, LoadError> {
+    array(value, path)?
+        .iter()
+        .enumerate()
+        .map(|(i, value)| Ok((i, number(value, &format!("{path}[{i}]"))?)))
+        .collect()
+}
+
+/// An array of strings, keyed by their index.
+fn strings(value: &Value, path: &str) -> Result<HashMap<usize, String>, LoadError> {
+    array(value, path)?
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let string = value.as_str().ok_or_else(|| LoadError::WrongType {
+                path: format!("{path}[{i}]"),
+                expected: "a string",
+            })?;
+            Ok((i, string.to_string()))
+        })
+        .collect()
+}
+
+fn integer(value: &Value, path: &str) -> Result<i32, LoadError> {
+    value
+        .as_i64()
+        .and_then(|number| i32::try_from(number).ok())
+        .ok_or_else(|| LoadError::WrongType {
+            path: path.to_string(),
+            expected: "an integer",
+        })
+}
+
+fn number(value: &Value, path: &str) -> Result<usize, LoadError> {
+    value
+        .as_u64()
+        .map(|number| number as usize)
+        .ok_or_else(|| LoadError::WrongType {
+            path: path.to_string(),
+            expected: "a non-negative integer",
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn older_schema_versions_are_upgraded() {
+        let file = json!({
+            "schema_version": 1,
+            "groups_subjects_hours": [],
+            "teachers_hours": [],
+            "subjects_teachers": [],
+        });
+        assert_eq!(parse_problem(&file).unwrap().total_hours, DEFAULT_HOURS);
+
+        let mut file = file;
+        file["schema_version"] = (SCHEMA_VERSION + 1).into();
+        let error = parse_problem(&file).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "unsupported schema_version {}, expected 1 to {SCHEMA_VERSION}",
+                SCHEMA_VERSION + 1
+            )
+        );
+
+        file.as_object_mut().unwrap().remove("schema_version");
+        let error = parse_problem(&file).unwrap_err();
+        assert!(matches!(
+            error,
+            LoadError::MissingField {
+                field: "schema_version",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parses_the_constraints_format() {
+        let problem = parse_problem(&json!({
+            "schema_version": 2,
+            "groups_subjects_hours": [[
+                {"subject": 0, "hours": 2, "consecutive": true},
+                {"subject": 1, "hours": 1, "consecutive": false},
+            ]],
+            "teachers_hours": [3, 4],
+            "subjects_teachers": [[0], [0, {"lecturer": 1, "weight": 3}]],
+        }))
+        .unwrap();
+        assert_eq!(problem.group_requirements[&0], vec![(0, 2), (1, 1)]);
+        assert_eq!(problem.lecturer_requirements[&1], 4);
+        assert_eq!(problem.subject_requirements[&1], vec![0, 1]);
+        assert_eq!(
+            (problem.suitability(1, 0), problem.suitability(1, 1)),
+            (1, 3)
+        );
+        assert_eq!(problem.consecutive, [(0, 0)].into_iter().collect());
+        assert_eq!(problem.room_count, 0);
+
+        let problem = parse_problem(&json!({
+            "schema_version": 2,
+            "groups_subjects_hours": [],
+            "teachers_hours": [],
+            "subjects_teachers": [],
+            "group_sizes": [25],
+            "group_weights": [3],
+            "total_hours": 24,
+            "room_capacities": [30, 20],
+            "lecturer_unavailable": [[], [0, 19]],
+            "forbidden_periods": [0],
+            "max_period_per_day": 2,
+            "weights": {"gaps": 3, "room_clash": 20},
+            "max_hours_per_day": [2],
+            "default_max_hours_per_day": 3,
+            "max_consecutive": [2, 3],
+            "lecturer_names": ["Dr. Shevchenko"],
+            "pinned": [{"group": 0, "subject": 1, "lecturer": 1, "hour": 7}],
+            "lecturer_preferences": [[], [{"hour": 0, "score": 2}, {"hour": 19, "score": -1}]],
+            "rotate_subjects": [1],
+            "lab_lectures": [{"lab": 2, "lecture": 1}],
+            "lecture_before_lab": true,
+            "must_be_first": [{"group": 0, "subject": 2}],
+            "group_disliked_hours": [[], [4, 5]],
+            "room_change_needs_gap": true,
+            "group_daily_min": [2],
+            "group_daily_max": [3, 4],
+            "subject_difficulty": [0, 5],
+        }))
+        .unwrap();
+        assert!(!problem.lecturer_unavailable.contains_key(&0));
+        assert_eq!(problem.forbidden_periods, [0, 3].into());
+        assert_eq!(
+            problem.lecturer_unavailable[&1],
+            [0, 19].into_iter().collect()
+        );
+        assert_eq!(problem.room_count, 2);
+        assert_eq!((problem.total_hours, problem.days()), (24, 6));
+        assert_eq!(problem.group_sizes[&0], 25);
+        assert_eq!((problem.group_weight(0), problem.group_weight(1)), (3, 1));
+        assert_eq!(problem.room_capacities[&1], 20);
+        assert_eq!(
+            (problem.daily_cap(0), problem.daily_cap(1)),
+            (Some(2), Some(3))
+        );
+        assert_eq!(problem.max_consecutive, [(0, 2), (1, 3)].into());
+        assert_eq!(problem.pinned, vec![(0, 1, 1, 7)]);
+        assert_eq!(problem.rotate_subjects, [1].into());
+        assert_eq!(problem.lab_lectures, [(2, 1)].into());
+        assert!(problem.lecture_before_lab);
+        assert_eq!(problem.must_be_first, [(0, 2)].into());
+        assert!(problem.room_change_needs_gap);
+        assert_eq!(problem.group_daily_min, [(0, 2)].into());
+        assert_eq!(problem.group_daily_max, [(0, 3), (1, 4)].into());
+        assert_eq!(problem.subject_difficulty, [(0, 0), (1, 5)].into());
+        assert_eq!(problem.group_disliked_hours, [(1, [4, 5].into())].into());
+        assert!(!problem.lecturer_preferences.contains_key(&0));
+        assert_eq!(
+            (problem.preference(1, 0), problem.preference(1, 19)),
+            (2, -1)
+        );
+        assert_eq!(
+            (problem.lecturer_label(0), problem.lecturer_label(1)),
+            ("Dr. Shevchenko".to_string(), "1".to_string())
+        );
+        assert_eq!(
+            problem.weights,
+            FitnessWeights {
+                gaps: 3,
+                room_clash: 20,
+                ..FitnessWeights::default()
+            }
+        );
+
+        let error = parse_problem(&json!({
+            "schema_version": 2,
+            "groups_subjects_hours": [],
+            "teachers_hours": [],
+            "subjects_teachers": [],
+            "weights": {"gap": 3},
+        }))
+        .unwrap_err();
+        assert!(matches!(&error, LoadError::UnknownWeight { name } if name == "gap"));
+    }
+
+    #[test]
+    fn reports_a_missing_field_with_its_path() {
+        let error = parse_problem(&json!({
+            "schema_version": 2,
+            "groups_subjects_hours": [[], [], [{"subject": 0, "hours": 2}, {"subject": 1, "hour": 1}]],
+            "teachers_hours": [3],
+            "subjects_teachers": [[0], [0]],
+        }))
+        .unwrap_err();
+        assert!(matches!(
+            &error,
+            LoadError::MissingField { path, field: "hours" } if path == "groups_subjects_hours[2][1]"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "missing field `hours` in groups_subjects_hours[2][1]"
+        );
+
+        let error =
+            parse_problem(&json!({"schema_version": 2, "groups_subjects_hours": []})).unwrap_err();
+        assert!(matches!(
+            &error,
+            LoadError::MissingField { path, field: "teachers_hours" } if path.is_empty()
+        ));
+    }
+
+    #[test]
+    fn reports_a_missing_file() {
+        let path = Path::new("no/such/constraints.json");
+        let error = load_problem(path).unwrap_err();
+        assert!(matches!(&error, LoadError::NotFound(missing) if missing == path));
+        assert_eq!(error.to_string(), "no/such/constraints.json does not exist");
+    }
+
+    #[test]
+    fn reports_a_wrong_type_with_its_path() {
+        let error = parse_problem(&json!({
+            "schema_version": 2,
+            "groups_subjects_hours": [],
+            "teachers_hours": [3, -1],
+            "subjects_teachers": [],
+        }))
+        .unwrap_err();
+        assert!(matches!(
+            &error,
+            LoadError::WrongType { path, expected: "a non-negative integer" } if path == "teachers_hours[1]"
+        ));
+
+        let error = parse_problem(&json!({
+            "schema_version": 2,
+            "groups_subjects_hours": [],
+            "teachers_hours": [],
+            "subjects_teachers": {"0": [1]},
+        }))
+        .unwrap_err();
+        assert!(matches!(
+            &error,
+            LoadError::WrongType { path, expected: "an array" } if path == "subjects_teachers"
+        ));
+
+        let error = parse_problem(&json!([])).unwrap_err();
+        assert!(matches!(
+            &error,
+            LoadError::WrongType { path, expected: "an object" } if path.is_empty()
+        ));
+    }
+
+    #[test]
+    fn schedules_written_as_json_load_back() {
+        let mut problem = crate::dataset::small_example();
+        problem.weeks = 2;
+        problem.total_hours = 40;
+        let context = Context::new(problem);
+        let genes_per_week = context.group_subjects.len() / 2;
+        let genome: Genome = context
+            .group_subjects
+            .iter()
+            .enumerate()
+            .map(|(index, (_, subject))| {
+                let hour = index / genes_per_week * 40 + index % genes_per_week;
+                Dna((*subject, 3, 0, TimeSlot::from_index(hour)))
+            })
+            .collect();
+        let lessons = crate::schedule::lessons(&context, &genome);
+        let mut file = json!({ "lessons": lessons });
+        assert_eq!(parse_schedule(&file, &context).unwrap(), genome);
+
+        // Group 0's first lesson, of subject 0 in the first week.
+        file["lessons"].as_array_mut().unwrap().remove(0);
+        assert!(matches!(
+            parse_schedule(&file, &context).unwrap_err(),
+            LoadError::LessonMismatch {
+                group: 0,
+                subject: 0,
+                week: 0
+            }
+        ));
+    }
+}
@@ -0,0 +1,3187 @@
+pub mod analysis;
+pub mod cache;
+pub mod cnf;
+pub mod dashboard;
+pub mod dataset;
+pub mod dot;
+pub mod feasible;
+pub mod fet;
+pub mod hyper;
+pub mod i18n;
+pub mod ics;
+pub mod input;
+pub mod merge;
+pub mod proof;
+pub mod repair;
+pub mod schedule;
+pub mod solver;
+pub mod telemetry;
+pub mod workload;
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use genevo::operator::{GeneticOperator, MutationOp};
+use genevo::prelude::*;
+use genevo::random::{random_index, SliceRandom};
+use serde::{Deserialize, Serialize};
+
+pub use solver::{
+    solve, solve_iter, solve_with, solve_with_builder, Generation, Solution, SolverParams, Step,
+};
+
+pub type GroupId = usize;
+pub type SubjectId = usize;
+pub type LecturerId = usize;
+pub type RoomId = usize;
+
+pub const PERIODS_PER_DAY: usize = 4;
+pub const DEFAULT_HOURS: usize = 5 * PERIODS_PER_DAY; // time slots per week unless a problem sets `total_hours`.
+
+/// A period of a day. Days count on across weeks, week `w` of a problem with `d` days a week
+/// has the days `w * d..(w + 1) * d`, so slots order the same as their `index`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
+pub struct TimeSlot {
+    pub day: u8,
+    pub period: u8,
+}
+
+impl TimeSlot {
+    /// The slot at a dense index `0..total_hours * weeks`.
+    pub fn from_index(index: usize) -> Self {
+        Self {
+            day: u8::try_from(index / PERIODS_PER_DAY).expect("more days than a u8 holds"),
+            period: (index % PERIODS_PER_DAY) as u8,
+        }
+    }
+
+    /// Dense index of the slot, for per-slot arrays.
+    pub fn index(self) -> usize {
+        usize::from(self.day) * PERIODS_PER_DAY + usize::from(self.period)
+    }
+
+    /// The week of the slot, with `days` days a week (`Problem::days`).
+    pub fn week(self, days: usize) -> usize {
+        usize::from(self.day) / days
+    }
+}
+
+impl std::fmt::Display for TimeSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "day {}, period {}", self.day, self.period)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Dna(pub (SubjectId, LecturerId, RoomId, TimeSlot));
+
+pub type Genome = Vec<Dna>; // (lecturer, room, slot) for the corresponding (group, subject) from `Context::group_subjects`.
+
+/// How much each constraint counts towards fitness. Every lesson earns `lesson` and loses
+/// `group_clash` if its group is busy, plus the largest of the weights its lecturer and room
+/// break; `gaps` is lost per idle period in a group's day, and `repeated_subject` per lesson
+/// on a day its group already has the subject, unless every day of the week has it. Soft
+/// terms off by default, like `early_start`, weigh 0. Keep the hard constraint weights well
+/// above these, so that no amount of compacting makes up for a clash. As JSON, an object by
+/// the names in `NAMES`, where missing weights keep their default.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FitnessWeights {
+    pub lesson: i64,
+    pub group_clash: i64,
+    pub lecturer_clash: i64,          // also teaching at an unavailable hour.
+    pub over_allocated_lecturer: i64, // beyond the lecturer's weekly hours.
+    pub room_clash: i64,              // also a room too small for the group.
+    pub gaps: i64,
+    pub split_pair: i64, // per double lesson whose two hours aren't adjacent periods of a day.
+    pub daily_overload: i64, // per hour a lecturer teaches beyond their daily cap.
+    pub moved_pin: i64,  // per pinned lesson not at its lecturer and hour.
+    pub preference: i64, // per preference point of a clash-free lesson's hour, best kept small.
+    pub repeated_subject: i64, // per avoidable repeat of a group's subject on one day.
+    pub long_run: i64,   // per period a lecturer teaches past their cap on periods in a row.
+    pub suitability: i64, // per suitability point above 1 of a clash-free lesson's lecturer.
+    pub early_start: i64, // per group day whose first open period has a lesson.
+    pub rotation: i64,   // per lesson of a rotating subject by its lecturer of the week before.
+    pub lab_before_lecture: i64, // per lab taught before its lecture on the same day.
+    pub not_first: i64,  // per lesson meant to start its group's day that comes after another.
+    pub disliked_hour: i64, // per lesson at an hour its group would rather not have one.
+    pub room_change: i64, // per lecturer moving rooms between periods in a row.
+    pub daily_band: i64, // per day a group has lessons on but fewer or more than its band allows.
+    pub stacked_difficulty: i64, // per point of difficulty of the easier of two subjects a group has back to back.
+}
+
+impl Default for FitnessWeights {
+    fn default() -> Self {
+        Self {
+            lesson: 10,
+            group_clash: 10,
+            lecturer_clash: 10,
+            over_allocated_lecturer: 10,
+            room_clash: 10,
+            gaps: 1,
+            split_pair: 10,
+            daily_overload: 10,
+            moved_pin: 10,
+            preference: 1,
+            repeated_subject: 1,
+            long_run: 10,
+            suitability: 1,
+            early_start: 0,
+            rotation: 1,
+            lab_before_lecture: 1,
+            not_first: 1,
+            disliked_hour: 1,
+            room_change: 1,
+            daily_band: 1,
+            stacked_difficulty: 1,
+        }
+    }
+}
+
+impl FitnessWeights {
+    pub const NAMES: [&'static str; 21] = [
+        "lesson",
+        "group_clash",
+        "lecturer_clash",
+        "over_allocated_lecturer",
+        "room_clash",
+        "gaps",
+        "split_pair",
+        "daily_overload",
+        "moved_pin",
+        "preference",
+        "repeated_subject",
+        "long_run",
+        "suitability",
+        "early_start",
+        "rotation",
+        "lab_before_lecture",
+        "not_first",
+        "disliked_hour",
+        "room_change",
+        "daily_band",
+        "stacked_difficulty",
+    ];
+
+    /// The weight called `name`, one of `NAMES`.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut i64> {
+        match name {
+            "lesson" => Some(&mut self.lesson),
+            "group_clash" => Some(&mut self.group_clash),
+            "lecturer_clash" => Some(&mut self.lecturer_clash),
+            "over_allocated_lecturer" => Some(&mut self.over_allocated_lecturer),
+            "room_clash" => Some(&mut self.room_clash),
+            "gaps" => Some(&mut self.gaps),
+            "split_pair" => Some(&mut self.split_pair),
+            "daily_overload" => Some(&mut self.daily_overload),
+            "moved_pin" => Some(&mut self.moved_pin),
+            "preference" => Some(&mut self.preference),
+            "repeated_subject" => Some(&mut self.repeated_subject),
+            "long_run" => Some(&mut self.long_run),
+            "suitability" => Some(&mut self.suitability),
+            "early_start" => Some(&mut self.early_start),
+            "rotation" => Some(&mut self.rotation),
+            "lab_before_lecture" => Some(&mut self.lab_before_lecture),
+            "not_first" => Some(&mut self.not_first),
+            "disliked_hour" => Some(&mut self.disliked_hour),
+            "room_change" => Some(&mut self.room_change),
+            "daily_band" => Some(&mut self.daily_band),
+            "stacked_difficulty" => Some(&mut self.stacked_difficulty),
+            _ => None,
+        }
+    }
+
+    /// These weights with every soft term at 0, so that the fitness only counts lessons
+    /// and the hard constraints they break.
+    pub fn hard_only(self) -> Self {
+        Self {
+            gaps: 0,
+            preference: 0,
+            repeated_subject: 0,
+            suitability: 0,
+            early_start: 0,
+            rotation: 0,
+            lab_before_lecture: 0,
+            not_first: 0,
+            disliked_hour: 0,
+            room_change: 0,
+            daily_band: 0,
+            stacked_difficulty: 0,
+            ..self
+        }
+    }
+
+    /// Sets the weight called `name`, one of `NAMES`, to `weight`, which can't be negative:
+    /// fitness subtracts penalties, so a negative one would reward what it penalizes.
+    pub fn set(&mut self, name: &str, weight: i64) -> Result<(), WeightError> {
+        let slot = self.get_mut(name).ok_or_else(|| WeightError::Unknown {
+            name: name.to_string(),
+        })?;
+        if weight < 0 {
+            return Err(WeightError::Negative {
+                name: name.to_string(),
+                weight,
+            });
+        }
+        *slot = weight;
+        Ok(())
+    }
+
+    /// Checks every weight as `set` does, for weights read all at once.
+    pub fn validate(&self) -> Result<(), WeightError> {
+        let mut weights = *self;
+        for name in Self::NAMES {
+            let weight = *weights.get_mut(name).expect("`NAMES` are all weights");
+            weights.set(name, weight)?;
+        }
+        Ok(())
+    }
+
+    /// The most a single lesson's lecturer and room can cost it.
+    fn largest_resource_penalty(&self) -> i64 {
+        self.lecturer_clash
+            .max(self.over_allocated_lecturer)
+            .max(self.room_clash)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub group_requirements: HashMap<GroupId, Vec<(SubjectId, usize)>>, // list of (subject, hours) for each group.
+    pub lecturer_requirements: HashMap<LecturerId, usize>,             // hours for each lecturer.
+    pub subject_requirements: HashMap<SubjectId, Vec<LecturerId>>, // suitable lecturers for each subject.
+    pub lecturer_suitability: HashMap<(SubjectId, LecturerId), u32>, // how well a lecturer suits a subject, 1 without one.
+    pub weeks: usize, // number of repeated weeks, lecturer hours are a per-week budget.
+    pub total_hours: usize, // time slots per week, whole days of `PERIODS_PER_DAY` periods.
+    pub room_count: usize, // rooms `0..room_count`, 0 leaves rooms out: every lesson gets room 0.
+    pub group_sizes: HashMap<GroupId, usize>, // students per group, a group without one fits any room.
+    pub room_capacities: HashMap<RoomId, usize>, // seats per room, a room without one fits any group.
+    pub lecturer_unavailable: HashMap<LecturerId, HashSet<usize>>, // hours of every week (`0..total_hours`) a lecturer can't teach.
+    pub forbidden_periods: HashSet<usize>, // periods of every day (`0..PERIODS_PER_DAY`) no lesson may take.
+    pub consecutive: HashSet<(GroupId, SubjectId)>, // requirements taught as double lessons, two adjacent periods each.
+    pub max_hours_per_day: HashMap<LecturerId, usize>, // per-lecturer daily caps, overriding the default.
+    pub default_max_hours_per_day: Option<usize>,      // daily cap for lecturers without their own.
+    pub max_consecutive: HashMap<LecturerId, usize>, // most periods in a row a lecturer teaches, unlimited without one.
+    pub lecturer_preferences: HashMap<LecturerId, HashMap<usize, i32>>, // how much a lecturer likes (or, below 0, dislikes) teaching at hours of every week.
+    pub pinned: Vec<(GroupId, SubjectId, LecturerId, usize)>, // lessons fixed in advance, at a slot index (`0..total_hours * weeks`).
+    pub group_weights: HashMap<GroupId, i64>, // how much each group's lessons count towards fitness, 1 without one.
+    pub rotate_subjects: HashSet<SubjectId>, // subjects whose lecturers should change from week to week.
+    pub lab_lectures: HashMap<SubjectId, SubjectId>, // the lecture subject of each lab subject.
+    pub lecture_before_lab: bool, // whether labs should follow their lectures on days with both.
+    pub must_be_first: HashSet<(GroupId, SubjectId)>, // requirements whose lessons should start the group's day.
+    pub group_disliked_hours: HashMap<GroupId, HashSet<usize>>, // hours of every week (`0..total_hours`) a group would rather not have lessons at.
+    pub room_change_needs_gap: bool, // whether lecturers changing rooms should have a free period in between.
+    pub group_daily_min: HashMap<GroupId, usize>, // fewest hours a group should come in for on a day with lessons.
+    pub group_daily_max: HashMap<GroupId, usize>, // most hours a group should have on a day.
+    pub subject_difficulty: HashMap<SubjectId, u8>, // how hard each subject is, 0 without one.
+    pub weights: FitnessWeights,
+    pub group_names: HashMap<GroupId, String>, // display names, output falls back to ids without one.
+    pub subject_names: HashMap<SubjectId, String>,
+    pub lecturer_names: HashMap<LecturerId, String>,
+}
+
+impl Problem {
+    pub fn new(
+        group_requirements: HashMap<GroupId, Vec<(SubjectId, usize)>>,
+        lecturer_requirements: HashMap<LecturerId, usize>,
+        subject_requirements: HashMap<SubjectId, Vec<LecturerId>>,
+    ) -> Self {
+        Self {
+            group_requirements,
+            lecturer_requirements,
+            subject_requirements,
+            lecturer_suitability: HashMap::new(),
+            weeks: 1,
+            total_hours: DEFAULT_HOURS,
+            room_count: 0,
+            group_sizes: HashMap::new(),
+            room_capacities: HashMap::new(),
+            lecturer_unavailable: HashMap::new(),
+            forbidden_periods: HashSet::new(),
+            consecutive: HashSet::new(),
+            max_hours_per_day: HashMap::new(),
+            default_max_hours_per_day: None,
+            max_consecutive: HashMap::new(),
+            lecturer_preferences: HashMap::new(),
+            pinned: vec![],
+            group_weights: HashMap::new(),
+            rotate_subjects: HashSet::new(),
+            lab_lectures: HashMap::new(),
+            lecture_before_lab: false,
+            must_be_first: HashSet::new(),
+            group_disliked_hours: HashMap::new(),
+            room_change_needs_gap: false,
+            group_daily_min: HashMap::new(),
+            group_daily_max: HashMap::new(),
+            subject_difficulty: HashMap::new(),
+            weights: FitnessWeights::default(),
+            group_names: HashMap::new(),
+            subject_names: HashMap::new(),
+            lecturer_names: HashMap::new(),
+        }
+    }
+
+    /// The display name of `group`, its id if it has none.
+    pub fn group_label(&self, group: GroupId) -> String {
+        label(&self.group_names, group)
+    }
+
+    /// The display name of `subject`, its id if it has none.
+    pub fn subject_label(&self, subject: SubjectId) -> String {
+        label(&self.subject_names, subject)
+    }
+
+    /// The display name of `lecturer`, its id if it has none.
+    pub fn lecturer_label(&self, lecturer: LecturerId) -> String {
+        label(&self.lecturer_names, lecturer)
+    }
+
+    /// The most hours `lecturer` may teach on one day, `None` if unlimited.
+    pub fn daily_cap(&self, lecturer: LecturerId) -> Option<usize> {
+        self.max_hours_per_day
+            .get(&lecturer)
+            .copied()
+            .or(self.default_max_hours_per_day)
+    }
+
+    /// How much `lecturer` likes teaching at `hour` of a week (`0..total_hours`), 0 if indifferent.
+    pub fn preference(&self, lecturer: LecturerId, hour: usize) -> i64 {
+        self.lecturer_preferences
+            .get(&lecturer)
+            .and_then(|hours| hours.get(&hour))
+            .map_or(0, |&score| i64::from(score))
+    }
+
+    /// How well `lecturer` suits `subject` compared to its other lecturers, 1 by default.
+    pub fn suitability(&self, subject: SubjectId, lecturer: LecturerId) -> i64 {
+        self.lecturer_suitability
+            .get(&(subject, lecturer))
+            .map_or(1, |&suitability| i64::from(suitability))
+    }
+
+    /// How many times over the lessons of `group` count towards fitness.
+    pub fn group_weight(&self, group: GroupId) -> i64 {
+        self.group_weights.get(&group).copied().unwrap_or(1)
+    }
+
+    /// Whether `lecturer` can teach at `hour` of a week (`0..total_hours`), which no one can
+    /// in a forbidden period.
+    pub fn is_available(&self, lecturer: LecturerId, hour: usize) -> bool {
+        !self.forbidden_periods.contains(&(hour % PERIODS_PER_DAY))
+            && self
+                .lecturer_unavailable
+                .get(&lecturer)
+                .is_none_or(|hours| !hours.contains(&hour))
+    }
+
+    /// Whether `room` seats all students of `group`.
+    pub fn fits(&self, group: GroupId, room: RoomId) -> bool {
+        match (
+            self.group_sizes.get(&group),
+            self.room_capacities.get(&room),
+        ) {
+            (Some(size), Some(capacity)) => size <= capacity,
+            _ => true,
+        }
+    }
+
+    /// Days per week, the whole days `total_hours` makes up.
+    pub fn days(&self) -> usize {
+        self.total_hours / PERIODS_PER_DAY
+    }
+
+    /// Hours per week outside the forbidden periods.
+    pub fn open_hours(&self) -> usize {
+        let open = (0..PERIODS_PER_DAY)
+            .filter(|period| !self.forbidden_periods.contains(period))
+            .count();
+        self.days() * open
+    }
+
+    /// Forbids every period after `period`, so that no day's lessons run later than it.
+    pub fn limit_periods_per_day(&mut self, period: usize) {
+        self.forbidden_periods.extend(period + 1..PERIODS_PER_DAY);
+    }
+
+    /// Checks the problem for inconsistencies the solver can't cope with, reporting all of them.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        // Everything else is checked against the weeks, which have to make sense first.
+        if self.weeks == 0 {
+            return Err(vec![ValidationError::NoWeeks]);
+        }
+        let week = self.total_hours;
+        if week == 0 || !week.is_multiple_of(PERIODS_PER_DAY) {
+            return Err(vec![ValidationError::PartialDays { total_hours: week }]);
+        }
+        if self.days() * self.weeks > usize::from(u8::MAX) + 1 {
+            let days = self.days() * self.weeks;
+            return Err(vec![ValidationError::TooManyDays { days }]);
+        }
+        let mut errors = Vec::new();
+        let mut groups: Vec<_> = self.group_requirements.iter().collect();
+        groups.sort_by_key(|(group, _)| **group);
+        for (&group, subjects) in groups {
+            for &(subject, hours) in subjects {
+                if hours == 0 {
+                    errors.push(ValidationError::NoHours { group, subject });
+                }
+                if self
+                    .subject_requirements
+                    .get(&subject)
+                    .is_none_or(Vec::is_empty)
+                {
+                    errors.push(ValidationError::NoLecturers { group, subject });
+                } else if !self.subject_requirements[&subject]
+                    .iter()
+                    .any(|&lecturer| (0..week).any(|hour| self.is_available(lecturer, hour)))
+                {
+                    errors.push(ValidationError::NoAvailableLecturer { group, subject });
+                }
+            }
+            let hours = subjects.iter().map(|(_, hours)| hours).sum();
+            if hours > week {
+                errors.push(ValidationError::GroupOverloaded { group, hours, week });
+            }
+        }
+
+        let mut subjects: Vec<_> = self.subject_requirements.iter().collect();
+        subjects.sort_by_key(|(subject, _)| **subject);
+        for (&subject, lecturers) in subjects {
+            for &lecturer in lecturers {
+                if !self.lecturer_requirements.contains_key(&lecturer) {
+                    errors.push(ValidationError::UnknownLecturer { subject, lecturer });
+                }
+            }
+        }
+
+        // Every pin takes one of its requirement's hours in the week of its slot.
+        let mut pins: HashMap<(GroupId, SubjectId, usize), usize> = HashMap::new();
+        for &(group, subject, lecturer, hour) in &self.pinned {
+            let pins = pins.entry((group, subject, hour / week)).or_default();
+            *pins += 1;
+            let required = self
+                .group_requirements
+                .get(&group)
+                .into_iter()
+                .flatten()
+                .filter(|(required, _)| *required == subject)
+                .map(|(_, hours)| hours)
+                .sum();
+            if hour >= week * self.weeks || *pins > required {
+                errors.push(ValidationError::PinWithoutLesson {
+                    group,
+                    subject,
+                    hour,
+                });
+            }
+            let suitable = self
+                .subject_requirements
+                .get(&subject)
+                .is_some_and(|lecturers| lecturers.contains(&lecturer));
+            if !suitable {
+                errors.push(ValidationError::UnsuitablePin { subject, lecturer });
+            }
+        }
+
+        let mut unavailable: Vec<_> = self.lecturer_unavailable.iter().collect();
+        unavailable.sort_by_key(|(lecturer, _)| **lecturer);
+        for (&lecturer, hours) in unavailable {
+            let mut hours: Vec<_> = hours.iter().filter(|hour| **hour >= week).collect();
+            hours.sort();
+            for &hour in hours {
+                errors.push(ValidationError::UnavailableHourOutOfRange {
+                    lecturer,
+                    hour,
+                    week,
+                });
+            }
+        }
+
+        let mut forbidden: Vec<_> = self
+            .forbidden_periods
+            .iter()
+            .filter(|period| **period >= PERIODS_PER_DAY)
+            .collect();
+        forbidden.sort();
+        for &period in forbidden {
+            errors.push(ValidationError::ForbiddenPeriodOutOfRange { period });
+        }
+
+        let mut preferences: Vec<_> = self.lecturer_preferences.iter().collect();
+        preferences.sort_by_key(|(lecturer, _)| **lecturer);
+        for (&lecturer, hours) in preferences {
+            let mut hours: Vec<_> = hours.keys().filter(|hour| **hour >= week).collect();
+            hours.sort();
+            for &hour in hours {
+                errors.push(ValidationError::PreferenceHourOutOfRange {
+                    lecturer,
+                    hour,
+                    week,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks aggregate bounds no schedule can beat, before spending a whole run on the
+    /// problem: a week's lecturer budgets against the hours the groups need, overall and for
+    /// the subjects only one lecturer teaches, and each group's hours against a week.
+    pub fn feasibility_check(&self) -> FeasibilityReport {
+        let capacity = analysis::capacity_utilization(self);
+        let mut shortfalls = vec![];
+        if capacity.overall.demand > capacity.overall.capacity {
+            shortfalls.push(Shortfall::Overall {
+                needed: capacity.overall.demand,
+                allocated: capacity.overall.capacity,
+            });
+        }
+        for (lecturer, utilization) in capacity.lecturers {
+            if utilization.demand > utilization.capacity {
+                shortfalls.push(Shortfall::Lecturer {
+                    lecturer,
+                    needed: utilization.demand,
+                    allocated: utilization.capacity,
+                });
+            }
+        }
+        let mut groups: Vec<_> = self.group_requirements.iter().collect();
+        groups.sort_by_key(|(group, _)| **group);
+        for (&group, subjects) in groups {
+            let hours = subjects.iter().map(|(_, hours)| hours).sum();
+            if hours > self.open_hours() {
+                shortfalls.push(Shortfall::Group {
+                    group,
+                    hours,
+                    week: self.open_hours(),
+                });
+            }
+        }
+        FeasibilityReport { shortfalls }
+    }
+
+    /// One past the highest group id.
+    fn group_count(&self) -> usize {
+        self.group_requirements.keys().max().map_or(0, |id| id + 1)
+    }
+
+    /// One past the highest subject id with lecturers.
+    fn subject_count(&self) -> usize {
+        self.subject_requirements
+            .keys()
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+
+    /// One past the highest lecturer id that has a budget or can teach a subject.
+    fn lecturer_count(&self) -> usize {
+        self.lecturer_requirements
+            .keys()
+            .chain(self.subject_requirements.values().flatten())
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+}
+
+fn label(names: &HashMap<usize, String>, id: usize) -> String {
+    names.get(&id).cloned().unwrap_or_else(|| id.to_string())
+}
+
+/// A fitness weight `FitnessWeights::set` refuses.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum WeightError {
+    #[error("unknown weight `{name}`, expected one of {}", FitnessWeights::NAMES.join(", "))]
+    Unknown { name: String },
+    #[error("weight `{name}` must not be negative, got {weight}")]
+    Negative { name: String, weight: i64 },
+}
+
+/// An inconsistency in a `Problem`, see `Problem::validate`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ValidationError {
+    NoWeeks, // `weeks` is 0, leaving no time to schedule anything in.
+    NoHours {
+        group: GroupId,
+        subject: SubjectId,
+    },
+    NoLecturers {
+        group: GroupId,
+        subject: SubjectId,
+    },
+    NoAvailableLecturer {
+        group: GroupId,
+        subject: SubjectId,
+    }, // every lecturer of the subject is unavailable at every hour.
+    GroupOverloaded {
+        group: GroupId,
+        hours: usize,
+        week: usize,
+    }, // more required hours than the `week` has.
+    UnknownLecturer {
+        subject: SubjectId,
+        lecturer: LecturerId,
+    }, // no entry in `lecturer_requirements`.
+    UnavailableHourOutOfRange {
+        lecturer: LecturerId,
+        hour: usize,
+        week: usize,
+    },
+    PreferenceHourOutOfRange {
+        lecturer: LecturerId,
+        hour: usize,
+        week: usize,
+    },
+    ForbiddenPeriodOutOfRange {
+        period: usize,
+    },
+    PinWithoutLesson {
+        group: GroupId,
+        subject: SubjectId,
+        hour: usize,
+    }, // out of range, or more pins than the requirement has hours that week.
+    UnsuitablePin {
+        subject: SubjectId,
+        lecturer: LecturerId,
+    }, // pinned to a lecturer who can't teach the subject.
+    PartialDays {
+        total_hours: usize,
+    }, // a week that isn't a positive number of whole days.
+    TooManyDays {
+        days: usize,
+    }, // more days over all weeks than a `TimeSlot` can address.
+}
+
+impl ValidationError {
+    /// Whether the solver can't run on the problem at all. An overloaded group only makes
+    /// collisions unavoidable, the solver still minimizes them.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, ValidationError::GroupOverloaded { .. })
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationError::NoWeeks => write!(f, "the problem has 0 weeks, at least 1 is needed"),
+            ValidationError::NoHours { group, subject } => {
+                write!(f, "group {group} requires subject {subject} for 0 hours")
+            }
+            ValidationError::NoLecturers { group, subject } => write!(
+                f,
+                "group {group} requires subject {subject}, which no lecturer teaches"
+            ),
+            ValidationError::NoAvailableLecturer { group, subject } => write!(
+                f,
+                "group {group} requires subject {subject}, but all its lecturers are always unavailable"
+            ),
+            ValidationError::GroupOverloaded { group, hours, week } => write!(
+                f,
+                "group {group} requires {hours} hours, but a week only has {week}"
+            ),
+            ValidationError::UnknownLecturer { subject, lecturer } => write!(
+                f,
+                "subject {subject} is taught by lecturer {lecturer}, who has no hours"
+            ),
+            ValidationError::UnavailableHourOutOfRange {
+                lecturer,
+                hour,
+                week,
+            } => write!(
+                f,
+                "lecturer {lecturer} is unavailable at hour {hour}, but a week only has {week}"
+            ),
+            ValidationError::PreferenceHourOutOfRange {
+                lecturer,
+                hour,
+                week,
+            } => write!(
+                f,
+                "lecturer {lecturer} has a preference for hour {hour}, but a week only has {week}"
+            ),
+            ValidationError::ForbiddenPeriodOutOfRange { period } => write!(
+                f,
+                "period {period} is forbidden, but a day only has {PERIODS_PER_DAY}"
+            ),
+            ValidationError::PinWithoutLesson {
+                group,
+                subject,
+                hour,
+            } => write!(
+                f,
+                "a lesson of group {group}, subject {subject} is pinned to hour {hour}, \
+                 but there is no unpinned hour of it left in that week"
+            ),
+            ValidationError::UnsuitablePin { subject, lecturer } => write!(
+                f,
+                "a lesson of subject {subject} is pinned to lecturer {lecturer}, who doesn't teach it"
+            ),
+            ValidationError::PartialDays { total_hours } => write!(
+                f,
+                "a week of {total_hours} hours isn't a whole number of days of {PERIODS_PER_DAY} periods"
+            ),
+            ValidationError::TooManyDays { days } => write!(
+                f,
+                "the weeks add up to {days} days, but at most {} are supported",
+                usize::from(u8::MAX) + 1
+            ),
+        }
+    }
+}
+
+/// The bounds a problem breaks, see `Problem::feasibility_check`. Each one rules out a
+/// schedule satisfying every hard constraint, the solver can only minimize the violations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeasibilityReport {
+    pub shortfalls: Vec<Shortfall>,
+}
+
+impl FeasibilityReport {
+    pub fn is_clear(&self) -> bool {
+        self.shortfalls.is_empty()
+    }
+}
+
+/// Hours needed per week beyond the hours available for them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Shortfall {
+    Overall {
+        needed: usize,
+        allocated: usize,
+    }, // all group hours against all lecturer budgets.
+    Lecturer {
+        lecturer: LecturerId,
+        needed: usize,
+        allocated: usize,
+    }, // hours of the subjects only this lecturer teaches against their budget.
+    Group {
+        group: GroupId,
+        hours: usize,
+        week: usize,
+    }, // more hours than the `week` has outside forbidden periods.
+}
+
+impl std::fmt::Display for Shortfall {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Shortfall::Overall { needed, allocated } => write!(
+                f,
+                "the groups need {needed} teaching hours but the lecturers are allocated {allocated}"
+            ),
+            Shortfall::Lecturer {
+                lecturer,
+                needed,
+                allocated,
+            } => write!(
+                f,
+                "lecturer {lecturer} needs {needed} teaching hours but is allocated {allocated}"
+            ),
+            Shortfall::Group { group, hours, week } => write!(
+                f,
+                "group {group} needs {hours} hours but a week only has {week}"
+            ),
+        }
+    }
+}
+
+/// A problem together with its gene layout, i.e. everything a genome is interpreted against.
+/// Each solve owns its context, so several problems can be solved in one process.
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub problem: Problem,
+    /// The (group, subject) lesson every gene stands for, one entry per required hour. Laid
+    /// out week by week, so gene `i` belongs to week `i / (len / weeks)`. Groups are sorted
+    /// so that the layout, and therefore a seeded run, doesn't depend on hashing order.
+    pub group_subjects: Vec<(GroupId, SubjectId)>,
+    /// The rooms large enough for each group, or all rooms if none is. Empty without rooms.
+    pub group_rooms: HashMap<GroupId, Vec<RoomId>>,
+    /// The first gene of every double lesson. A `Problem::consecutive` requirement of `h`
+    /// hours has `h` adjacent genes, as any other; they are paired off in order, genes `i` and
+    /// `i + 1` being the two halves of a double lesson, each a full `Dna` of its own. Fitness
+    /// wants the second half in the period right after the first. With an odd `h`, the last
+    /// gene is a single lesson.
+    pub pairs: Vec<usize>,
+    room_fits: Vec<bool>, // `Problem::fits`, indexed by `group * room_count + room`.
+    unavailable: Vec<bool>, // negated `Problem::is_available`, indexed by `lecturer * total_hours + hour`.
+    daily_caps: Vec<usize>, // `Problem::daily_cap` per lecturer, empty if no lecturer has one.
+    run_caps: Vec<usize>, // `Problem::max_consecutive` per lecturer, empty if no lecturer has one.
+    pinned: Vec<Option<(LecturerId, TimeSlot)>>, // per gene, empty if nothing is pinned.
+    preferences: Vec<i64>, // `Problem::preference`, indexed by `lecturer * total_hours + hour`, empty if none.
+    suitability: Vec<i64>, // `Problem::suitability`, indexed by `subject * lecturers + lecturer`, empty if none.
+    group_weights: Vec<i64>, // `Problem::group_weight` per group, empty if every group weighs 1.
+    /// The (group, subject, week) each gene is a lesson of, numbered densely, for spreading a
+    /// subject over the days of the week. `None` for the second half of a double lesson, which
+    /// belongs on the day of the first.
+    subject_weeks: Vec<Option<usize>>,
+    subject_week_count: usize,
+}
+
+impl Context {
+    pub fn new(problem: Problem) -> Self {
+        let mut groups: Vec<_> = problem.group_requirements.iter().collect();
+        groups.sort_by_key(|(group, _)| **group);
+        let group_subjects: Vec<_> = (0..problem.weeks)
+            .flat_map(|_| {
+                groups.iter().flat_map(|(group, subjects)| {
+                    subjects
+                        .iter()
+                        .flat_map(|(subject, hours)| (0..*hours).map(|_| (**group, *subject)))
+                })
+            })
+            .collect();
+        // Pairs never span two weeks, whose blocks of genes may end and start on one subject.
+        let genes_per_week = group_subjects.len() / problem.weeks;
+        let mut pairs = vec![];
+        let mut index = 0;
+        while index + 1 < group_subjects.len() {
+            let lesson = group_subjects[index];
+            let ends_week = (index + 1) % genes_per_week == 0;
+            if problem.consecutive.contains(&lesson)
+                && !ends_week
+                && group_subjects[index + 1] == lesson
+            {
+                pairs.push(index);
+                index += 2;
+            } else {
+                index += 1;
+            }
+        }
+        let group_rooms = problem
+            .group_requirements
+            .keys()
+            .map(|&group| {
+                let all = 0..problem.room_count;
+                let fitting: Vec<_> = all
+                    .clone()
+                    .filter(|&room| problem.fits(group, room))
+                    .collect();
+                (
+                    group,
+                    if fitting.is_empty() {
+                        all.collect()
+                    } else {
+                        fitting
+                    },
+                )
+            })
+            .collect();
+        let week = problem.total_hours;
+        let room_fits = (0..problem.group_count())
+            .flat_map(|group| (0..problem.room_count).map(move |room| (group, room)))
+            .map(|(group, room)| problem.fits(group, room))
+            .collect();
+        let unavailable = (0..problem.lecturer_count())
+            .flat_map(|lecturer| (0..week).map(move |hour| (lecturer, hour)))
+            .map(|(lecturer, hour)| !problem.is_available(lecturer, hour))
+            .collect();
+        let daily_caps = if problem.max_hours_per_day.is_empty()
+            && problem.default_max_hours_per_day.is_none()
+        {
+            vec![]
+        } else {
+            (0..problem.lecturer_count())
+                .map(|lecturer| problem.daily_cap(lecturer).unwrap_or(usize::MAX))
+                .collect()
+        };
+        let run_caps = if problem.max_consecutive.is_empty() {
+            vec![]
+        } else {
+            (0..problem.lecturer_count())
+                .map(|lecturer| {
+                    problem
+                        .max_consecutive
+                        .get(&lecturer)
+                        .copied()
+                        .unwrap_or(usize::MAX)
+                })
+                .collect()
+        };
+        let preferences = if problem.lecturer_preferences.is_empty() {
+            vec![]
+        } else {
+            (0..problem.lecturer_count())
+                .flat_map(|lecturer| (0..week).map(move |hour| (lecturer, hour)))
+                .map(|(lecturer, hour)| problem.preference(lecturer, hour))
+                .collect()
+        };
+        let suitability = if problem.lecturer_suitability.is_empty() {
+            vec![]
+        } else {
+            let lecturers = problem.lecturer_count();
+            (0..problem.subject_count())
+                .flat_map(|subject| (0..lecturers).map(move |lecturer| (subject, lecturer)))
+                .map(|(subject, lecturer)| problem.suitability(subject, lecturer))
+                .collect()
+        };
+        let group_weights = if problem.group_weights.values().all(|&weight| weight == 1) {
+            vec![]
+        } else {
+            (0..problem.group_count())
+                .map(|group| problem.group_weight(group))
+                .collect()
+        };
+        let genes_per_week = group_subjects.len() / problem.weeks;
+        let mut numbers = HashMap::new();
+        let subject_weeks = (0..group_subjects.len())
+            .map(|index| {
+                if index > 0 && pairs.binary_search(&(index - 1)).is_ok() {
+                    return None;
+                }
+                let (group, subject) = group_subjects[index];
+                let next = numbers.len();
+                Some(
+                    *numbers
+                        .entry((group, subject, index / genes_per_week))
+                        .or_insert(next),
+                )
+            })
+            .collect();
+        let subject_week_count = numbers.len();
+        // Each pin takes the first unpinned gene of its lesson in the week of its slot.
+        let mut pinned = vec![];
+        if !problem.pinned.is_empty() {
+            pinned = vec![None; group_subjects.len()];
+            let genes_per_week = group_subjects.len() / problem.weeks;
+            for &(group, subject, lecturer, hour) in &problem.pinned {
+                let pin_week = hour / week;
+                let genes =
+                    pin_week * genes_per_week..((pin_week + 1) * genes_per_week).min(pinned.len());
+                if let Some(index) = genes.into_iter().find(|&index| {
+                    group_subjects[index] == (group, subject) && pinned[index].is_none()
+                }) {
+                    pinned[index] = Some((lecturer, TimeSlot::from_index(hour)));
+                }
+            }
+        }
+        Self {
+            problem,
+            group_subjects,
+            group_rooms,
+            pairs,
+            room_fits,
+            unavailable,
+            daily_caps,
+            run_caps,
+            pinned,
+            preferences,
+            suitability,
+            group_weights,
+            subject_weeks,
+            subject_week_count,
+        }
+    }
+
+    /// How many times over the lessons of `group` count, see `Problem::group_weight`.
+    fn group_weight(&self, group: GroupId) -> i64 {
+        self.group_weights.get(group).copied().unwrap_or(1)
+    }
+
+    /// The sum of `group_weight` over all genes, what one point per lesson adds up to.
+    fn weighted_lessons(&self) -> i64 {
+        self.group_subjects
+            .iter()
+            .map(|(group, _)| self.group_weight(*group))
+            .sum()
+    }
+
+    /// The groups with a band of daily hours, a `group_daily_min` or a `group_daily_max`.
+    fn banded_groups(&self) -> impl Iterator<Item = GroupId> + '_ {
+        let problem = &self.problem;
+        (0..problem.group_count()).filter(|group| {
+            problem.group_daily_min.contains_key(group)
+                || problem.group_daily_max.contains_key(group)
+        })
+    }
+
+    /// A random hour of a week below `range`, avoiding the hours `lecturer` is unavailable
+    /// at unless that leaves none. Draws like `gen_range(0..range)` for lecturers that are
+    /// always available, so that seeded runs don't change. An empty range gives hour 0.
+    fn random_hour<R: Rng>(&self, lecturer: LecturerId, range: usize, rng: &mut R) -> usize {
+        if range == 0 {
+            return 0;
+        }
+        if self.problem.forbidden_periods.is_empty()
+            && !self.problem.lecturer_unavailable.contains_key(&lecturer)
+        {
+            return rng.gen_range(0..range);
+        }
+        let available: Vec<_> = (0..range)
+            .filter(|&hour| !self.unavailable[lecturer * self.problem.total_hours + hour])
+            .collect();
+        if available.is_empty() {
+            rng.gen_range(0..range)
+        } else {
+            available[rng.gen_range(0..available.len())]
+        }
+    }
+
+    /// A random room for a lesson of `group`, preferring rooms large enough. Always room 0
+    /// without rooms, drawing nothing from `rng` so that seeded runs don't change.
+    fn random_room<R: Rng>(&self, group: GroupId, rng: &mut R) -> RoomId {
+        match self.group_rooms.get(&group) {
+            Some(rooms) if !rooms.is_empty() => rooms[rng.gen_range(0..rooms.len())],
+            _ => 0,
+        }
+    }
+
+    /// A random suitable lecturer for `subject`, in proportion to their suitability. Draws
+    /// like `gen_range(0..lecturers)` while no lecturer has a suitability of their own, so
+    /// that seeded runs don't change.
+    fn random_lecturer<R: Rng>(&self, subject: SubjectId, rng: &mut R) -> LecturerId {
+        let lecturers = &self.problem.subject_requirements[&subject];
+        let total: i64 = if self.suitability.is_empty() {
+            0
+        } else {
+            lecturers
+                .iter()
+                .map(|&lecturer| self.suitability_of(subject, lecturer))
+                .sum()
+        };
+        if total == 0 {
+            return lecturers[rng.gen_range(0..lecturers.len())];
+        }
+        let mut pick = rng.gen_range(0..total);
+        for &lecturer in lecturers {
+            let suitability = self.suitability_of(subject, lecturer);
+            if pick < suitability {
+                return lecturer;
+            }
+            pick -= suitability;
+        }
+        unreachable!("the pick is below the total suitability")
+    }
+
+    /// `Problem::suitability`, 1 if no lecturer has one.
+    fn suitability_of(&self, subject: SubjectId, lecturer: LecturerId) -> i64 {
+        let lecturers = self.problem.lecturer_count();
+        self.suitability
+            .get(subject * lecturers + lecturer)
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// The lecturer and slot the gene at `index` is pinned to, if any.
+    pub fn pin(&self, index: usize) -> Option<(LecturerId, TimeSlot)> {
+        self.pinned.get(index).copied().flatten()
+    }
+
+    /// The least and most weighted preference all genes together can add to the fitness,
+    /// each gene with the worst and best hour of its suitable lecturers.
+    fn preference_bounds(&self) -> (i64, i64) {
+        if self.preferences.is_empty() {
+            return (0, 0);
+        }
+        let weight = self.problem.weights.preference;
+        let week = self.problem.total_hours;
+        let mut bounds = (0, 0);
+        for (_, subject) in &self.group_subjects {
+            let scores = self.problem.subject_requirements[subject]
+                .iter()
+                .flat_map(|lecturer| &self.preferences[lecturer * week..(lecturer + 1) * week])
+                .map(|preference| weight * preference);
+            let (worst, best) = scores.fold((0, 0), |(worst, best), score| {
+                (score.min(worst), score.max(best))
+            });
+            bounds = (bounds.0 + worst, bounds.1 + best);
+        }
+        bounds
+    }
+
+    /// The least and most weighted suitability all genes together can add to the fitness,
+    /// each gene with the least and most suitable of its lecturers.
+    fn suitability_bounds(&self) -> (i64, i64) {
+        if self.suitability.is_empty() {
+            return (0, 0);
+        }
+        let weight = self.problem.weights.suitability;
+        let mut bounds = (0, 0);
+        for (_, subject) in &self.group_subjects {
+            let scores = self.problem.subject_requirements[subject]
+                .iter()
+                .map(|&lecturer| weight * (self.suitability_of(*subject, lecturer) - 1));
+            let (worst, best) = scores.fold((0, 0), |(worst, best), score| {
+                (score.min(worst), score.max(best))
+            });
+            bounds = (bounds.0 + worst, bounds.1 + best);
+        }
+        bounds
+    }
+
+    /// Fitness of `genome` as if the gene at index `ignored` was not scheduled at all.
+    pub fn fitness_ignoring(&self, genome: &Genome, ignored: Option<usize>) -> i64 {
+        self.score_genes(genome, |index| Some(index) != ignored)
+            .fitness
+    }
+
+    /// The fitness of `genome` split by constraint, see `FitnessBreakdown`.
+    pub fn fitness_breakdown(&self, genome: &Genome) -> FitnessBreakdown {
+        self.score_genes(genome, |_| true).breakdown
+    }
+
+    /// Whether `genome` satisfies every hard constraint, whatever its soft penalties.
+    pub fn is_feasible(&self, genome: &Genome) -> bool {
+        let score = self.score_genes(genome, |_| true);
+        score.satisfied == self.group_subjects.len()
+            && score.moved_pins == 0
+            && score.daily_overloads == 0
+            && score.long_runs == 0
+            && score.split_pairs == 0
+    }
+
+    /// Scores only the genes whose index passes `included`, as if no others were scheduled.
+    fn score_genes(&self, genome: &Genome, included: impl Fn(usize) -> bool) -> PartialScore {
+        self.check_genes(genome, included, |_, _| {})
+    }
+
+    /// `score_genes`, also passing the index and hard constraint checks of every gene with
+    /// one broken to `on_violation`. Genes are checked in order, so of two clashing lessons
+    /// the later one is reported, and split double lessons are reported after all genes.
+    fn check_genes(
+        &self,
+        genome: &Genome,
+        included: impl Fn(usize) -> bool,
+        mut on_violation: impl FnMut(usize, GeneCheck),
+    ) -> PartialScore {
+        let problem = &self.problem;
+        let weights = &problem.weights;
+        let week = problem.total_hours;
+        let hours = week * problem.weeks;
+        let lecturers = problem.lecturer_count();
+
+        let mut score = PartialScore::default();
+        let mut used_group_hours = Occupancy::new(hours, problem.group_count());
+        let mut used_lecturer_hours = Occupancy::new(hours, lecturers);
+        let mut used_room_hours = Occupancy::new(hours, problem.room_count);
+        // The periods each group has lessons in, a bit each, indexed by `group * days + day`.
+        let days = problem.days() * problem.weeks;
+        let mut group_days = vec![0u32; problem.group_count() * days];
+        // Hours each lecturer teaches per day, indexed by `lecturer * days + day`. Only lessons
+        // that passed the lecturer check count, so a clash or an over-budget lesson isn't
+        // penalized a second time for the daily cap.
+        let mut lecturer_days = vec![0usize; self.daily_caps.len() * days];
+        // The periods each lecturer teaches in, a bit each, indexed by `lecturer * days + day`.
+        // Counted like `lecturer_days`, and per day so that a run never wraps into the next.
+        let mut lecturer_periods = vec![0u32; self.run_caps.len() * days];
+        // The days of the week each (group, subject, week) has lessons on, indexed by
+        // `subject_week * days_per_week + day`, and how many lessons it has.
+        let days_per_week = problem.days();
+        let mut subject_days = vec![false; self.subject_week_count * days_per_week];
+        let mut subject_lessons = vec![0usize; self.subject_week_count];
+        // Indexed by `week * lecturers + lecturer`.
+        let mut free_lecturer_hours = vec![0usize; lecturers * problem.weeks];
+        for (lecturer, budget) in &problem.lecturer_requirements {
+            for week in 0..problem.weeks {
+                free_lecturer_hours[week * lecturers + lecturer] = *budget;
+            }
+        }
+
+        for (index, ((group, subject), (lecturer, room, hour))) in self
+            .group_subjects
+            .iter()
+            .zip(genome.iter().map(|x| (x.0 .1, x.0 .2, x.0 .3.index())))
+            .enumerate()
+            .filter(|(index, _)| included(*index))
+        {
+            let satisfies_group = used_group_hours.insert(hour, *group);
+            group_days[group * days + hour / PERIODS_PER_DAY] |= 1 << (hour % PERIODS_PER_DAY);
+            if let Some(subject_week) = self.subject_weeks[index] {
+                subject_lessons[subject_week] += 1;
+                let day = hour / PERIODS_PER_DAY % days_per_week;
+                subject_days[subject_week * days_per_week + day] = true;
+            }
+
+            let free_hours = &mut free_lecturer_hours[hour / week * lecturers + lecturer];
+            let over_allocated = *free_hours == 0;
+            let lecturer_busy = used_lecturer_hours.contains(hour, lecturer);
+            let lecturer_unavailable = self.unavailable[lecturer * week + hour % week];
+            let lecturer_clash = lecturer_busy || lecturer_unavailable;
+            let satisfies_lecturer = !over_allocated && !lecturer_clash;
+
+            if satisfies_lecturer {
+                *free_hours -= 1;
+                used_lecturer_hours.insert(hour, lecturer);
+                if let Some(count) = lecturer_days.get_mut(lecturer * days + hour / PERIODS_PER_DAY)
+                {
+                    *count += 1;
+                }
+                if let Some(periods) =
+                    lecturer_periods.get_mut(lecturer * days + hour / PERIODS_PER_DAY)
+                {
+                    *periods |= 1 << (hour % PERIODS_PER_DAY);
+                }
+            }
+
+            // A double-booked or too small room counts against the lesson like a busy lecturer.
+            let room_free = problem.room_count == 0 || used_room_hours.insert(hour, room);
+            let room_fits =
+                problem.room_count == 0 || self.room_fits[group * problem.room_count + room];
+            let satisfies_room = room_free && room_fits;
+
+            // A lesson pays for its worst lecturer or room problem only, not for each of them.
+            let group_weight = self.group_weight(*group);
+            let points = &mut score.breakdown;
+            points.lessons += group_weight * weights.lesson;
+            if !satisfies_group {
+                points.group_clashes -= group_weight * weights.group_clash;
+            }
+            let resource_penalty = [
+                (
+                    lecturer_clash,
+                    weights.lecturer_clash,
+                    &mut points.lecturer_clashes,
+                ),
+                (
+                    over_allocated,
+                    weights.over_allocated_lecturer,
+                    &mut points.over_allocations,
+                ),
+                (
+                    !satisfies_room,
+                    weights.room_clash,
+                    &mut points.room_clashes,
+                ),
+            ]
+            .into_iter()
+            .filter(|(broken, _, _)| *broken)
+            .reduce(|worst, next| if next.1 > worst.1 { next } else { worst });
+            if let Some((_, weight, points)) = resource_penalty {
+                *points -= group_weight * weight;
+            }
+
+            let moved_pin = self.pin(index).is_some_and(|(pinned_lecturer, slot)| {
+                pinned_lecturer != lecturer || slot.index() != hour
+            });
+            if moved_pin {
+                score.moved_pins += 1;
+                score.breakdown.moved_pins -= weights.moved_pin;
+            }
+
+            let satisfied = satisfies_group && satisfies_lecturer && satisfies_room;
+            score.satisfied += usize::from(satisfied);
+            // Preferences never make up for a broken hard constraint.
+            if satisfied && !self.preferences.is_empty() {
+                let preference = self.preferences[lecturer * week + hour % week];
+                score.preference += preference;
+                score.breakdown.preference += weights.preference * preference;
+            }
+            if satisfied && !self.suitability.is_empty() {
+                let suitability = self.suitability_of(*subject, lecturer) - 1;
+                score.suitability += suitability;
+                score.breakdown.suitability += weights.suitability * suitability;
+            }
+            score.group_clashes += usize::from(!satisfies_group);
+            score.lecturer_violations += usize::from(!satisfies_lecturer);
+            score.over_allocated_lecturers += usize::from(over_allocated);
+            score.room_clashes += usize::from(!room_free);
+            score.undersized_rooms += usize::from(!room_fits);
+
+            let check = GeneCheck {
+                group_clash: !satisfies_group,
+                lecturer_busy,
+                lecturer_unavailable,
+                over_allocated,
+                room_taken: !room_free,
+                room_too_small: !room_fits,
+                moved_pin,
+                split_pair: false,
+            };
+            if check != GeneCheck::default() {
+                on_violation(index, check);
+            }
+        }
+
+        score.gaps = group_days.iter().map(|&periods| gaps(periods)).sum();
+        score.breakdown.gaps = -weights.gaps * score.gaps as i64;
+        if let Some(first) =
+            (0..PERIODS_PER_DAY).find(|period| !problem.forbidden_periods.contains(period))
+        {
+            score.early_starts = group_days
+                .iter()
+                .filter(|&&periods| periods & 1 << first != 0)
+                .count();
+        }
+        score.breakdown.early_starts = -weights.early_start * score.early_starts as i64;
+        if !problem.rotate_subjects.is_empty() {
+            let genes_per_week = self.group_subjects.len() / problem.weeks;
+            let rotating: Vec<_> = (0..genome.len())
+                .filter(|&index| included(index))
+                .filter(|&index| {
+                    let (_, subject) = self.group_subjects[index];
+                    problem.rotate_subjects.contains(&subject)
+                })
+                .map(|index| {
+                    let (group, subject) = self.group_subjects[index];
+                    (group, subject, index / genes_per_week, genome[index].0 .1)
+                })
+                .collect();
+            let taught: HashSet<_> = rotating.iter().collect();
+            score.repeated_lecturers = rotating
+                .iter()
+                .filter(|&&(group, subject, week, lecturer)| {
+                    week > 0 && taught.contains(&(group, subject, week - 1, lecturer))
+                })
+                .count();
+        }
+        score.breakdown.repeated_lecturers = -weights.rotation * score.repeated_lecturers as i64;
+        if problem.lecture_before_lab && !problem.lab_lectures.is_empty() {
+            let lesson = |index: usize| {
+                let (group, subject) = self.group_subjects[index];
+                let slot = genome[index].0 .3;
+                (group, subject, slot.day, slot.period)
+            };
+            let mut last_lectures = HashMap::new();
+            for (group, subject, day, period) in
+                (0..genome.len()).filter(|&i| included(i)).map(lesson)
+            {
+                let last = last_lectures.entry((group, subject, day)).or_insert(period);
+                *last = period.max(*last);
+            }
+            score.early_labs = (0..genome.len())
+                .filter(|&index| included(index))
+                .map(lesson)
+                .filter(|&(group, subject, day, period)| {
+                    problem.lab_lectures.get(&subject).is_some_and(|lecture| {
+                        last_lectures
+                            .get(&(group, *lecture, day))
+                            .is_some_and(|&last| last > period)
+                    })
+                })
+                .count();
+        }
+        score.breakdown.early_labs = -weights.lab_before_lecture * score.early_labs as i64;
+        if !problem.must_be_first.is_empty() {
+            score.late_first_lessons = (0..genome.len())
+                .filter(|&index| included(index))
+                .filter(|&index| {
+                    let (group, subject) = self.group_subjects[index];
+                    let slot = genome[index].0 .3;
+                    let earlier = (1 << slot.period) - 1;
+                    problem.must_be_first.contains(&(group, subject))
+                        && group_days[group * days + usize::from(slot.day)] & earlier != 0
+                })
+                .count();
+        }
+        score.breakdown.late_first_lessons = -weights.not_first * score.late_first_lessons as i64;
+        if !problem.group_disliked_hours.is_empty() {
+            score.disliked_lessons = (0..genome.len())
+                .filter(|&index| included(index))
+                .filter(|&index| {
+                    let (group, _) = self.group_subjects[index];
+                    let hour = genome[index].0 .3.index() % week;
+                    problem
+                        .group_disliked_hours
+                        .get(&group)
+                        .is_some_and(|hours| hours.contains(&hour))
+                })
+                .count();
+        }
+        score.breakdown.disliked_lessons = -weights.disliked_hour * score.disliked_lessons as i64;
+        if problem.room_change_needs_gap && problem.room_count > 0 {
+            // The first lesson in a lecturer's period decides the room they're in.
+            let mut rooms = HashMap::new();
+            for index in (0..genome.len()).filter(|&index| included(index)) {
+                let (_, lecturer, room, slot) = genome[index].0;
+                rooms
+                    .entry((lecturer, slot.day, slot.period))
+                    .or_insert(room);
+            }
+            score.tight_room_changes = rooms
+                .iter()
+                .filter(|(&(lecturer, day, period), room)| {
+                    rooms
+                        .get(&(lecturer, day, period + 1))
+                        .is_some_and(|next| next != *room)
+                })
+                .count();
+        }
+        score.breakdown.tight_room_changes = -weights.room_change * score.tight_room_changes as i64;
+        for group in self.banded_groups() {
+            let min = problem.group_daily_min.get(&group).copied().unwrap_or(0);
+            let max = problem
+                .group_daily_max
+                .get(&group)
+                .copied()
+                .unwrap_or(usize::MAX);
+            score.off_band_days += group_days[group * days..(group + 1) * days]
+                .iter()
+                .map(|periods| periods.count_ones() as usize)
+                .filter(|&hours| hours > 0 && !(min..=max).contains(&hours))
+                .count();
+        }
+        score.breakdown.off_band_days = -weights.daily_band * score.off_band_days as i64;
+        if !problem.subject_difficulty.is_empty() {
+            // The first lesson in a group's period decides the subject it has then.
+            let mut subjects = HashMap::new();
+            for index in (0..genome.len()).filter(|&index| included(index)) {
+                let (group, subject) = self.group_subjects[index];
+                let slot = genome[index].0 .3;
+                subjects
+                    .entry((group, slot.day, slot.period))
+                    .or_insert(subject);
+            }
+            let difficulty = |subject| {
+                problem
+                    .subject_difficulty
+                    .get(subject)
+                    .copied()
+                    .unwrap_or(0)
+            };
+            score.stacked_difficulty = subjects
+                .iter()
+                .filter_map(|(&(group, day, period), subject)| {
+                    // A subject following itself is one longer lesson, as double lessons are.
+                    let next = subjects
+                        .get(&(group, day, period + 1))
+                        .filter(|next| *next != subject)?;
+                    Some(usize::from(difficulty(subject).min(difficulty(next))))
+                })
+                .sum();
+        }
+        score.breakdown.stacked_difficulty =
+            -weights.stacked_difficulty * score.stacked_difficulty as i64;
+        // With more lessons than days some days repeat the subject anyway, only repeats that a
+        // free day could have taken count.
+        score.repeated_subjects = subject_lessons
+            .iter()
+            .zip(subject_days.chunks(days_per_week))
+            .map(|(&lessons, days)| {
+                let distinct = days.iter().filter(|&&day| day).count();
+                lessons.min(days_per_week) - distinct
+            })
+            .sum();
+        score.breakdown.repeated_subjects =
+            -weights.repeated_subject * score.repeated_subjects as i64;
+        for &first in &self.pairs {
+            let (a, b) = (genome[first].0 .3, genome[first + 1].0 .3);
+            if included(first)
+                && included(first + 1)
+                && (a.day != b.day || a.period + 1 != b.period)
+            {
+                score.split_pairs += 1;
+                let check = GeneCheck {
+                    split_pair: true,
+                    ..GeneCheck::default()
+                };
+                on_violation(first + 1, check);
+            }
+        }
+        score.breakdown.split_pairs = -weights.split_pair * score.split_pairs as i64;
+        score.daily_overloads = lecturer_days
+            .iter()
+            .enumerate()
+            .map(|(index, hours)| hours.saturating_sub(self.daily_caps[index / days]))
+            .sum();
+        score.breakdown.daily_overloads = -weights.daily_overload * score.daily_overloads as i64;
+        score.long_runs = lecturer_periods
+            .iter()
+            .enumerate()
+            .map(|(index, &periods)| overrun(periods, self.run_caps[index / days]))
+            .sum();
+        score.breakdown.long_runs = -weights.long_run * score.long_runs as i64;
+        score.fitness = score.breakdown.total();
+        score
+    }
+}
+
+/// Which hard constraints a single gene breaks, see `Context::check_genes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct GeneCheck {
+    pub group_clash: bool,          // its group already has a lesson at that hour.
+    pub lecturer_busy: bool,        // its lecturer already teaches at that hour.
+    pub lecturer_unavailable: bool, // its lecturer can't teach at that hour.
+    pub over_allocated: bool,       // its lecturer is out of weekly budget.
+    pub room_taken: bool,           // its room already has a lesson at that hour.
+    pub room_too_small: bool,       // its room has fewer seats than the group has students.
+    pub moved_pin: bool,            // it is pinned, but not to its lecturer and hour.
+    pub split_pair: bool, // it is the second half of a double lesson, not right after the first.
+}
+
+/// Fitness of a set of genes together with the constraint counts behind it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PartialScore {
+    pub fitness: i64,
+    pub satisfied: usize, // genes with a free group hour, a free lecturer and a free room.
+    pub group_clashes: usize, // genes whose group already has a lesson at that hour.
+    pub lecturer_violations: usize, // genes whose lecturer is busy, unavailable or out of weekly budget.
+    pub over_allocated_lecturers: usize, // genes whose lecturer is out of weekly budget.
+    pub room_clashes: usize,        // genes whose room already has a lesson at that hour.
+    pub undersized_rooms: usize,    // genes whose room has fewer seats than the group has students.
+    pub gaps: usize, // idle periods between the first and last lesson of a group's day.
+    pub split_pairs: usize, // double lessons whose second half doesn't directly follow the first.
+    pub daily_overloads: usize, // hours lecturers teach beyond their daily caps.
+    pub moved_pins: usize, // pinned genes not at their lecturer and hour.
+    pub preference: i64, // lecturer preference for the hours of genes breaking no hard constraint.
+    pub repeated_subjects: usize, // lessons of a group's subject on a day it already has it, where another day was free.
+    pub long_runs: usize,         // periods lecturers teach past their cap on periods in a row.
+    pub suitability: i64, // suitability above 1 of the lecturers of genes breaking no hard constraint.
+    pub early_starts: usize, // group days with a lesson in the first period no rule forbids.
+    pub repeated_lecturers: usize, // lessons of rotating subjects the group had from the same lecturer the week before.
+    pub early_labs: usize, // labs taught before a lecture of their subject the group has later the same day.
+    pub late_first_lessons: usize, // lessons meant to start their group's day with an earlier lesson that day.
+    pub disliked_lessons: usize,   // lessons at hours their group would rather not have them at.
+    pub tight_room_changes: usize, // lecturers moving to another room for the very next period.
+    pub off_band_days: usize,      // days with lessons outside their group's band of daily hours.
+    pub stacked_difficulty: usize, // over distinct subjects a group has back to back, the sum of the difficulties of the easier of the two.
+    pub breakdown: FitnessBreakdown, // `fitness` by constraint, adding up to it.
+}
+
+/// How much each kind of constraint adds to the fitness, negative for penalties, in the
+/// weighted points `FitnessWeights` gives it. The fields add up to the fitness, a lesson's
+/// lecturer or room penalty counting under whichever of them cost it most.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FitnessBreakdown {
+    pub lessons: i64, // `lesson` per gene, clashing or not.
+    pub group_clashes: i64,
+    pub lecturer_clashes: i64, // also teaching at an unavailable hour.
+    pub over_allocations: i64,
+    pub room_clashes: i64, // also a room too small for the group.
+    pub gaps: i64,
+    pub split_pairs: i64,
+    pub daily_overloads: i64,
+    pub moved_pins: i64,
+    pub preference: i64,
+    pub repeated_subjects: i64,
+    pub long_runs: i64,
+    pub suitability: i64,
+    pub early_starts: i64,
+    pub repeated_lecturers: i64,
+    pub early_labs: i64,
+    pub late_first_lessons: i64,
+    pub disliked_lessons: i64,
+    pub tight_room_changes: i64,
+    pub off_band_days: i64,
+    pub stacked_difficulty: i64,
+}
+
+impl FitnessBreakdown {
+    /// The named terms, in the order of the fields.
+    pub fn terms(&self) -> [(&'static str, i64); 21] {
+        [
+            ("lessons", self.lessons),
+            ("group_clashes", self.group_clashes),
+            ("lecturer_clashes", self.lecturer_clashes),
+            ("over_allocations", self.over_allocations),
+            ("room_clashes", self.room_clashes),
+            ("gaps", self.gaps),
+            ("split_pairs", self.split_pairs),
+            ("daily_overloads", self.daily_overloads),
+            ("moved_pins", self.moved_pins),
+            ("preference", self.preference),
+            ("repeated_subjects", self.repeated_subjects),
+            ("long_runs", self.long_runs),
+            ("suitability", self.suitability),
+            ("early_starts", self.early_starts),
+            ("repeated_lecturers", self.repeated_lecturers),
+            ("early_labs", self.early_labs),
+            ("late_first_lessons", self.late_first_lessons),
+            ("disliked_lessons", self.disliked_lessons),
+            ("tight_room_changes", self.tight_room_changes),
+            ("off_band_days", self.off_band_days),
+            ("stacked_difficulty", self.stacked_difficulty),
+        ]
+    }
+
+    /// The fitness these terms add up to.
+    pub fn total(&self) -> i64 {
+        self.terms().iter().map(|(_, points)| points).sum()
+    }
+}
+
+/// The nonzero terms, e.g. `lessons +300, gaps -4`.
+impl std::fmt::Display for FitnessBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let terms: Vec<_> = self
+            .terms()
+            .into_iter()
+            .filter(|(_, points)| *points != 0)
+            .map(|(name, points)| format!("{name} {points:+}"))
+            .collect();
+        if terms.is_empty() {
+            write!(f, "nothing")
+        } else {
+            write!(f, "{}", terms.join(", "))
+        }
+    }
+}
+
+/// Idle periods between the first and last lesson in `periods`, a bitset of a day's periods.
+fn gaps(periods: u32) -> usize {
+    if periods == 0 {
+        return 0;
+    }
+    let span = 32 - periods.leading_zeros() - periods.trailing_zeros();
+    (span - periods.count_ones()) as usize
+}
+
+/// Periods past `cap` in each run of adjacent periods in `periods`, a bitset of a day's periods.
+fn overrun(mut periods: u32, cap: usize) -> usize {
+    let mut past = 0;
+    while periods != 0 {
+        periods >>= periods.trailing_zeros();
+        let run = periods.trailing_ones();
+        past += (run as usize).saturating_sub(cap);
+        periods = periods.checked_shr(run).unwrap_or(0);
+    }
+    past
+}
+
+/// Per-hour bitset over dense ids, so collision checks in the fitness loop avoid hashing.
+struct Occupancy {
+    words_per_hour: usize,
+    bits: Vec<u64>,
+}
+
+impl Occupancy {
+    fn new(hours: usize, ids: usize) -> Self {
+        let words_per_hour = ids.div_ceil(64);
+        Self {
+            words_per_hour,
+            bits: vec![0; hours * words_per_hour],
+        }
+    }
+
+    fn position(&self, hour: usize, id: usize) -> (usize, u64) {
+        (hour * self.words_per_hour + id / 64, 1 << (id % 64))
+    }
+
+    fn contains(&self, hour: usize, id: usize) -> bool {
+        let (word, mask) = self.position(hour, id);
+        self.bits[word] & mask != 0
+    }
+
+    /// Marks `id` as busy at `hour`, returning whether it was free before.
+    fn insert(&mut self, hour: usize, id: usize) -> bool {
+        let (word, mask) = self.position(hour, id);
+        let was_free = self.bits[word] & mask == 0;
+        self.bits[word] |= mask;
+        was_free
+    }
+}
+
+/// The fitness function for `Selection`
+impl FitnessFunction<Genome, i64> for &Context {
+    fn fitness_of(&self, genome: &Genome) -> i64 {
+        self.fitness_ignoring(genome, None)
+    }
+
+    fn average(&self, values: &[i64]) -> i64 {
+        (values.iter().sum::<i64>() as f32 / values.len() as f32).round() as i64
+    }
+
+    fn highest_possible_fitness(&self) -> i64 {
+        self.weighted_lessons() * self.problem.weights.lesson
+            + self.preference_bounds().1
+            + self.suitability_bounds().1
+    }
+
+    fn lowest_possible_fitness(&self) -> i64 {
+        let days = (self.problem.group_count() * self.problem.days() * self.problem.weeks) as i64;
+        let gaps = days * PERIODS_PER_DAY.saturating_sub(2) as i64;
+        let weights = &self.problem.weights;
+        let worst_lesson =
+            weights.lesson - weights.group_clash - weights.largest_resource_penalty();
+        // Every lesson counts towards at most one hour over a daily cap.
+        let overloads = if self.daily_caps.is_empty() {
+            0
+        } else {
+            self.group_subjects.len() as i64
+        };
+        // Likewise towards at most one period past a cap on periods in a row.
+        let long_runs = if self.run_caps.is_empty() {
+            0
+        } else {
+            self.group_subjects.len() as i64
+        };
+        // At worst every lesson of a rotating subject after the first week keeps its lecturer.
+        let genes_per_week = self.group_subjects.len() / self.problem.weeks;
+        let rotations = self.group_subjects[genes_per_week..]
+            .iter()
+            .filter(|(_, subject)| self.problem.rotate_subjects.contains(subject))
+            .count() as i64;
+        // At worst every lab comes before its lecture.
+        let labs = if self.problem.lecture_before_lab {
+            self.group_subjects
+                .iter()
+                .filter(|(_, subject)| self.problem.lab_lectures.contains_key(subject))
+                .count() as i64
+        } else {
+            0
+        };
+        // At worst every lesson meant to start the day comes after another.
+        let late_firsts = self
+            .group_subjects
+            .iter()
+            .filter(|requirement| self.problem.must_be_first.contains(requirement))
+            .count() as i64;
+        // At worst every lesson of a group with disliked hours lands on one.
+        let disliked = self
+            .group_subjects
+            .iter()
+            .filter(|(group, _)| self.problem.group_disliked_hours.contains_key(group))
+            .count() as i64;
+        // At worst every lesson is followed by its lecturer's lesson in another room.
+        let room_changes = if self.problem.room_change_needs_gap && self.problem.room_count > 0 {
+            self.group_subjects.len() as i64
+        } else {
+            0
+        };
+        // At worst every day of a group with a band of daily hours falls outside it.
+        let off_band =
+            (self.banded_groups().count() * self.problem.days() * self.problem.weeks) as i64;
+        // At worst every day of every group alternates between the hardest subjects.
+        let hardest = self
+            .problem
+            .subject_difficulty
+            .values()
+            .max()
+            .copied()
+            .unwrap_or(0);
+        let stacked = days * PERIODS_PER_DAY.saturating_sub(1) as i64 * i64::from(hardest);
+        // At worst all lessons of a subject's week but the first repeat it on one day.
+        let repeats =
+            (self.subject_weeks.iter().flatten().count() - self.subject_week_count) as i64;
+        self.weighted_lessons() * worst_lesson
+            - gaps * weights.gaps
+            - self.pairs.len() as i64 * weights.split_pair
+            - overloads * weights.daily_overload
+            - long_runs * weights.long_run
+            - self.pinned.iter().flatten().count() as i64 * weights.moved_pin
+            - repeats * weights.repeated_subject
+            - days * weights.early_start
+            - rotations * weights.rotation
+            - labs * weights.lab_before_lecture
+            - late_firsts * weights.not_first
+            - disliked * weights.disliked_hour
+            - room_changes * weights.room_change
+            - off_band * weights.daily_band
+            - stacked * weights.stacked_difficulty
+            + self.preference_bounds().0
+            + self.suitability_bounds().0
+    }
+}
+
+/// How the initial population picks the hours of each group's lessons.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum InitHourDist {
+    #[default]
+    Uniform, // every lesson independently at random.
+    Spread, // a group's lessons of a week on distinct hours, in random order.
+}
+
+impl FromStr for InitHourDist {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(InitHourDist::Uniform),
+            "spread" => Ok(InitHourDist::Spread),
+            other => Err(format!(
+                "unsupported initial hour distribution `{other}`, expected `uniform` or `spread`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RandomScheduleBuilder<'a>(pub &'a Context, pub InitHourDist);
+
+impl GenomeBuilder<Genome> for RandomScheduleBuilder<'_> {
+    fn build_genome<R>(&self, _: usize, rng: &mut R) -> Genome
+    where
+        R: Rng + Sized,
+    {
+        let group_subjects = &self.0.group_subjects;
+        let hours = self.0.problem.total_hours;
+        let genes_per_week = group_subjects.len() / self.0.problem.weeks;
+        // For `Spread`, a shuffled hour order per (group, week) and how much of it is used.
+        let mut spread: HashMap<(GroupId, usize), (Vec<usize>, usize)> = HashMap::new();
+
+        group_subjects
+            .iter()
+            .enumerate()
+            .map(|(index, (group, subject))| {
+                if let Some((lecturer, slot)) = self.0.pin(index) {
+                    return Dna((*subject, lecturer, self.0.random_room(*group, rng), slot));
+                }
+                let lecturer = self.0.random_lecturer(*subject, rng);
+                let week = index / genes_per_week;
+                let hour = match self.1 {
+                    InitHourDist::Uniform => self.0.random_hour(lecturer, hours, rng),
+                    InitHourDist::Spread => {
+                        let (order, used) = spread.entry((*group, week)).or_insert_with(|| {
+                            let mut order: Vec<_> = (0..hours).collect();
+                            order.shuffle(rng);
+                            (order, 0)
+                        });
+                        let next = *used % hours;
+                        *used += 1;
+                        // Take the next hour the lecturer is available at, if there is one.
+                        if let Some(offset) = order[next..]
+                            .iter()
+                            .position(|&hour| self.0.problem.is_available(lecturer, hour))
+                        {
+                            order.swap(next, next + offset);
+                        }
+                        order[next]
+                    }
+                };
+
+                let room = self.0.random_room(*group, rng);
+
+                Dna((
+                    *subject,
+                    lecturer,
+                    room,
+                    TimeSlot::from_index(week * hours + hour),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Which fields of a gene the mutation operator may change.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Mutate {
+    #[default]
+    Both,
+    Hour,
+    Lecturer,
+}
+
+impl Mutate {
+    pub fn hour(self) -> bool {
+        self != Mutate::Lecturer
+    }
+
+    pub fn lecturer(self) -> bool {
+        self != Mutate::Hour
+    }
+}
+
+impl FromStr for Mutate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "both" => Ok(Mutate::Both),
+            "hour" => Ok(Mutate::Hour),
+            "lecturer" => Ok(Mutate::Lecturer),
+            other => Err(format!(
+                "unsupported mutation `{other}`, expected `both`, `hour` or `lecturer`"
+            )),
+        }
+    }
+}
+
+/// Genevo's random value mutation, but with the suitable lecturers taken from the context
+/// instead of global state: mutates `len * mutation_rate` random genes (rounded randomly),
+/// redrawing their hour within the same week and/or their lecturer. A new hour comes with a
+/// new room, as rooms are only ever busy at a given hour. Pinned genes are never changed.
+#[derive(Debug, Clone)]
+pub struct ScheduleMutator<'a> {
+    context: &'a Context,
+    mutation_rate: &'a MutationRate,
+    mutate: Mutate,
+}
+
+/// A mutation rate the solver can change between generations, while genevo owns the
+/// `ScheduleMutator` reading it.
+#[derive(Debug)]
+pub struct MutationRate(AtomicU64); // the bits of an `f64`.
+
+impl MutationRate {
+    pub fn new(rate: f64) -> Self {
+        Self(AtomicU64::new(rate.to_bits()))
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, rate: f64) {
+        self.0.store(rate.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl<'a> ScheduleMutator<'a> {
+    pub fn new(context: &'a Context, mutation_rate: &'a MutationRate, mutate: Mutate) -> Self {
+        Self {
+            context,
+            mutation_rate,
+            mutate,
+        }
+    }
+}
+
+impl GeneticOperator for ScheduleMutator<'_> {
+    fn name() -> String {
+        "Schedule-Mutator".to_string()
+    }
+}
+
+impl MutationOp<Genome> for ScheduleMutator<'_> {
+    fn mutate<R>(&self, mut genome: Genome, rng: &mut R) -> Genome
+    where
+        R: Rng + Sized,
+    {
+        let genome_length = genome.len();
+        let num_mutations =
+            ((genome_length as f64 * self.mutation_rate.get()) + rng.gen::<f64>()).floor() as usize;
+        for _ in 0..num_mutations {
+            let index = random_index(rng, genome_length);
+            if self.context.pin(index).is_some() {
+                continue;
+            }
+            let group = self.context.group_subjects[index].0;
+            let hours = self.context.problem.total_hours;
+            genome[index] = mutate_dna(self.context, group, genome[index], self.mutate, hours, rng);
+        }
+        genome
+    }
+}
+
+/// Mutates one gene of `group`, drawing new hours from the first `hours` of its week like
+/// `RandomScheduleBuilder` does with `Problem::total_hours`.
+fn mutate_dna<R>(
+    context: &Context,
+    group: GroupId,
+    mut value: Dna,
+    mutate: Mutate,
+    hours: usize,
+    rng: &mut R,
+) -> Dna
+where
+    R: Rng + Sized,
+{
+    let (week, days) = (context.problem.total_hours, context.problem.days());
+    if mutate.hour() {
+        let hour = context.random_hour(value.0 .1, hours, rng);
+        value.0 .3 = TimeSlot::from_index(value.0 .3.week(days) * week + hour);
+        if context.problem.room_count > 0 {
+            value.0 .2 = context.random_room(group, rng);
+        }
+    }
+
+    if mutate.lecturer() {
+        let lecturers = context
+            .problem
+            .subject_requirements
+            .get(&value.0 .0)
+            .unwrap();
+        let index = rng.gen_range(0..lecturers.len());
+        value.0 .1 = lecturers[index];
+
+        let hour = value.0 .3.index() % week;
+        if mutate.hour() && !context.problem.is_available(value.0 .1, hour) {
+            let hour = context.random_hour(value.0 .1, hours, rng);
+            value.0 .3 = TimeSlot::from_index(value.0 .3.week(days) * week + hour);
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Group 0 takes subjects 0 and 1, group 1 subject 1, an hour each. Lecturer 0 teaches
+    /// subject 0, lecturer 1 subject 1 with a budget for both of its groups.
+    fn tiny_problem() -> Problem {
+        Problem::new(
+            [(0, vec![(0, 1), (1, 1)]), (1, vec![(1, 1)])].into(),
+            [(0, 1), (1, 2)].into(),
+            [(0, vec![0]), (1, vec![1])].into(),
+        )
+    }
+
+    /// A genome for `tiny_problem` with its three lessons at the given periods of the first day.
+    fn tiny_genome(periods: [u8; 3]) -> Genome {
+        [(0, 0), (1, 1), (1, 1)]
+            .into_iter()
+            .zip(periods)
+            .map(|((subject, lecturer), period)| {
+                Dna((subject, lecturer, 0, TimeSlot { day: 0, period }))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn conflict_free_schedule_reaches_the_highest_fitness() {
+        let context = Context::new(tiny_problem());
+        let genome = tiny_genome([0, 1, 0]);
+        assert_eq!((&context).fitness_of(&genome), 30);
+        assert_eq!((&context).highest_possible_fitness(), 30);
+        assert!(context.is_feasible(&genome));
+    }
+
+    #[test]
+    fn double_booked_group_loses_one_lesson() {
+        let context = Context::new(tiny_problem());
+        // Group 0 has both of its lessons in period 0, group 1 is alone in period 1.
+        let genome = tiny_genome([0, 0, 1]);
+        assert_eq!((&context).fitness_of(&genome), 30 - 10);
+    }
+
+    #[test]
+    fn double_booked_lecturer_loses_one_lesson() {
+        let context = Context::new(tiny_problem());
+        // Both groups are free in period 1, but lecturer 1 teaches them both then.
+        let genome = tiny_genome([0, 1, 1]);
+        assert_eq!((&context).fitness_of(&genome), 30 - 10);
+        let score = analysis::evaluate_genes(&genome, &[0, 1, 2], &context);
+        assert_eq!((score.group_clashes, score.lecturer_violations), (0, 1));
+    }
+
+    #[test]
+    fn lessons_past_the_lecturer_budget_lose_fitness() {
+        let mut problem = tiny_problem();
+        problem.lecturer_requirements.insert(1, 1);
+        let context = Context::new(problem);
+        let genome = tiny_genome([0, 1, 0]);
+        assert_eq!((&context).fitness_of(&genome), 30 - 10);
+        let score = analysis::evaluate_genes(&genome, &[0, 1, 2], &context);
+        assert_eq!(score.over_allocated_lecturers, 1);
+    }
+
+    #[test]
+    fn heavier_groups_count_more() {
+        let mut problem = tiny_problem();
+        problem.group_weights.insert(0, 3);
+        let context = Context::new(problem);
+        assert_eq!((&context).highest_possible_fitness(), 3 * 20 + 10);
+        assert_eq!((&context).fitness_of(&tiny_genome([0, 1, 0])), 70);
+
+        // A clash costs three times as much in a lesson of group 0 as in one of group 1.
+        let group_0_clash = (&context).fitness_of(&tiny_genome([0, 0, 1]));
+        let group_1_clash = (&context).fitness_of(&tiny_genome([0, 1, 1]));
+        assert_eq!((group_0_clash, group_1_clash), (70 - 30, 70 - 10));
+    }
+
+    #[test]
+    fn schedule_usage_counts_lessons_and_occupied_hours() {
+        use analysis::Utilization;
+
+        let context = Context::new(tiny_problem());
+        // Group 0's lessons clash in period 0, so it occupies one hour but lecturers teach three.
+        let usage = analysis::schedule_usage(&context, &tiny_genome([0, 0, 1]));
+        let used = |demand, capacity| Utilization { demand, capacity };
+        assert_eq!(usage.lecturers[&0], used(1, 1));
+        assert_eq!(usage.lecturers[&1], used(2, 2));
+        assert_eq!(usage.groups[&0], used(1, DEFAULT_HOURS));
+        assert_eq!(usage.groups[&1], used(1, DEFAULT_HOURS));
+        assert_eq!(usage.slots, used(2, 2 * DEFAULT_HOURS));
+        // Both groups spend only the morning of day 0 in lessons, of ten half-days a week.
+        assert_eq!(usage.free_half_days, [(0, 9), (1, 9)].into());
+
+        let usage = analysis::schedule_usage(&context, &tiny_genome([0, 3, 1]));
+        assert_eq!(usage.free_half_days, [(0, 8), (1, 9)].into());
+    }
+
+    #[test]
+    fn restricted_mutation_keeps_the_other_field() {
+        let context = Context::new(dataset::small_example());
+        let mut rng = genevo::random::get_rng([1; 32]);
+        let mut hours_changed = false;
+        let mut lecturers_changed = false;
+        for _ in 0..200 {
+            let gene = Dna((1, 0, 0, TimeSlot::from_index(3))); // subject 1 can be taught by lecturers 0 and 2.
+
+            let mutated = mutate_dna(&context, 0, gene, Mutate::Hour, DEFAULT_HOURS, &mut rng);
+            assert_eq!(mutated.0 .1, gene.0 .1);
+            hours_changed |= mutated.0 .3 != gene.0 .3;
+
+            let mutated = mutate_dna(&context, 0, gene, Mutate::Lecturer, DEFAULT_HOURS, &mut rng);
+            assert_eq!(mutated.0 .3, gene.0 .3);
+            lecturers_changed |= mutated.0 .1 != gene.0 .1;
+        }
+        assert!(hours_changed && lecturers_changed);
+    }
+
+    #[test]
+    fn mutated_hours_stay_in_range() {
+        let context = Context::new(dataset::small_example());
+        let mut rng = genevo::random::get_rng([1; 32]);
+        let gene = Dna((1, 0, 0, TimeSlot::from_index(DEFAULT_HOURS + 3))); // an hour of week 1.
+        let mut last_hour_drawn = false;
+        for _ in 0..500 {
+            let mutated = mutate_dna(&context, 0, gene, Mutate::Both, DEFAULT_HOURS, &mut rng);
+            let hour = mutated.0 .3.index();
+            assert!((DEFAULT_HOURS..2 * DEFAULT_HOURS).contains(&hour), "{hour}");
+            last_hour_drawn |= hour == 2 * DEFAULT_HOURS - 1;
+        }
+        assert!(last_hour_drawn, "the last hour of the week is never drawn");
+
+        // A week of a single hour leaves nothing to choose, nor is there anything with none.
+        for hours in [1, 0] {
+            let mutated = mutate_dna(&context, 0, gene, Mutate::Hour, hours, &mut rng);
+            assert_eq!(mutated.0 .3.index(), DEFAULT_HOURS);
+        }
+    }
+
+    #[test]
+    fn validation_reports_every_error() {
+        assert_eq!(dataset::small_example().validate(), Ok(()));
+
+        let mut problem = dataset::small_example();
+        problem.group_requirements.get_mut(&0).unwrap().push((9, 0));
+        problem
+            .group_requirements
+            .get_mut(&2)
+            .unwrap()
+            .push((1, 11));
+        problem.subject_requirements.insert(4, vec![7]);
+        assert_eq!(
+            problem.validate(),
+            Err(vec![
+                ValidationError::NoHours {
+                    group: 0,
+                    subject: 9
+                },
+                ValidationError::NoLecturers {
+                    group: 0,
+                    subject: 9
+                },
+                ValidationError::GroupOverloaded {
+                    group: 2,
+                    hours: 21,
+                    week: DEFAULT_HOURS
+                },
+                ValidationError::UnknownLecturer {
+                    subject: 4,
+                    lecturer: 7
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn zero_weeks_are_reported_before_anything_else() {
+        let mut problem = dataset::small_example();
+        problem.weeks = 0;
+        problem.total_hours = 22;
+        problem.group_requirements.get_mut(&0).unwrap().push((9, 0));
+        assert_eq!(problem.validate(), Err(vec![ValidationError::NoWeeks]));
+        assert!(problem.validate().unwrap_err()[0].is_fatal());
+    }
+
+    #[test]
+    fn more_suitable_lecturers_are_picked_and_rewarded_more() {
+        let mut problem = dataset::small_example();
+        // Subject 1 is taught by lecturers 0 and 2.
+        problem.lecturer_suitability = [((1, 2), 4)].into();
+        let context = Context::new(problem);
+
+        let mut rng = genevo::random::get_rng([6; 32]);
+        let (mut picks, mut by_lecturer_2) = (0, 0);
+        for _ in 0..50 {
+            let genome =
+                RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(0, &mut rng);
+            for dna in genome.iter().filter(|dna| dna.0 .0 == 1) {
+                picks += 1;
+                by_lecturer_2 += usize::from(dna.0 .1 == 2);
+            }
+        }
+        // Four in five on average.
+        assert!(by_lecturer_2 * 10 > picks * 7, "{by_lecturer_2} of {picks}");
+
+        // Genes 2 and 3 are hours of group 0's subject 1.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[2] = Dna((1, 2, 0, TimeSlot { day: 0, period: 0 }));
+        genome[3] = Dna((1, 0, 0, TimeSlot { day: 1, period: 0 }));
+        let score = analysis::evaluate_genes(&genome, &[2, 3], &context);
+        assert_eq!((score.satisfied, score.suitability), (2, 3));
+        assert_eq!(score.breakdown.suitability, 3);
+        assert_eq!(
+            (&context).highest_possible_fitness(),
+            300 + 5 * 3,
+            "every hour of subject 1 could go to lecturer 2"
+        );
+    }
+
+    #[test]
+    fn forbidden_periods_are_avoided_and_penalized() {
+        let mut problem = dataset::small_example();
+        problem.forbidden_periods = [0].into();
+        assert_eq!(problem.validate(), Ok(()));
+        assert_eq!(problem.open_hours(), 15);
+        let context = Context::new(problem);
+
+        let mut rng = genevo::random::get_rng([5; 32]);
+        for dist in [InitHourDist::Uniform, InitHourDist::Spread] {
+            for _ in 0..20 {
+                let genome = RandomScheduleBuilder(&context, dist).build_genome(0, &mut rng);
+                assert!(genome.iter().all(|dna| dna.0 .3.period != 0));
+            }
+        }
+
+        // Genes 0 and 1 are hours of group 0's subject 0, which only lecturer 3 teaches.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot { day: 0, period: 0 }));
+        genome[1] = Dna((0, 3, 0, TimeSlot { day: 1, period: 1 }));
+        let score = analysis::evaluate_genes(&genome, &[0, 1], &context);
+        assert_eq!((score.satisfied, score.lecturer_violations), (1, 1));
+
+        let mut problem = dataset::small_example();
+        problem.forbidden_periods = [1, 2, 3, PERIODS_PER_DAY].into();
+        assert_eq!(
+            problem.validate(),
+            Err(vec![ValidationError::ForbiddenPeriodOutOfRange {
+                period: PERIODS_PER_DAY
+            }])
+        );
+        problem.forbidden_periods.remove(&PERIODS_PER_DAY);
+        assert_eq!(
+            problem.feasibility_check().shortfalls,
+            (0..3)
+                .map(|group| Shortfall::Group {
+                    group,
+                    hours: 10,
+                    week: 5
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn no_lesson_runs_past_the_last_period_of_the_day() {
+        let mut problem = dataset::small_example();
+        problem.forbidden_periods = [0].into();
+        problem.limit_periods_per_day(2);
+        assert_eq!(problem.forbidden_periods, [0, 3].into());
+        problem.limit_periods_per_day(PERIODS_PER_DAY);
+        assert_eq!(problem.forbidden_periods, [0, 3].into());
+
+        let context = Context::new(problem);
+        let mut rng = genevo::random::get_rng([6; 32]);
+        for dist in [InitHourDist::Uniform, InitHourDist::Spread] {
+            for _ in 0..20 {
+                let genome = RandomScheduleBuilder(&context, dist).build_genome(0, &mut rng);
+                assert!(genome.iter().all(|dna| (1..=2).contains(&dna.0 .3.period)));
+            }
+        }
+    }
+
+    #[test]
+    fn lecturer_conflicts_count_only_the_hours_left_open() {
+        // Only lecturer 3 teaches subject 0, 4 hours a week.
+        let mut problem = dataset::small_example();
+        problem.forbidden_periods = [0, 1, 2].into();
+        assert!(analysis::unavoidable_lecturer_conflicts(&problem).is_empty());
+
+        problem.lecturer_unavailable.insert(3, [3, 7].into());
+        let conflicts = analysis::unavoidable_lecturer_conflicts(&problem);
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| (
+                    conflict.lecturers.as_slice(),
+                    conflict.forced_hours,
+                    conflict.open_hours
+                ))
+                .collect::<Vec<_>>(),
+            [([3].as_slice(), 4, 3)]
+        );
+    }
+
+    #[test]
+    fn interchangeable_lecturers_share_every_lecturer_constraint() {
+        // Lecturers 0 and 1 both teach subjects 1, 2 and 4 with a budget of 6.
+        let mut problem = dataset::small_example();
+        problem.subject_requirements.insert(1, vec![0, 1, 2]);
+        problem.subject_requirements.insert(4, vec![0, 1, 2]);
+        assert_eq!(
+            analysis::interchangeable_lecturers(&problem),
+            vec![vec![0, 1]]
+        );
+
+        problem.lecturer_unavailable.insert(1, [0].into());
+        assert!(analysis::interchangeable_lecturers(&problem).is_empty());
+    }
+
+    #[test]
+    fn fingerprints_cover_what_solving_depends_on() {
+        let problem = dataset::small_example();
+        let fingerprint = analysis::fingerprint(&problem);
+
+        let mut named = problem.clone();
+        named.group_names.insert(0, "A".to_string());
+        assert_eq!(analysis::fingerprint(&named), fingerprint);
+
+        let changes: [fn(&mut Problem); 14] = [
+            |problem| problem.total_hours = 6 * PERIODS_PER_DAY,
+            |problem| problem.forbidden_periods = [0].into(),
+            |problem| problem.max_consecutive = [(0, 2)].into(),
+            |problem| problem.lecturer_unavailable = [(0, [1].into())].into(),
+            |problem| problem.weights.gaps += 1,
+            |problem| problem.consecutive = [(0, 0)].into(),
+            |problem| problem.rotate_subjects = [1].into(),
+            |problem| problem.lecture_before_lab = true,
+            |problem| problem.must_be_first = [(0, 1)].into(),
+            |problem| problem.group_disliked_hours = [(0, [1].into())].into(),
+            |problem| problem.room_change_needs_gap = true,
+            |problem| problem.group_daily_min = [(0, 2)].into(),
+            |problem| problem.group_daily_max = [(0, 3)].into(),
+            |problem| problem.subject_difficulty = [(0, 2)].into(),
+        ];
+        for change in changes {
+            let mut changed = problem.clone();
+            change(&mut changed);
+            assert_ne!(analysis::fingerprint(&changed), fingerprint);
+        }
+    }
+
+    #[test]
+    fn fitness_weights_round_trip_through_json() {
+        let weights = FitnessWeights {
+            gaps: 3,
+            ..FitnessWeights::default()
+        };
+        let json = serde_json::to_string(&weights).unwrap();
+        assert_eq!(
+            serde_json::from_str::<FitnessWeights>(&json).unwrap(),
+            weights
+        );
+
+        let partial: FitnessWeights = serde_json::from_str(r#"{"gaps": 3}"#).unwrap();
+        assert_eq!(partial, weights);
+        assert!(serde_json::from_str::<FitnessWeights>(r#"{"gap": 3}"#).is_err());
+    }
+
+    #[test]
+    fn weights_are_known_and_non_negative() {
+        let mut weights = FitnessWeights::default();
+        assert_eq!(weights.set("gaps", 4), Ok(()));
+        assert_eq!(weights.gaps, 4);
+        assert_eq!(
+            weights.set("gap", 4),
+            Err(WeightError::Unknown {
+                name: "gap".to_string()
+            })
+        );
+        let negative = WeightError::Negative {
+            name: "gaps".to_string(),
+            weight: -1,
+        };
+        assert_eq!(weights.set("gaps", -1), Err(negative.clone()));
+        assert_eq!(weights.gaps, 4);
+
+        assert_eq!(weights.validate(), Ok(()));
+        let read: FitnessWeights = serde_json::from_str(r#"{"gaps": -1}"#).unwrap();
+        assert_eq!(read.validate(), Err(negative));
+    }
+
+    #[test]
+    fn weeks_can_have_more_hours() {
+        let mut problem = dataset::small_example();
+        problem.total_hours = 7 * PERIODS_PER_DAY;
+        problem.weeks = 2;
+        problem.lecturer_unavailable.insert(3, [27].into());
+        assert_eq!(problem.validate(), Ok(()));
+        assert_eq!(problem.days(), 7);
+
+        let context = Context::new(problem);
+        let genes_per_week = context.group_subjects.len() / 2;
+        let mut rng = genevo::random::get_rng([3; 32]);
+        let mut late_hours = false;
+        for _ in 0..20 {
+            let genome =
+                RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(0, &mut rng);
+            for (index, dna) in genome.iter().enumerate() {
+                let hour = dna.0 .3.index();
+                assert_eq!(hour / 28, index / genes_per_week, "gene {index}: {hour}");
+                late_hours |= hour % 28 >= DEFAULT_HOURS;
+            }
+        }
+        assert!(late_hours, "the hours past the default week are used too");
+
+        let mut problem = dataset::small_example();
+        problem.total_hours = 22;
+        assert_eq!(
+            problem.validate(),
+            Err(vec![ValidationError::PartialDays { total_hours: 22 }])
+        );
+    }
+
+    #[test]
+    fn feasibility_check_reports_each_shortfall() {
+        assert!(dataset::small_example().feasibility_check().is_clear());
+
+        let mut problem = dataset::small_example();
+        // Lecturer 3 is the only one teaching subject 0, four hours across the groups.
+        problem.lecturer_requirements.insert(3, 2);
+        problem
+            .group_requirements
+            .get_mut(&2)
+            .unwrap()
+            .push((1, 11));
+        let shortfalls = problem.feasibility_check().shortfalls;
+        assert_eq!(
+            shortfalls,
+            vec![
+                Shortfall::Overall {
+                    needed: 41,
+                    allocated: 28
+                },
+                Shortfall::Lecturer {
+                    lecturer: 3,
+                    needed: 4,
+                    allocated: 2
+                },
+                Shortfall::Group {
+                    group: 2,
+                    hours: 21,
+                    week: DEFAULT_HOURS
+                },
+            ]
+        );
+        assert_eq!(
+            shortfalls[1].to_string(),
+            "lecturer 3 needs 4 teaching hours but is allocated 2"
+        );
+    }
+
+    #[test]
+    fn always_unavailable_lecturer_is_flagged() {
+        let mut problem = dataset::small_example();
+        // Subject 0 is only taught by lecturer 3.
+        problem
+            .lecturer_unavailable
+            .insert(3, (0..DEFAULT_HOURS).collect());
+        assert_eq!(
+            problem.validate(),
+            Err((0..3)
+                .map(|group| ValidationError::NoAvailableLecturer { group, subject: 0 })
+                .collect())
+        );
+    }
+
+    #[test]
+    fn preferred_hours_score_higher() {
+        let mut problem = dataset::small_example();
+        problem
+            .lecturer_preferences
+            .insert(3, [(7, 2), (8, -1)].into_iter().collect());
+        let context = Context::new(problem);
+        // Genes 0 and 1 are both hours of group 0's subject 0, which only lecturer 3 teaches.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        let mut score_at = |hour| {
+            genome[0] = Dna((0, 3, 0, TimeSlot::from_index(hour)));
+            analysis::evaluate_genes(&genome, &[0], &context).fitness
+        };
+        let (preferred, disliked, neutral) = (score_at(7), score_at(8), score_at(9));
+        assert_eq!((preferred - neutral, disliked - neutral), (2, -1));
+
+        // Of two lessons clashing at the preferred hour, only the one keeping it counts.
+        genome[0] = Dna((0, 3, 0, TimeSlot::from_index(7)));
+        genome[1] = Dna((0, 3, 0, TimeSlot::from_index(7)));
+        let score = analysis::evaluate_genes(&genome, &[0, 1], &context);
+        assert_eq!(score.preference, 2);
+        assert!(score.fitness <= (&context).highest_possible_fitness());
+    }
+
+    #[test]
+    fn impossible_pins_are_flagged() {
+        let mut problem = dataset::small_example();
+        // Group 0 has two hours of subject 0, which only lecturer 3 teaches.
+        problem.pinned = vec![
+            (0, 0, 3, 1),
+            (0, 0, 3, 2),
+            (0, 0, 3, 3),
+            (1, 4, 0, DEFAULT_HOURS),
+        ];
+        assert_eq!(
+            problem.validate(),
+            Err(vec![
+                ValidationError::PinWithoutLesson {
+                    group: 0,
+                    subject: 0,
+                    hour: 3
+                },
+                ValidationError::PinWithoutLesson {
+                    group: 1,
+                    subject: 4,
+                    hour: DEFAULT_HOURS
+                },
+                ValidationError::UnsuitablePin {
+                    subject: 4,
+                    lecturer: 0
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn unavailable_hours_violate_and_are_avoided() {
+        let mut problem = dataset::small_example();
+        problem
+            .lecturer_unavailable
+            .insert(3, (0..DEFAULT_HOURS).filter(|hour| *hour != 7).collect());
+        let context = Context::new(problem);
+
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot::from_index(5)));
+        let score = analysis::evaluate_genes(&genome, &[0], &context);
+        assert_eq!((score.satisfied, score.lecturer_violations), (0, 1));
+
+        let mut rng = genevo::random::get_rng([2; 32]);
+        let genome =
+            RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(0, &mut rng);
+        for dna in genome.iter().filter(|dna| dna.0 .1 == 3) {
+            assert_eq!(dna.0 .3.index(), 7);
+        }
+        // A group's lessons are spread over distinct hours, so only one of them can get hour 7.
+        let genome =
+            RandomScheduleBuilder(&context, InitHourDist::Spread).build_genome(0, &mut rng);
+        for group in 0..3 {
+            let hours: Vec<_> = (0..genome.len())
+                .filter(|&index| context.group_subjects[index].0 == group)
+                .filter(|&index| genome[index].0 .1 == 3)
+                .map(|index| genome[index].0 .3.index())
+                .collect();
+            assert!(hours.contains(&7), "group {group}: {hours:?}");
+        }
+    }
+
+    #[test]
+    fn colliding_genes_score_in_isolation() {
+        let context = Context::new(dataset::small_example());
+        // Genes 0 and 1 are both hours of group 0's subject 0, which only lecturer 3 teaches.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot::from_index(5)));
+        genome[1] = Dna((0, 3, 0, TimeSlot::from_index(5)));
+
+        // Being on the same day, the second one also repeats the subject.
+        let score = analysis::evaluate_genes(&genome, &[0, 1], &context);
+        assert_eq!(
+            score,
+            PartialScore {
+                fitness: -1,
+                satisfied: 1,
+                group_clashes: 1,
+                lecturer_violations: 1,
+                over_allocated_lecturers: 0,
+                room_clashes: 0,
+                undersized_rooms: 0,
+                gaps: 0,
+                split_pairs: 0,
+                daily_overloads: 0,
+                moved_pins: 0,
+                preference: 0,
+                repeated_subjects: 1,
+                long_runs: 0,
+                suitability: 0,
+                early_starts: 0,
+                repeated_lecturers: 0,
+                early_labs: 0,
+                late_first_lessons: 0,
+                disliked_lessons: 0,
+                tight_room_changes: 0,
+                off_band_days: 0,
+                stacked_difficulty: 0,
+                breakdown: FitnessBreakdown {
+                    lessons: 20,
+                    group_clashes: -10,
+                    lecturer_clashes: -10,
+                    repeated_subjects: -1,
+                    ..FitnessBreakdown::default()
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn fitness_breakdown_adds_up_to_the_fitness() {
+        let mut problem = dataset::small_example();
+        problem.room_count = 2;
+        problem.default_max_hours_per_day = Some(2);
+        problem.group_weights = [(1, 2)].into();
+        let context = Context::new(problem);
+        let mut rng = genevo::random::get_rng([4; 32]);
+        for _ in 0..20 {
+            let genome =
+                RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(0, &mut rng);
+            let breakdown = context.fitness_breakdown(&genome);
+            assert_eq!(breakdown.total(), (&context).fitness_of(&genome));
+            assert_eq!(
+                breakdown.lessons,
+                (&context).highest_possible_fitness(),
+                "{breakdown}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_lesson_pays_for_its_worst_resource_problem() {
+        let mut problem = dataset::small_example();
+        problem.room_count = 1;
+        problem.weights = FitnessWeights {
+            lesson: 4,
+            group_clash: 2,
+            lecturer_clash: 3,
+            room_clash: 5,
+            repeated_subject: 0,
+            ..FitnessWeights::default()
+        };
+        let context = Context::new(problem);
+        // Genes 0 and 1 are both hours of group 0's subject 0, which only lecturer 3 teaches.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot::from_index(5)));
+        genome[1] = Dna((0, 3, 0, TimeSlot::from_index(5)));
+
+        // The second lesson clashes on group, lecturer and room: 4 - 2 - max(3, 5).
+        let score = analysis::evaluate_genes(&genome, &[0, 1], &context);
+        assert_eq!(score.fitness, 4 + (4 - 2 - 5));
+        assert_eq!(
+            (&context).lowest_possible_fitness(),
+            30 * (4 - 2 - 10) - 3 * 5 * 2
+        );
+    }
+
+    #[test]
+    fn periods_past_the_cap_in_a_row_are_penalized() {
+        let mut problem = dataset::small_example();
+        problem.max_consecutive = [(2, 3)].into();
+        let context = Context::new(problem);
+        // Genes 2 to 5 are hours of group 0's subject 1, which lecturer 2 can teach.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        for (gene, period) in [(2, 0), (3, 1), (4, 2), (5, 3)] {
+            genome[gene] = Dna((1, 2, 0, TimeSlot { day: 0, period }));
+        }
+
+        // Four in a row is one past the cap.
+        let score = analysis::evaluate_genes(&genome, &[2, 3, 4, 5], &context);
+        assert_eq!((score.satisfied, score.long_runs), (4, 1));
+        assert!(!context.is_feasible(&genome));
+
+        // The last periods of one day and the first of the next aren't in a row.
+        for (gene, day, period) in [(2, 0, 2), (3, 0, 3), (4, 1, 0), (5, 1, 1)] {
+            genome[gene].0 .3 = TimeSlot { day, period };
+        }
+        let score = analysis::evaluate_genes(&genome, &[2, 3, 4, 5], &context);
+        assert_eq!(score.long_runs, 0);
+    }
+
+    #[test]
+    fn hours_over_the_daily_cap_are_penalized_once() {
+        let mut problem = dataset::small_example();
+        problem.default_max_hours_per_day = Some(2);
+        let context = Context::new(problem);
+        // Genes 2 to 6 are hours of group 0's subject 1, which lecturer 2 can teach.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        for (gene, period) in [(2, 0), (3, 1), (4, 2), (5, 2)] {
+            genome[gene] = Dna((1, 2, 0, TimeSlot { day: 0, period }));
+        }
+
+        // Gene 5 clashes with gene 4, so only genes 2 to 4 count towards the cap.
+        let score = analysis::evaluate_genes(&genome, &[2, 3, 4, 5], &context);
+        assert_eq!((score.lecturer_violations, score.daily_overloads), (1, 1));
+        let overloads = analysis::daily_overloads(&context, &genome[2..6].to_vec());
+        assert_eq!(
+            overloads,
+            vec![analysis::DailyOverload {
+                lecturer: 2,
+                day: 0,
+                hours: 3,
+                cap: 2,
+            }]
+        );
+
+        genome[4].0 .3.day = 1;
+        genome[5].0 .3.day = 1;
+        let score = analysis::evaluate_genes(&genome, &[2, 3, 4, 5], &context);
+        assert_eq!(score.daily_overloads, 0);
+    }
+
+    #[test]
+    fn double_lessons_want_adjacent_periods() {
+        let mut problem = dataset::small_example();
+        problem.consecutive.insert((0, 1));
+        let context = Context::new(problem);
+        // Group 0's five hours of subject 1 are genes 2 to 6, the last one a single lesson.
+        assert_eq!(context.pairs, vec![2, 4]);
+
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[2] = Dna((1, 0, 0, TimeSlot { day: 1, period: 2 }));
+        genome[3] = Dna((1, 0, 0, TimeSlot { day: 1, period: 3 }));
+        let score = analysis::evaluate_genes(&genome, &[2, 3], &context);
+        assert_eq!(score.split_pairs, 0);
+
+        for split in [
+            TimeSlot { day: 2, period: 0 },
+            TimeSlot { day: 1, period: 1 },
+        ] {
+            genome[3].0 .3 = split;
+            let score = analysis::evaluate_genes(&genome, &[2, 3], &context);
+            assert_eq!(score.split_pairs, 1, "{split}");
+        }
+    }
+
+    #[test]
+    fn double_lessons_pair_within_each_week() {
+        let mut problem = Problem::new(
+            [(0, vec![(0, 3)])].into(),
+            [(0, 3)].into(),
+            [(0, vec![0])].into(),
+        );
+        problem.weeks = 2;
+        problem.consecutive.insert((0, 0));
+        let context = Context::new(problem);
+        // Each week has one double lesson and a single one, genes 0 to 2 and 3 to 5.
+        assert_eq!(context.pairs, vec![0, 3]);
+    }
+
+    #[test]
+    fn subjects_spread_over_the_days_they_can() {
+        let mut problem = Problem::new(
+            [(0, vec![(0, 7), (1, 2)])].into(),
+            [(0, 9)].into(),
+            [(0, vec![0]), (1, vec![0])].into(),
+        );
+        problem.consecutive.insert((0, 1));
+        let context = Context::new(problem);
+        let at = |subject, days: &[u8]| -> Vec<Dna> {
+            days.iter()
+                .enumerate()
+                .map(|(period, &day)| {
+                    let slot = TimeSlot {
+                        day,
+                        period: period as u8 % 4,
+                    };
+                    Dna((subject, 0, 0, slot))
+                })
+                .collect()
+        };
+        let repeats = |days: &[u8]| {
+            let mut genome = at(0, days);
+            genome.extend(at(1, &[0, 0]));
+            analysis::evaluate_genes(&genome, &(0..genome.len()).collect::<Vec<_>>(), &context)
+                .repeated_subjects
+        };
+
+        // Seven hours over five days repeat the subject on two days however they are spread,
+        // and the halves of subject 1's double lesson share day 0 as they should.
+        assert_eq!(repeats(&[0, 1, 2, 3, 4, 0, 1]), 0);
+        assert_eq!(repeats(&[0, 0, 0, 1, 2, 3, 4]), 0);
+        assert_eq!(repeats(&[0, 0, 1, 1, 2, 3, 3]), 1);
+        assert_eq!(repeats(&[0, 0, 0, 0, 1, 1, 1]), 3);
+    }
+
+    #[test]
+    fn compact_days_score_higher_than_gappy_ones() {
+        let context = Context::new(dataset::small_example());
+        // Genes 0 and 1 are both hours of group 0's subject 0, which only lecturer 3 teaches.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot { day: 0, period: 0 }));
+        genome[1] = Dna((0, 3, 0, TimeSlot { day: 0, period: 1 }));
+        let compact = analysis::evaluate_genes(&genome, &[0, 1], &context);
+
+        genome[1].0 .3.period = 3;
+        let gappy = analysis::evaluate_genes(&genome, &[0, 1], &context);
+
+        assert_eq!((compact.satisfied, compact.gaps), (2, 0));
+        assert_eq!((gappy.satisfied, gappy.gaps), (2, 2));
+        assert!(compact.fitness > gappy.fitness);
+    }
+
+    #[test]
+    fn days_starting_in_the_first_open_period_are_early_starts() {
+        let mut problem = dataset::small_example();
+        problem.weights.early_start = 3;
+        let context = Context::new(problem.clone());
+        // Genes 0 and 1 are both hours of group 0's subject 0, which only lecturer 3 teaches.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot { day: 0, period: 0 }));
+        genome[1] = Dna((0, 3, 0, TimeSlot { day: 1, period: 1 }));
+        let score = analysis::evaluate_genes(&genome, &[0, 1], &context);
+        assert_eq!((score.early_starts, score.breakdown.early_starts), (1, -3));
+
+        // Without period 0, a day starting in period 1 starts as early as it can.
+        problem.forbidden_periods = [0].into();
+        let context = Context::new(problem);
+        genome[0].0 .3.period = 2;
+        let score = analysis::evaluate_genes(&genome, &[0, 1], &context);
+        assert_eq!(score.early_starts, 1);
+    }
+
+    #[test]
+    fn rotating_subjects_want_another_lecturer_each_week() {
+        let mut problem = dataset::small_example();
+        problem.weeks = 3;
+        problem.rotate_subjects = [1].into();
+        let context = Context::new(problem);
+        // Genes 2 to 6 are group 0's subject 1 in the first week, lecturers 0 and 2 teach it.
+        let genes_per_week = context.group_subjects.len() / 3;
+        let lessons = |week: usize| (2..7).map(move |gene| week * genes_per_week + gene);
+        let mut genome = vec![Dna((1, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        for gene in lessons(1) {
+            genome[gene].0 .1 = 2;
+        }
+        let all: Vec<_> = lessons(0).chain(lessons(1)).chain(lessons(2)).collect();
+        let score = analysis::evaluate_genes(&genome, &all, &context);
+        assert_eq!(score.repeated_lecturers, 0);
+
+        // Lecturer 2 again in week 2 repeats week 1 lesson by lesson.
+        for gene in lessons(2) {
+            genome[gene].0 .1 = 2;
+        }
+        let score = analysis::evaluate_genes(&genome, &all, &context);
+        assert_eq!(
+            (score.repeated_lecturers, score.breakdown.repeated_lecturers),
+            (5, -5)
+        );
+    }
+
+    #[test]
+    fn labs_before_their_lecture_on_the_same_day_are_early() {
+        let mut problem = dataset::small_example();
+        problem.lab_lectures = [(2, 1)].into();
+        problem.lecture_before_lab = true;
+        problem.weights.lab_before_lecture = 2;
+        let context = Context::new(problem);
+        // Gene 2 is a lecture of group 0's subject 1, genes 7 and 8 its labs of subject 2.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[2] = Dna((1, 0, 0, TimeSlot { day: 0, period: 1 }));
+        genome[7] = Dna((2, 1, 0, TimeSlot { day: 0, period: 0 }));
+        genome[8] = Dna((2, 1, 0, TimeSlot { day: 0, period: 2 }));
+        let score = analysis::evaluate_genes(&genome, &[2, 7, 8], &context);
+        assert_eq!((score.early_labs, score.breakdown.early_labs), (1, -2));
+
+        // A lab on a day without its lecture has nothing to follow.
+        genome[7].0 .3.day = 1;
+        let score = analysis::evaluate_genes(&genome, &[2, 7, 8], &context);
+        assert_eq!(score.early_labs, 0);
+
+        let mut problem = context.problem.clone();
+        problem.lecture_before_lab = false;
+        genome[7].0 .3.day = 0;
+        let context = Context::new(problem);
+        let score = analysis::evaluate_genes(&genome, &[2, 7, 8], &context);
+        assert_eq!(score.early_labs, 0);
+    }
+
+    #[test]
+    fn lessons_meant_to_start_the_day_come_first() {
+        let mut problem = dataset::small_example();
+        problem.must_be_first = [(0, 2)].into();
+        let context = Context::new(problem);
+        // Genes 7 and 8 are group 0's subject 2, gene 2 one of its other lessons.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[2] = Dna((1, 0, 0, TimeSlot { day: 0, period: 1 }));
+        genome[7] = Dna((2, 1, 0, TimeSlot { day: 0, period: 2 }));
+        genome[8] = Dna((2, 1, 0, TimeSlot { day: 1, period: 3 }));
+        let score = analysis::evaluate_genes(&genome, &[2, 7, 8], &context);
+        assert_eq!(
+            (score.late_first_lessons, score.breakdown.late_first_lessons),
+            (1, -1)
+        );
+
+        // The first lesson of a day starts it whatever its period.
+        genome[7].0 .3.period = 0;
+        let score = analysis::evaluate_genes(&genome, &[2, 7, 8], &context);
+        assert_eq!(score.late_first_lessons, 0);
+    }
+
+    #[test]
+    fn lessons_at_disliked_hours_cost_their_weight() {
+        let mut problem = dataset::small_example();
+        problem.weeks = 2;
+        problem.group_disliked_hours = [(0, [5].into())].into();
+        problem.weights.disliked_hour = 3;
+        let context = Context::new(problem);
+        // Group 0 dislikes hour 5 of every week, group 1 (from gene 10 on) doesn't mind it.
+        let mut genome =
+            vec![Dna((0, 3, 0, TimeSlot::from_index(5))); context.group_subjects.len()];
+        let score = analysis::evaluate_genes(&genome, &[0, 10], &context);
+        assert_eq!(
+            (score.disliked_lessons, score.breakdown.disliked_lessons),
+            (1, -3)
+        );
+
+        let second_week = context.group_subjects.len() / 2;
+        genome[second_week].0 .3 = TimeSlot::from_index(DEFAULT_HOURS + 5);
+        let score = analysis::evaluate_genes(&genome, &[second_week], &context);
+        assert_eq!(score.disliked_lessons, 1, "the second week dislikes it too");
+    }
+
+    #[test]
+    fn lecturers_changing_rooms_want_a_free_period_between() {
+        let mut problem = dataset::small_example();
+        problem.room_count = 2;
+        problem.room_change_needs_gap = true;
+        let context = Context::new(problem);
+        // Lecturer 3 teaches genes 0, 1 and 10, in periods 0, 1 and 3 of day 0.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot { day: 0, period: 0 }));
+        genome[1] = Dna((0, 3, 1, TimeSlot { day: 0, period: 1 }));
+        genome[10] = Dna((0, 3, 0, TimeSlot { day: 0, period: 3 }));
+        let score = analysis::evaluate_genes(&genome, &[0, 1, 10], &context);
+        assert_eq!(
+            (score.tight_room_changes, score.breakdown.tight_room_changes),
+            (1, -1)
+        );
+
+        genome[1].0 .2 = 0;
+        let score = analysis::evaluate_genes(&genome, &[0, 1, 10], &context);
+        assert_eq!(score.tight_room_changes, 0);
+    }
+
+    #[test]
+    fn days_outside_a_groups_band_of_hours_are_off_band() {
+        let mut problem = dataset::small_example();
+        problem.group_daily_min = [(0, 2)].into();
+        problem.group_daily_max = [(0, 3)].into();
+        let context = Context::new(problem);
+        // Group 0 (genes 0 to 9) has one lesson on day 0, two on day 1 and four on day 2.
+        let slots = [(0, 0), (1, 0), (1, 1), (2, 0), (2, 1), (2, 2), (2, 3)];
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        for (gene, (day, period)) in slots.into_iter().enumerate() {
+            genome[gene].0 .3 = TimeSlot { day, period };
+        }
+        let genes: Vec<_> = (0..slots.len()).collect();
+        let score = analysis::evaluate_genes(&genome, &genes, &context);
+        assert_eq!(
+            (score.off_band_days, score.breakdown.off_band_days),
+            (2, -2)
+        );
+
+        // Days without lessons are free to stay empty.
+        let score = analysis::evaluate_genes(&genome, &genes[1..3], &context);
+        assert_eq!(score.off_band_days, 0);
+    }
+
+    #[test]
+    fn hard_subjects_back_to_back_stack_their_difficulty() {
+        let mut problem = dataset::small_example();
+        problem.subject_difficulty = [(0, 3), (1, 2)].into();
+        problem.weights.stacked_difficulty = 2;
+        let context = Context::new(problem);
+        // Gene 0 is group 0's subject 0, genes 2 and 3 its subject 1 and gene 7 its subject 2.
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0].0 .3 = TimeSlot { day: 0, period: 1 };
+        genome[2].0 .3 = TimeSlot { day: 0, period: 2 };
+        genome[7].0 .3 = TimeSlot { day: 0, period: 3 };
+        genome[3].0 .3 = TimeSlot { day: 1, period: 0 };
+        let score = analysis::evaluate_genes(&genome, &[0, 2, 3, 7], &context);
+        // Subjects 0 and 1 back to back stack 2, subject 2 after subject 1 is easy.
+        assert_eq!(
+            (score.stacked_difficulty, score.breakdown.stacked_difficulty),
+            (2, -4)
+        );
+
+        genome[7].0 .3 = TimeSlot { day: 0, period: 2 };
+        genome[2].0 .3 = TimeSlot { day: 0, period: 3 };
+        let score = analysis::evaluate_genes(&genome, &[0, 2, 3, 7], &context);
+        assert_eq!(score.stacked_difficulty, 0);
+    }
+
+    #[test]
+    fn double_lessons_of_a_hard_subject_stack_nothing() {
+        let mut problem = dataset::small_example();
+        problem.subject_difficulty = [(1, 3)].into();
+        problem.consecutive.insert((0, 1));
+        let context = Context::new(problem);
+        // Genes 2 and 3 are group 0's first double lesson of subject 1.
+        let mut genome = vec![Dna((1, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[2].0 .3 = TimeSlot { day: 0, period: 1 };
+        genome[3].0 .3 = TimeSlot { day: 0, period: 2 };
+        let score = analysis::evaluate_genes(&genome, &[2, 3], &context);
+        assert_eq!(score.split_pairs, 0);
+        assert_eq!(
+            (score.stacked_difficulty, score.breakdown.stacked_difficulty),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn lessons_sharing_a_room_clash() {
+        let mut problem = dataset::small_example();
+        problem.room_count = 2;
+        let context = Context::new(problem);
+        // Gene 0 is group 0's subject 0 (lecturer 3), gene 11 group 1's subject 3 (lecturer 4).
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 1, TimeSlot::from_index(5)));
+        genome[11] = Dna((3, 4, 1, TimeSlot::from_index(5)));
+
+        let score = analysis::evaluate_genes(&genome, &[0, 11], &context);
+        assert_eq!((score.satisfied, score.room_clashes), (1, 1));
+
+        genome[11].0 .2 = 0;
+        let score = analysis::evaluate_genes(&genome, &[0, 11], &context);
+        assert_eq!((score.satisfied, score.room_clashes), (2, 0));
+    }
+
+    #[test]
+    fn groups_prefer_rooms_large_enough() {
+        let mut problem = dataset::small_example();
+        problem.room_count = 2;
+        problem.group_sizes = [(0, 30), (1, 50)].into_iter().collect();
+        problem.room_capacities = [(0, 20), (1, 40)].into_iter().collect();
+        let context = Context::new(problem);
+        assert_eq!(context.group_rooms[&0], vec![1]);
+        assert_eq!(
+            context.group_rooms[&1],
+            vec![0, 1],
+            "no room fits, so any will do"
+        );
+        assert_eq!(context.group_rooms[&2], vec![0, 1], "group 2 has no size");
+
+        let mut genome = vec![Dna((0, 0, 0, TimeSlot::default())); context.group_subjects.len()];
+        genome[0] = Dna((0, 3, 0, TimeSlot::from_index(5)));
+        let score = analysis::evaluate_genes(&genome, &[0], &context);
+        assert_eq!((score.satisfied, score.undersized_rooms), (0, 1));
+
+        genome[0].0 .2 = 1;
+        let score = analysis::evaluate_genes(&genome, &[0], &context);
+        assert_eq!((score.satisfied, score.undersized_rooms), (1, 0));
+    }
+
+    #[test]
+    fn diagnose_lists_what_the_fitness_counts() {
+        use analysis::ViolationKind;
+
+        let mut problem = dataset::small_example();
+        problem.consecutive.insert((2, 2));
+        problem.room_count = 2;
+        problem.group_sizes.insert(1, 30);
+        problem.room_capacities.insert(1, 20);
+        problem.default_max_hours_per_day = Some(3);
+        problem.max_consecutive = [(0, 1), (2, 2)].into();
+        let context = Context::new(problem);
+        let mut rng = genevo::random::get_rng([4; 32]);
+        for _ in 0..20 {
+            let genome =
+                RandomScheduleBuilder(&context, InitHourDist::Uniform).build_genome(0, &mut rng);
+            let score =
+                analysis::evaluate_genes(&genome, &(0..genome.len()).collect::<Vec<_>>(), &context);
+            let violations = analysis::diagnose(&context, &genome);
+            let count = |kinds: &[ViolationKind]| {
+                violations
+                    .iter()
+                    .filter(|violation| kinds.contains(&violation.kind))
+                    .count()
+            };
+            assert_eq!(count(&[ViolationKind::GroupClash]), score.group_clashes);
+            assert_eq!(
+                count(&[ViolationKind::OverAllocatedLecturer]),
+                score.over_allocated_lecturers
+            );
+            assert_eq!(count(&[ViolationKind::RoomClash]), score.room_clashes);
+            assert_eq!(
+                count(&[ViolationKind::RoomTooSmall]),
+                score.undersized_rooms
+            );
+            assert_eq!(
+                count(&[ViolationKind::SplitDoubleLesson]),
+                score.split_pairs
+            );
+            let overloads: usize = violations
+                .iter()
+                .map(|violation| match violation.kind {
+                    ViolationKind::DailyOverload { hours, cap } => hours - cap,
+                    _ => 0,
+                })
+                .sum();
+            assert_eq!(overloads, score.daily_overloads);
+            let long_runs = violations
+                .iter()
+                .any(|violation| matches!(violation.kind, ViolationKind::LongRun { .. }));
+            assert_eq!(long_runs, score.long_runs > 0);
+            assert!(
+                count(&[
+                    ViolationKind::LecturerClash,
+                    ViolationKind::LecturerUnavailable,
+                    ViolationKind::OverAllocatedLecturer
+                ]) >= score.lecturer_violations
+            );
+            assert_eq!(violations.is_empty(), context.is_feasible(&genome));
+        }
+    }
+
+    /// Problems of up to 3 groups, subjects and lecturers over 1 to 7 days and 1 or 2 weeks,
+    /// every subject with at least one suitable lecturer.
+    fn arbitrary_problem() -> impl proptest::strategy::Strategy<Value = Problem> {
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+
+        (1..4usize, 1..4usize)
+            .prop_flat_map(|(lecturers, subjects)| {
+                (
+                    vec(vec((0..subjects, 1..4usize), 0..4), 1..4),
+                    vec(0..10usize, lecturers),
+                    vec(vec(0..lecturers, 1..=lecturers), subjects),
+                    vec(vec(0..7 * PERIODS_PER_DAY, 0..6), lecturers),
+                    1..8usize,
+                    1..3usize,
+                    0..3usize,
+                )
+            })
+            .prop_map(
+                |(groups, budgets, teachers, unavailable, days, weeks, room_count)| {
+                    let mut problem = Problem::new(
+                        groups.into_iter().enumerate().collect(),
+                        budgets.into_iter().enumerate().collect(),
+                        teachers.into_iter().enumerate().collect(),
+                    );
+                    problem.total_hours = days * PERIODS_PER_DAY;
+                    problem.weeks = weeks;
+                    problem.room_count = room_count;
+                    problem.lecturer_unavailable = unavailable
+                        .into_iter()
+                        .map(|hours| {
+                            hours
+                                .into_iter()
+                                .filter(|&hour| hour < problem.total_hours)
+                                .collect()
+                        })
+                        .enumerate()
+                        .collect();
+                    problem
+                },
+            )
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn built_genomes_fit_their_problem(
+            problem in arbitrary_problem(),
+            spread: bool,
+            seed: [u8; 32],
+        ) {
+            let context = Context::new(problem);
+            let problem = &context.problem;
+            let dist = if spread {
+                InitHourDist::Spread
+            } else {
+                InitHourDist::Uniform
+            };
+            let mut rng = genevo::random::get_rng(seed);
+            let genome = RandomScheduleBuilder(&context, dist).build_genome(0, &mut rng);
+
+            let hours_per_week: usize = problem
+                .group_requirements
+                .values()
+                .flatten()
+                .map(|(_, hours)| hours)
+                .sum();
+            proptest::prop_assert_eq!(genome.len(), hours_per_week * problem.weeks);
+            proptest::prop_assert_eq!(genome.len(), context.group_subjects.len());
+            for (index, (dna, (_, subject))) in genome.iter().zip(&context.group_subjects).enumerate() {
+                let Dna((gene_subject, lecturer, room, slot)) = *dna;
+                proptest::prop_assert_eq!(gene_subject, *subject);
+                proptest::prop_assert!(problem.subject_requirements[subject].contains(&lecturer));
+                // Every gene stays in the week it belongs to.
+                proptest::prop_assert_eq!(
+                    slot.index() / problem.total_hours,
+                    index / hours_per_week,
+                    "gene {} at {}", index, slot.index()
+                );
+                proptest::prop_assert!(room < problem.room_count.max(1));
+            }
+        }
+    }
+}
@@ -1,336 +1,854 @@
-use genevo::mutation::value::{RandomValueMutation, RandomValueMutator};
-use once_cell::sync::OnceCell;
-use std::collections::{HashMap, HashSet};
+mod cli;
 
+use std::io::{IsTerminal, Write};
+
+use chrono::Datelike;
 use genevo::prelude::*;
-use genevo::reinsertion::elitist::ElitistReinserter;
-use genevo::selection::truncation::MaximizeSelector;
-use genevo::operator::prelude::{SinglePointCrossBreeder, UniformCrossBreeder, MultiPointCrossBreeder};
 use genevo::types::fmt::Display;
 
-pub type GroupId = usize;
-pub type SubjectId = usize;
-pub type LecturerId = usize;
+use islab3::{
+    analysis, cnf, dashboard, dataset, dot, feasible, fet, hyper, ics, input, proof, repair,
+    schedule, solver, telemetry, workload, Context, FitnessWeights, GroupId, LecturerId, Problem,
+    SolverParams, SubjectId, TimeSlot,
+};
 
-pub const HOURS: usize = 20;
+const SELFTEST_SEED: u64 = 4; // known to reach the highest fitness of `small_example` within the generation limit.
 
-static GROUP_SUBJECTS: OnceCell<Vec<(GroupId, SubjectId)>> = OnceCell::new();
-static PROBLEM: OnceCell<Problem> = OnceCell::new();
+/// Writes an export file, exiting with an error message if that fails.
+fn write_output(path: &std::path::Path, contents: &str) {
+    if let Err(error) = std::fs::write(path, contents) {
+        eprintln!("error: cannot write {}: {error}", path.display());
+        std::process::exit(1);
+    }
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct Dna(pub (SubjectId, LecturerId, usize));
+/// `lecturers` by their display names, as a `[a, b]` list.
+fn lecturer_labels<'a>(
+    problem: &Problem,
+    lecturers: impl IntoIterator<Item = &'a LecturerId>,
+) -> String {
+    let labels: Vec<_> = lecturers
+        .into_iter()
+        .map(|&lecturer| problem.lecturer_label(lecturer))
+        .collect();
+    format!("[{}]", labels.join(", "))
+}
 
-pub type Genome = Vec<Dna>; // (lecturer, hour) for the corresponding (group, subject) from `GROUP_SUBJECTS`.
+/// `(group, subject, hours)` lessons with the display names of their groups and subjects.
+fn lesson_labels(problem: &Problem, lessons: &[(GroupId, SubjectId, usize)]) -> String {
+    let labels: Vec<_> = lessons
+        .iter()
+        .map(|&(group, subject, hours)| {
+            format!(
+                "({}, {}, {hours})",
+                problem.group_label(group),
+                problem.subject_label(subject)
+            )
+        })
+        .collect();
+    format!("[{}]", labels.join(", "))
+}
 
-#[derive(Debug, Clone)]
-struct Problem {
-    pub group_requirements: HashMap<GroupId, Vec<(SubjectId, usize)>>, // list of (subject, hours) for each group.
-    pub lecturer_requirements: HashMap<LecturerId, usize>,             // hours for each lecturer.
-    pub subject_requirements: HashMap<SubjectId, Vec<LecturerId>>, // suitable lecturers for each subject.
+/// Sends progress and diagnostics to stderr through `log`, at the level `--quiet` and
+/// `--verbose` pick unless `RUST_LOG` says otherwise. The schedule itself stays on stdout.
+fn init_logging(args: &cli::Args) {
+    let level = if args.quiet {
+        log::LevelFilter::Error
+    } else if args.verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format(|buf, record| match record.level() {
+            log::Level::Error => writeln!(buf, "error: {}", record.args()),
+            log::Level::Warn => writeln!(buf, "warning: {}", record.args()),
+            _ => writeln!(buf, "{}", record.args()),
+        })
+        .init();
 }
 
-impl Problem {
-    pub fn new(
-        group_requirements: HashMap<GroupId, Vec<(SubjectId, usize)>>,
-        lecturer_requirements: HashMap<LecturerId, usize>,
-        subject_requirements: HashMap<SubjectId, Vec<LecturerId>>,
-    ) -> Self {
-        Self {
-            group_requirements,
-            lecturer_requirements,
-            subject_requirements,
-        }
+/// A bar of the generations run out of `generations`, with the best fitness so far. Hidden
+/// unless stderr is a terminal, and when exports may be on their way through a pipe.
+fn progress_bar(args: &cli::Args, generations: u64) -> indicatif::ProgressBar {
+    if !std::io::stderr().is_terminal()
+        || args.output_json.is_some()
+        || args.output_csv.is_some()
+        || args.stats_csv.is_some()
+    {
+        return indicatif::ProgressBar::hidden();
     }
+    let style = indicatif::ProgressStyle::with_template(
+        "{bar:40} {pos}/{len} generations, best fitness {msg}",
+    )
+    .expect("the progress template is valid");
+    indicatif::ProgressBar::new(generations).with_style(style)
 }
 
-/// The fitness function for `Selection`
-impl<'a> FitnessFunction<Genome, i64> for &'a Problem {
-    fn fitness_of(&self, genome: &Genome) -> i64 {
-        let mut fitness = 0i64;
-        let mut used_group_hours: HashSet<(GroupId, usize)> = HashSet::new();
-        let mut used_lecturer_hours: HashSet<(LecturerId, usize)> = HashSet::new();
-        let mut free_lecturer_hours: HashMap<LecturerId, usize> =
-            self.lecturer_requirements.clone();
-
-        for ((group, _subject), (lecturer, hour)) in GROUP_SUBJECTS
-            .get()
-            .unwrap()
-            .iter()
-            .zip(genome.iter().map(|x| (x.0 .1, x.0 .2)))
-        {
-            let satisfies_group = used_group_hours.insert((*group, hour));
-
-            let mut satisfies_lecturer = true;
-            if free_lecturer_hours
-                .get(&lecturer)
-                .copied()
-                .unwrap_or_default()
-                == 0
-            {
-                satisfies_lecturer = false;
+fn main() {
+    let args = cli::Args::parse();
+    init_logging(&args);
+    let labels = args.lang.labels();
+
+    let example = if args.selftest {
+        Some(cli::Example::Small)
+    } else {
+        args.example
+    };
+    let mut problem = if let Some(example) = example {
+        match example {
+            cli::Example::Small => dataset::small_example(),
+            cli::Example::Medium => dataset::medium_example(),
+        }
+    } else {
+        let path = args
+            .constraints
+            .as_deref()
+            .unwrap_or(std::path::Path::new("constraints.json"));
+        input::load_problem(path).unwrap_or_else(|error| {
+            eprintln!("error: {error}");
+            if let input::LoadError::NotFound(_) = error {
+                if args.constraints.is_none() {
+                    eprintln!("pass `--constraints <path>` or `--example {{small,medium}}`");
+                } else {
+                    eprintln!("check the path, or solve `--example {{small,medium}}` instead");
+                }
             }
-            if used_lecturer_hours.contains(&(lecturer, hour)) {
-                satisfies_lecturer = false;
+            std::process::exit(1);
+        })
+    };
+    problem.weeks = args.weeks;
+    if args.max_hours_per_day.is_some() {
+        problem.default_max_hours_per_day = args.max_hours_per_day;
+    }
+    if let Some(period) = args.max_period_per_day {
+        problem.limit_periods_per_day(period);
+    }
+    if let Some(path) = &args.fitness_config {
+        let weights = std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|error| error.to_string()))
+            .and_then(|weights: FitnessWeights| {
+                weights.validate().map_err(|error| error.to_string())?;
+                Ok(weights)
+            });
+        match weights {
+            Ok(weights) => problem.weights = weights,
+            Err(error) => {
+                eprintln!(
+                    "error: cannot read fitness weights from {}: {error}",
+                    path.display()
+                );
+                std::process::exit(1);
             }
+        }
+    }
+    for (name, weight) in &args.weights {
+        problem
+            .weights
+            .set(name, *weight)
+            .expect("checked by the CLI");
+    }
+    if args.selftest {
+        // The soft terms can't all be met at once, without them only a schedule that breaks
+        // no hard constraint reaches the highest possible fitness.
+        problem.weights = problem.weights.hard_only();
+    }
+    if args.print_fitness_config {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&problem.weights).unwrap()
+        );
+    }
 
-            if satisfies_lecturer {
-                *free_lecturer_hours.get_mut(&lecturer).unwrap() -= 1;
-                used_lecturer_hours.insert((lecturer, hour));
-            }
+    if let Err(errors) = problem.validate() {
+        // The rest, overloaded groups, are reported by the feasibility check below.
+        for error in errors.iter().filter(|error| error.is_fatal()) {
+            eprintln!("error: {error}");
+        }
+        let fatal = errors.iter().filter(|error| error.is_fatal()).count();
+        if fatal > 0 {
+            eprintln!("the problem has {fatal} error(s), not solving it");
+            std::process::exit(1);
+        }
+    }
 
-            match (satisfies_group, satisfies_lecturer) {
-                (true, true) => fitness += 1,
-                (false, false) => fitness -= 1,
-                _ => {}
-            }
+    let feasibility = problem.feasibility_check();
+    for shortfall in &feasibility.shortfalls {
+        if args.strict {
+            eprintln!("error: {shortfall}");
+        } else {
+            log::warn!("{shortfall}, so no schedule satisfies every hard constraint");
         }
+    }
+    if args.strict && !feasibility.is_clear() {
+        eprintln!("the problem has no feasible schedule, not solving it with `--strict`");
+        std::process::exit(1);
+    }
 
-        fitness
+    let capacity = analysis::capacity_utilization(&problem);
+    log::debug!(
+        "capacity utilization: {} ({} required hours, {} lecturer hours per week)",
+        capacity.overall,
+        capacity.overall.demand,
+        capacity.overall.capacity
+    );
+    for (lecturer, utilization) in &capacity.lecturers {
+        // Only the lecturers that leave no room for the solver are worth pointing out, those
+        // over capacity are already reported as shortfalls.
+        if utilization.demand > 0 && utilization.demand == utilization.capacity {
+            log::debug!(
+                "  lecturer {}: {} of {} hours are subjects only they teach, {utilization}",
+                problem.lecturer_label(*lecturer),
+                utilization.demand,
+                utilization.capacity
+            );
+        }
     }
 
-    fn average(&self, values: &[i64]) -> i64 {
-        (values.iter().sum::<i64>() as f32 / values.len() as f32).round() as i64
+    for conflict in analysis::unavoidable_lecturer_conflicts(&problem) {
+        match conflict.group {
+            None => log::warn!(
+                "lecturer {} is the only option for {} hours ({} as (group, subject, hours)), \
+                 but can teach only {} of them, so collisions are unavoidable",
+                problem.lecturer_label(conflict.lecturers[0]),
+                conflict.forced_hours,
+                lesson_labels(&problem, &conflict.lessons),
+                conflict.open_hours,
+            ),
+            Some(group) => log::warn!(
+                "group {} has {} hours only lecturers {} can teach ({} as (group, subject, \
+                 hours)), but only {} of them fit into its hours, so collisions are unavoidable",
+                problem.group_label(group),
+                conflict.forced_hours,
+                lecturer_labels(&problem, &conflict.lecturers),
+                lesson_labels(&problem, &conflict.lessons),
+                conflict.open_hours,
+            ),
+        }
     }
 
-    fn highest_possible_fitness(&self) -> i64 {
-        GROUP_SUBJECTS.get().unwrap().len() as i64
+    for set in analysis::interchangeable_lecturers(&problem) {
+        log::info!(
+            "note: lecturers {} are interchangeable (same subjects, hours and constraints){}",
+            lecturer_labels(&problem, &set),
+            if args.break_symmetry {
+                ", breaking the symmetry"
+            } else {
+                ", --break-symmetry may speed up the search"
+            }
+        );
     }
 
-    fn lowest_possible_fitness(&self) -> i64 {
-        -(GROUP_SUBJECTS.get().unwrap().len() as i64)
+    let context = Context::new(problem);
+    let problem = &context.problem;
+    log::debug!("{} lessons to schedule", context.group_subjects.len());
+
+    if let Some(path) = &args.export_cnf {
+        write_output(path, &cnf::to_dimacs(&context));
     }
-}
 
-#[derive(Debug)]
-struct RandomScheduleBuilder(pub Problem);
+    let accepted = args.accept.as_ref().map(|path| {
+        let genome = input::load_schedule(path, &context).unwrap_or_else(|error| {
+            eprintln!("error: {error}");
+            std::process::exit(2);
+        });
+        let mut errors = analysis::validate_genome(&context, &genome);
+        if errors.is_empty() && !context.is_feasible(&genome) {
+            errors.push("it breaks hard constraints".to_string());
+        }
+        if !errors.is_empty() {
+            eprintln!("error: cannot accept {}:", path.display());
+            for error in errors {
+                eprintln!("  {error}");
+            }
+            std::process::exit(2);
+        }
+        genome
+    });
+
+    let defaults = SolverParams::default();
+    let params = SolverParams {
+        population_size: args.population_size.unwrap_or(defaults.population_size),
+        generation_limit: args.generations.unwrap_or(defaults.generation_limit),
+        mutation_rate: args.mutation_rate.unwrap_or(defaults.mutation_rate),
+        reinsertion_ratio: args.elitism_ratio.unwrap_or(defaults.reinsertion_ratio),
+        adaptive_mutation: args.mutation_max.map(|max| solver::AdaptiveMutation {
+            max,
+            patience: args
+                .mutation_patience
+                .unwrap_or(solver::DEFAULT_MUTATION_PATIENCE),
+        }),
+        selection: args.selection,
+        selection_ratio: args.selection_ratio.unwrap_or(defaults.selection_ratio),
+        selection_count: args.selection_count.unwrap_or(defaults.selection_count),
+        tournament_size: args.tournament_size.unwrap_or(defaults.tournament_size),
+        seed: if args.selftest {
+            Some(solver::seed_from_u64(SELFTEST_SEED))
+        } else {
+            args.seed.map(solver::seed_from_u64)
+        },
+        evaluation: args.evaluation,
+        mutate: args.mutate,
+        init_hour_dist: args.init_hour_dist,
+        crossover: args.crossover,
+        crossover_points: args.crossover_points.unwrap_or(defaults.crossover_points),
+        break_symmetry: args.break_symmetry,
+        hyper_heuristic: args.hyper_heuristic,
+        stop_on_feasible: args.stop_on_feasible,
+        patience: args.patience,
+        patience_epsilon: args.patience_epsilon.unwrap_or(defaults.patience_epsilon),
+        cache_fitness: args.cache_fitness,
+        soft_deadline: args.soft_deadline,
+        tie_break: args.tie_break.clone(),
+    };
+    // genevo's multi-point crossover needs at least two genes per cut.
+    let uses_multi_point =
+        params.crossover == hyper::Crossover::MultiPoint || params.hyper_heuristic;
+    if uses_multi_point && params.crossover_points * 2 > context.group_subjects.len() {
+        eprintln!(
+            "error: `--crossover-points` must be at most {} for this problem",
+            context.group_subjects.len() / 2
+        );
+        std::process::exit(2);
+    }
+    let highest_fitness = (&context).highest_possible_fitness();
+    if let Some(runs) = args.feasibility_sweep {
+        let progress = progress_bar(&args, params.generation_limit * runs as u64);
+        let sweep = solver::sweep_feasibility(&context, &params, runs, |_, step| {
+            progress.inc(1);
+            progress.set_message(step.best.solution.fitness.to_string());
+        });
+        progress.finish_and_clear();
+        let never = format!(
+            "{:.0}% {} {}",
+            sweep.never_feasible() * 100.0,
+            labels.never_feasible_in,
+            sweep.generation_limit
+        );
+        match sweep.median() {
+            Some(median) => println!(
+                "{} {median} {}; {never}",
+                labels.feasible_in_median, labels.generations
+            ),
+            None => println!("{never}"),
+        }
+        return;
+    }
+    let mut feasible = feasible::FeasibleCollector::new(args.dedup);
+    let runs = args.restarts * args.islands * args.variations;
+    let progress = progress_bar(&args, params.generation_limit * runs as u64);
+
+    // `run` is the restart, or with `--islands` the island, the step is of.
+    let on_step = |run: usize, step: &solver::Step| {
+        if args.collect_feasible {
+            feasible.collect(&context, step.iteration, step.population);
+        }
+        progress.inc(1);
+        if step.is_final {
+            return;
+        }
+        progress.set_message(step.best.solution.fitness.to_string());
+        progress.suspend(|| {
+            if args.restarts > 1 && step.iteration == 1 {
+                log::info!("restart {run}:");
+            }
+            if args.islands > 1 && (step.iteration - 1).is_multiple_of(args.migration_interval) {
+                log::info!("island {run}:");
+            }
+            log::info!(
+                "step: generation: {}, average_fitness: {}, \
+                 best fitness: {}, duration: {:?}, processing_time: {:?}",
+                step.iteration,
+                step.population.average_fitness(),
+                step.best.solution.fitness,
+                step.duration.fmt(),
+                step.processing_time.fmt(),
+            );
+            if let Some(rate) = step.mutation_rate {
+                log::debug!("adaptive mutation: next rate: {rate:.3}");
+            }
+            if let Some(operator) = step.next_operator {
+                log::debug!("hyper-heuristic: next crossover: {operator}");
+            }
+        });
+    };
+    let solution = if let Some(accepted) = &accepted {
+        let variations = solver::variations(&context, accepted, &params, args.variations, on_step);
+        progress.finish_and_clear();
+        for (index, run) in variations.runs.iter().enumerate() {
+            let changed = run
+                .genome
+                .iter()
+                .zip(accepted)
+                .filter(|(dna, before)| dna != before)
+                .count();
+            log::info!(
+                "variation {index}: best fitness {}, {changed} lessons changed",
+                run.fitness
+            );
+        }
+        log::info!("variation {} is the fittest", variations.best);
+        variations.into_best()
+    } else if args.islands > 1 {
+        let islands = solver::solve_islands(
+            &context,
+            &params,
+            args.islands,
+            args.migration_interval,
+            on_step,
+        );
+        progress.finish_and_clear();
+        for (index, island) in islands.islands.iter().enumerate() {
+            log::info!("island {index}: best fitness {}", island.fitness);
+        }
+        log::info!("island {} holds the best schedule", islands.best);
+        islands.into_best()
+    } else {
+        let restarts = solver::solve_restarts(&context, &params, args.restarts, on_step);
+        progress.finish_and_clear();
+        if args.restarts > 1 {
+            for (index, run) in restarts.runs.iter().enumerate() {
+                log::info!(
+                    "restart {index}: best fitness {}, {}: {}",
+                    run.fitness,
+                    labels.stopped,
+                    run.stop_reason.describe(labels)
+                );
+            }
+            log::info!("restart {} found the best schedule", restarts.best);
+        }
+        restarts.into_best()
+    };
+    log::info!(
+        "{}: {}",
+        labels.stopped,
+        solution.stop_reason.describe(labels)
+    );
+    if let Some(termination) = &solution.termination {
+        log::info!(
+            "{} {}: {}: {}, {} {} {} {}, {}: {}",
+            labels.final_result,
+            termination.duration.fmt(),
+            labels.generation,
+            solution.generation,
+            labels.best_fitness,
+            solution.fitness,
+            labels.found_in_generation,
+            solution.found_in_generation,
+            labels.processing_time,
+            termination.processing_time.fmt(),
+        );
+    }
+    let mut genome = solution.genome;
+    let slot = |time: TimeSlot| {
+        let day = usize::from(time.day) % problem.days();
+        let period = format!("{} {day}, {} {}", labels.day, labels.period, time.period);
+        if problem.weeks > 1 {
+            format!("{} {}, {period}", labels.week, time.week(problem.days()))
+        } else {
+            period
+        }
+    };
+    if args.repair {
+        let repair = repair::repair_rooms(&context, &mut genome);
+        for (index, from, to) in &repair.moved {
+            let (group, subject) = context.group_subjects[*index];
+            log::info!(
+                "{}: {} {}, {} {}, {}: {} {} {from} {} {} {to}",
+                labels.repair,
+                labels.group,
+                problem.group_label(group),
+                labels.subject,
+                problem.subject_label(subject),
+                slot(genome[*index].0 .3),
+                labels.moved_from,
+                labels.room,
+                labels.to,
+                labels.room
+            );
+        }
+        for index in &repair.stuck {
+            let (group, subject) = context.group_subjects[*index];
+            let (_, _, room, time) = genome[*index].0;
+            log::warn!(
+                "{}: {} {}, {} {}, {}: {} {} {room}",
+                labels.repair,
+                labels.group,
+                problem.group_label(group),
+                labels.subject,
+                problem.subject_label(subject),
+                slot(time),
+                labels.no_free_room,
+                labels.room
+            );
+        }
+    }
+    log::debug!("fitness breakdown: {}", context.fitness_breakdown(&genome));
 
-impl GenomeBuilder<Genome> for RandomScheduleBuilder {
-    fn build_genome<R>(&self, _: usize, rng: &mut R) -> Genome
-    where
-        R: Rng + Sized,
-    {
-        let group_subjects = GROUP_SUBJECTS.get().unwrap();
+    if let Some(uses) = solution.operator_uses {
+        log::info!("crossover usage:");
+        for (name, uses) in hyper::OPERATORS.iter().zip(uses) {
+            log::info!("  {name}: {uses} generations");
+        }
+    }
 
-        group_subjects
-            .iter()
-            .map(|(_group, subject)| {
-                let lecturers = self.0.subject_requirements.get(subject).unwrap();
-                let lecturer = lecturers[rng.gen_range(0..lecturers.len())];
-                let hour = rng.gen_range(0..HOURS);
+    if let Some(stats) = solution.cache_stats {
+        log::info!(
+            "fitness cache: {} hits, {} misses ({:.1}% hit rate)",
+            stats.hits,
+            stats.misses,
+            stats.hit_rate()
+        );
+    }
 
-                Dna((*subject, lecturer, hour))
-            })
-            .collect()
+    if let Some(path) = &args.stats_csv {
+        write_output(path, &telemetry::stats_csv(&solution.history));
     }
-}
 
-impl RandomValueMutation for Dna {
-    fn random_mutated<R>(mut value: Self, _min_value: &Self, max_value: &Self, rng: &mut R) -> Self
-    where
-        R: Rng + Sized,
-    {
-        value.0 .2 = rng.gen_range(0..max_value.0 .2);
+    if let Some(path) = &args.sqlite {
+        let record = telemetry::RunRecord {
+            config: format!(
+                "population_size={} generations={} selection_ratio={} selection_count={} \
+                 mutation_rate={} reinsertion_ratio={} weeks={} stop_on_feasible={} patience={} \
+                 patience_epsilon={} mutation_max={} restarts={} islands={} migration_interval={} seed={}",
+                params.population_size,
+                params.generation_limit,
+                params.selection_ratio,
+                params.selection_count,
+                params.mutation_rate,
+                params.reinsertion_ratio,
+                problem.weeks,
+                params.stop_on_feasible,
+                params
+                    .patience
+                    .map_or("none".to_string(), |patience| patience.to_string()),
+                params.patience_epsilon,
+                params
+                    .adaptive_mutation
+                    .map_or("none".to_string(), |adaptive| adaptive.max.to_string()),
+                args.restarts,
+                args.islands,
+                args.migration_interval,
+                args.seed
+                    .map_or("random".to_string(), |seed| seed.to_string()),
+            ),
+            fingerprint: analysis::fingerprint(problem),
+            final_fitness: (&context).fitness_of(&genome),
+            highest_fitness,
+            feasible: context.is_feasible(&genome),
+            generations: &solution.history,
+        };
+        if let Err(error) = telemetry::record_run(path, &record) {
+            eprintln!(
+                "error: cannot record the run in {}: {error}",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+    }
 
-        let lecturers = PROBLEM
-            .get()
-            .unwrap()
-            .subject_requirements
-            .get(&value.0 .0)
-            .unwrap();
-        let index = rng.gen_range(0..lecturers.len());
-        value.0 .1 = lecturers[index];
+    if args.selftest {
+        let fitness = (&context).fitness_of(&genome);
+        let highest = highest_fitness;
+        let mut errors = analysis::validate_genome(&context, &genome);
+        if fitness != highest {
+            errors.push(format!(
+                "fitness {fitness} is below the highest possible {highest}"
+            ));
+        }
 
-        value
+        if errors.is_empty() {
+            println!(
+                "{} ({} {fitness}/{highest})",
+                labels.selftest_pass, labels.fitness
+            );
+            return;
+        }
+        println!("{}", labels.selftest_fail);
+        for error in errors {
+            println!("  {error}");
+        }
+        std::process::exit(1);
     }
-}
 
-fn main() {
-    let problem = if std::env::var("SMALL_EXAMPLE").is_ok() {
-        let group_requirements = vec![
-            (0_usize, vec![(0_usize, 2_usize), (1, 5), (2, 2), (3, 1)]), // 10
-            (1_usize, vec![(0_usize, 1_usize), (3, 2), (4, 6), (2, 1)]), // 10
-            (2_usize, vec![(0_usize, 1_usize), (2, 8), (3, 1)]),         // 10
-        ]
-        .into_iter()
-        .collect();
-        let lecturer_requirements = vec![(0_usize, 6_usize), (1, 6), (2, 10), (3, 4), (4, 4)]
-            .into_iter()
-            .collect();
-        let subject_requirements = vec![
-            (0_usize, vec![3_usize]),
-            (1, vec![0, 2]),
-            (2, vec![0, 1]),
-            (3, vec![4]),
-            (4, vec![1, 2]),
-        ]
-        .into_iter()
-        .collect();
-        Problem::new(
-            group_requirements,
-            lecturer_requirements,
-            subject_requirements,
-        )
-    } else {
-        let str = include_str!("../constraints.json");
-        let value: serde_json::Value = serde_json::from_str(str).unwrap();
-        let group_requirements = value["groups_subjects_hours"]
-            .as_array()
-            .unwrap()
-            .iter()
-            .enumerate()
-            .map(|(group, value)| {
-                let reqs: Vec<_> = value
-                    .as_array()
-                    .unwrap()
-                    .iter()
-                    .map(|obj| {
-                        let a = obj["subject"].as_i64().unwrap() as usize;
-                        let b = obj["hours"].as_i64().unwrap() as usize;
-
-                        (a, b)
-                    })
-                    .collect();
-                (group, reqs)
-            })
-            .collect();
+    for overload in analysis::daily_overloads(&context, &genome) {
+        log::warn!(
+            "lecturer {} teaches {} hours on day {}, above their cap of {}",
+            problem.lecturer_label(overload.lecturer),
+            overload.hours,
+            overload.day,
+            overload.cap
+        );
+    }
 
-        let lecturer_requirements = value["teachers_hours"]
-            .as_array()
-            .unwrap()
-            .iter()
-            .enumerate()
-            .map(|(lecturer, value)| {
-                let hours = value.as_i64().unwrap() as usize;
-                (lecturer, hours)
-            })
-            .collect();
+    let schedule = schedule::lessons(&context, &genome);
+    let mut by_lecturer = schedule.clone();
+    by_lecturer.sort_by_key(|lesson| (lesson.lecturer, lesson.slot, lesson.subject, lesson.group));
 
-        let subject_requirements = value["subjects_teachers"]
-            .as_array()
-            .unwrap()
+    let sessions = if args.sessions {
+        schedule::sessions(&schedule)
+    } else {
+        schedule
             .iter()
-            .enumerate()
-            .map(|(subject, value)| {
-                let reqs: Vec<_> = value
-                    .as_array()
-                    .unwrap()
-                    .iter()
-                    .map(|obj| obj.as_i64().unwrap() as usize)
-                    .collect();
-                (subject, reqs)
-            })
-            .collect();
-
-        Problem::new(
-            group_requirements,
-            lecturer_requirements,
-            subject_requirements,
-        )
+            .map(|&lesson| schedule::Session { lesson, periods: 1 })
+            .collect()
     };
+    let mut by_group_table = format!("{}\n", labels.by_groups);
+    for session in &sessions {
+        let lesson = &session.lesson;
+        let mut time = slot(lesson.slot);
+        if session.periods > 1 {
+            time = format!(
+                "{}–{} ({} {})",
+                time,
+                session.last_period(),
+                session.periods,
+                labels.periods
+            );
+        }
+        by_group_table.push_str(&format!(
+            "{} {}, {time}, {} {}, {} {}\n",
+            labels.group,
+            problem.group_label(lesson.group),
+            labels.subject,
+            problem.subject_label(lesson.subject),
+            labels.lecturer,
+            problem.lecturer_label(lesson.lecturer)
+        ));
+    }
+    let mut by_lecturer_table = format!("{}\n", labels.by_lecturers);
+    for lesson in &by_lecturer {
+        by_lecturer_table.push_str(&format!(
+            "{} {}, {}, {} {}, {} {}\n",
+            labels.lecturer,
+            problem.lecturer_label(lesson.lecturer),
+            slot(lesson.slot),
+            labels.subject,
+            problem.subject_label(lesson.subject),
+            labels.group,
+            problem.group_label(lesson.group)
+        ));
+    }
+    print!("{by_group_table}\n\n\n{by_lecturer_table}");
 
-    let group_subjects: Vec<_> = problem
-        .group_requirements
-        .iter()
-        .map(|(group, subjects)| {
-            subjects
-                .iter()
-                .map(|(subject, hours)| (0..*hours).map(|_| (*group, *subject)))
-                .flatten()
-        })
-        .flatten()
-        .collect();
-    dbg!(group_subjects.len());
-    GROUP_SUBJECTS.set(group_subjects).unwrap();
-    PROBLEM.set(problem.clone()).unwrap();
-
-
-    let initial_population: Population<Genome> = build_population()
-        .with_genome_builder(RandomScheduleBuilder(problem.clone()))
-        .of_size(200)
-        .uniform_at_random();
-
-    let mut simulation = simulate(
-        genetic_algorithm()
-            .with_evaluation(&problem)
-            .with_selection(MaximizeSelector::new(0.85, 20))
-            .with_crossover(UniformCrossBreeder::new())
-            .with_mutation(RandomValueMutator::new(
-                0.2,
-                Dna((0, 0, 0)),
-                Dna((0, usize::MAX, HOURS - 1)),
-            ))
-            .with_reinsertion(ElitistReinserter::new(&problem, false, 0.85))
-            .with_initial_population(initial_population)
-            .build(),
-    )
-    .until(GenerationLimit::new(100))
-    .build();
-
-    let genome = loop {
-        let result = simulation.step();
-
-        match result {
-            Ok(SimResult::Intermediate(step)) => {
-                let evaluated_population = step.result.evaluated_population;
-                let best_solution = step.result.best_solution;
-                println!(
-                    "step: generation: {}, average_fitness: {}, \
-                     best fitness: {}, duration: {:?}, processing_time: {:?}",
-                    step.iteration,
-                    evaluated_population.average_fitness(),
-                    best_solution.solution.fitness,
-                    step.duration.fmt(),
-                    step.processing_time.fmt(),
-                );
+    if let Some(dir) = &args.output_dir {
+        if let Err(error) = std::fs::create_dir_all(dir) {
+            eprintln!("error: cannot create {}: {error}", dir.display());
+            std::process::exit(1);
+        }
+        write_output(&dir.join("by_group.txt"), &by_group_table);
+        write_output(&dir.join("by_lecturer.txt"), &by_lecturer_table);
+        write_output(
+            &dir.join("by_group.csv"),
+            &schedule::to_csv(problem, &schedule, args.template),
+        );
+        write_output(
+            &dir.join("by_lecturer.csv"),
+            &schedule::to_csv(problem, &by_lecturer, args.template),
+        );
+    }
 
-                if best_solution.solution.fitness == GROUP_SUBJECTS.get().unwrap().len() as i64 {
-                    break best_solution.solution.genome;
-                }
+    if (&context).fitness_of(&genome) < highest_fitness {
+        let violations = analysis::diagnose(&context, &genome);
+        if !violations.is_empty() {
+            println!(
+                "\n\n\n{} ({})",
+                labels.remaining_violations,
+                violations.len()
+            );
+            for violation in &violations {
+                println!("{}", violation.describe(problem, labels));
             }
-            Ok(SimResult::Final(step, processing_time, duration, stop_reason)) => {
-                let best_solution = step.result.best_solution;
-                println!("{}", stop_reason);
-                println!(
-                    "Final result after {}: generation: {}, \
-                     best solution with fitness {} found in generation {}, processing_time: {}",
-                    duration.fmt(),
-                    step.iteration,
-                    best_solution.solution.fitness,
-                    best_solution.generation,
-                    processing_time.fmt(),
-                );
+        }
+    }
 
-                break best_solution.solution.genome;
-            }
-            Err(error) => {
-                panic!("{}", error);
-            }
+    let usage = args
+        .summary
+        .then(|| analysis::schedule_usage(&context, &genome));
+    if let Some(usage) = &usage {
+        println!("\n\n\n{}", labels.utilization);
+        for (lecturer, utilization) in &usage.lecturers {
+            println!(
+                "{} {}: {} {} {} {}, {}",
+                labels.lecturer,
+                problem.lecturer_label(*lecturer),
+                utilization.demand,
+                labels.of,
+                utilization.capacity,
+                labels.hours,
+                utilization.describe(labels)
+            );
         }
-    };
+        for (group, utilization) in &usage.groups {
+            println!(
+                "{} {}: {} {} {} {}, {}",
+                labels.group,
+                problem.group_label(*group),
+                utilization.demand,
+                labels.of,
+                utilization.capacity,
+                labels.hours_occupied,
+                utilization.describe(labels)
+            );
+        }
+        println!(
+            "{}: {} {} {} {}, {}",
+            labels.overall,
+            usage.slots.demand,
+            labels.of,
+            usage.slots.capacity,
+            labels.group_hours_occupied,
+            usage.slots.describe(labels)
+        );
+        for (group, free) in &usage.free_half_days {
+            println!(
+                "{} {}: {} {} {} {}",
+                labels.group,
+                problem.group_label(*group),
+                free,
+                labels.of,
+                problem.days() * problem.weeks * 2,
+                labels.half_days_free
+            );
+        }
+    }
 
-    let mut res1 = vec![];
-    let mut res2 = vec![];
-    for ((group, subject), (lecturer, hour)) in GROUP_SUBJECTS
-        .get()
-        .unwrap()
-        .iter()
-        .zip(genome.iter().map(|x| (x.0 .1, x.0 .2))) {
+    if let Some(path) = &args.output_csv {
+        write_output(path, &schedule::to_csv(problem, &schedule, args.template));
+    }
+
+    if let Some(path) = &args.output_ics {
+        let week_start = args.week_start.unwrap_or_else(|| {
+            let today = chrono::Local::now().date_naive();
+            today - chrono::Duration::days(today.weekday().num_days_from_monday().into())
+        });
+        write_output(
+            path,
+            &ics::to_ics(problem, &schedule, week_start, args.template),
+        );
+    }
+
+    if let Some(path) = &args.output_json {
+        let output = schedule::ScheduleOutput {
+            summary: schedule::Summary {
+                fitness: (&context).fitness_of(&genome),
+                highest_possible_fitness: highest_fitness,
+            },
+            usage,
+            lessons: schedule::named(problem, &schedule),
+        };
+        write_output(path, &serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    if let Some(path) = &args.export_dashboard {
+        let dashboard = dashboard::to_dashboard(&context, &genome, args.template);
+        write_output(path, &serde_json::to_string_pretty(&dashboard).unwrap());
+    }
+
+    if let Some(path) = &args.export_fet {
+        write_output(path, &fet::to_fet(&context, &genome, args.template));
+    }
 
-        res1.push((group, hour, subject, lecturer));
-        res2.push((lecturer, hour, subject, group));
+    if let Some(path) = &args.output_dot {
+        write_output(path, &dot::to_dot(problem, &schedule));
+    }
+
+    if let Some(path) = &args.export_proof {
+        let proof = proof::to_proof(&context, &genome);
+        write_output(path, &serde_json::to_string_pretty(&proof).unwrap());
+    }
 
-        // println!("group {group}, subject {subject}, lecturer {lecturer}, hour {hour}");
+    if let Some(path) = &args.lecturer_report {
+        let report = workload::lecturer_report(problem, &genome);
+        write_output(path, &serde_json::to_string_pretty(&report).unwrap());
     }
-    res1.sort();
-    res2.sort();
 
-    println!("Schedule ordered by groups");
-    for (group, hour, subject, lecturer) in res1 {
-        println!("group {group}, hour {hour}, subject {subject}, lecturer {lecturer}");
+    if let Some(path) = &args.landscape {
+        write_output(path, &analysis::landscape_csv(&context, &genome));
     }
 
-    println!("\n\n\nSchedule ordered by lecturers");
-    for (lecturer, hour, subject, group) in res2 {
-        println!("lecturer {lecturer}, hour {hour}, subject {subject}, group {group}");
+    if args.collect_feasible {
+        println!(
+            "\n\n\n{} {} {}",
+            labels.found,
+            feasible.found.len(),
+            labels.distinct_feasible_schedules
+        );
+        for (index, (generation, genome)) in feasible.found.iter().enumerate() {
+            let genes: Vec<_> = genome
+                .iter()
+                .map(|dna| format!("{}@{}", problem.lecturer_label(dna.0 .1), dna.0 .3.index()))
+                .collect();
+            println!(
+                "#{index} ({} {generation}), {}: {}",
+                labels.generation,
+                labels.genes_per_lesson,
+                genes.join(" ")
+            );
+        }
+    }
+
+    if args.robustness {
+        let group_subjects = &context.group_subjects;
+        let robustness = feasible.robustness();
+        let robust = robustness.iter().filter(|gene| gene.is_robust()).count();
+        println!(
+            "\n\n\n{} {} {}: {robust} {} {} {}",
+            labels.robustness_across,
+            feasible.found.len(),
+            labels.feasible_schedules,
+            labels.of,
+            robustness.len(),
+            labels.assignments_robust
+        );
+        for (index, gene) in robustness.iter().enumerate() {
+            let (group, subject) = group_subjects[index];
+            let kind = if gene.is_robust() {
+                labels.robust
+            } else {
+                labels.flexible
+            };
+            println!(
+                "{} {index} ({} {}, {} {}): {kind}, {} {}, {} {:?}",
+                labels.gene,
+                labels.group,
+                problem.group_label(group),
+                labels.subject,
+                problem.subject_label(subject),
+                labels.lecturers,
+                lecturer_labels(problem, &gene.lecturers),
+                labels.hours,
+                gene.hours
+            );
+        }
+    }
+
+    if args.gene_contributions {
+        let group_subjects = &context.group_subjects;
+        let mut ranked: Vec<_> = analysis::gene_contributions(&genome, &context)
+            .into_iter()
+            .enumerate()
+            .collect();
+        ranked.sort_by_key(|(index, contribution)| (*contribution, *index));
+
+        println!("\n\n\n{}", labels.gene_contributions);
+        for (index, contribution) in ranked {
+            let (group, subject) = group_subjects[index];
+            let (_, lecturer, _, time) = genome[index].0;
+            println!(
+                "{} {index} ({} {}, {} {}, {} {}, {}): {contribution:+}",
+                labels.gene,
+                labels.group,
+                problem.group_label(group),
+                labels.subject,
+                problem.subject_label(subject),
+                labels.lecturer,
+                problem.lecturer_label(lecturer),
+                slot(time)
+            );
+        }
     }
 }
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use crate::{Context, Dna, GroupId, LecturerId, RoomId, TimeSlot};
+
+/// A genome where some lessons may not be scheduled yet, aligned with `Context::group_subjects`.
+pub type PartialGenome = Vec<Option<Dna>>;
+
+/// Which source wins when two partial schedules disagree.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MergePolicy {
+    PreferFirst,
+    PreferSecond,
+}
+
+/// An overlap found while merging, together with how it was resolved.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Conflict {
+    /// Both sources scheduled the same lesson differently.
+    SameLesson {
+        index: usize,
+        kept: Dna,
+        dropped: Dna,
+    },
+    /// Two lessons put a group into one slot twice.
+    GroupClash {
+        group: GroupId,
+        slot: TimeSlot,
+        kept: usize,
+        dropped: usize,
+    },
+    /// Two lessons put a lecturer into one slot twice.
+    LecturerClash {
+        lecturer: LecturerId,
+        slot: TimeSlot,
+        kept: usize,
+        dropped: usize,
+    },
+    /// Two lessons put a room into one slot twice.
+    RoomClash {
+        room: RoomId,
+        slot: TimeSlot,
+        kept: usize,
+        dropped: usize,
+    },
+}
+
+/// Why two partial schedules cannot be merged.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum MergeError {
+    #[error("the partial schedules have {first} and {second} lessons, expected {expected}")]
+    LengthMismatch {
+        first: usize,
+        second: usize,
+        expected: usize,
+    },
+}
+
+/// Merges two partial schedules. On conflict the lesson of the preferred source is kept and
+/// the other one is unscheduled, as is the later of two clashing lessons of the same source,
+/// so the merged genome may still contain `None` genes.
+pub fn merge_schedules(
+    context: &Context,
+    a: &PartialGenome,
+    b: &PartialGenome,
+    policy: MergePolicy,
+) -> Result<(PartialGenome, Vec<Conflict>), MergeError> {
+    let group_subjects = &context.group_subjects;
+    if a.len() != group_subjects.len() || b.len() != group_subjects.len() {
+        return Err(MergeError::LengthMismatch {
+            first: a.len(),
+            second: b.len(),
+            expected: group_subjects.len(),
+        });
+    }
+    let (preferred, other) = match policy {
+        MergePolicy::PreferFirst => (a, b),
+        MergePolicy::PreferSecond => (b, a),
+    };
+
+    let mut merged: PartialGenome = vec![None; group_subjects.len()];
+    let mut conflicts = vec![];
+    let mut group_slots: HashMap<(GroupId, TimeSlot), usize> = HashMap::new();
+    let mut lecturer_slots: HashMap<(LecturerId, TimeSlot), usize> = HashMap::new();
+    let mut room_slots: HashMap<(RoomId, TimeSlot), usize> = HashMap::new();
+    // Without rooms every lesson is in room 0, which must not count as a clash.
+    let rooms = context.problem.room_count > 0;
+
+    // Preferred lessons go in first, so any clash found afterwards is resolved in their favour.
+    for (index, dna) in preferred.iter().enumerate().chain(other.iter().enumerate()) {
+        let Some(dna) = *dna else { continue };
+        let Dna((_, lecturer, room, slot)) = dna;
+        let group = group_subjects[index].0;
+
+        if let Some(kept) = merged[index] {
+            if kept != dna {
+                conflicts.push(Conflict::SameLesson {
+                    index,
+                    kept,
+                    dropped: dna,
+                });
+            }
+        } else if let Some(&kept) = group_slots.get(&(group, slot)) {
+            conflicts.push(Conflict::GroupClash {
+                group,
+                slot,
+                kept,
+                dropped: index,
+            });
+        } else if let Some(&kept) = lecturer_slots.get(&(lecturer, slot)) {
+            conflicts.push(Conflict::LecturerClash {
+                lecturer,
+                slot,
+                kept,
+                dropped: index,
+            });
+        } else if let Some(&kept) = rooms.then(|| room_slots.get(&(room, slot))).flatten() {
+            conflicts.push(Conflict::RoomClash {
+                room,
+                slot,
+                kept,
+                dropped: index,
+            });
+        } else {
+            group_slots.insert((group, slot), index);
+            lecturer_slots.insert((lecturer, slot), index);
+            room_slots.insert((room, slot), index);
+            merged[index] = Some(dna);
+        }
+    }
+
+    Ok((merged, conflicts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Problem;
+
+    /// Group 0 has subjects 0 and 1, group 1 subject 1, taught by lecturers 0 and 1 in turn.
+    fn context() -> Context {
+        Context::new(Problem::new(
+            [(0, vec![(0, 1), (1, 1)]), (1, vec![(1, 1)])].into(),
+            [(0, 1), (1, 2)].into(),
+            [(0, vec![0]), (1, vec![1])].into(),
+        ))
+    }
+
+    fn lesson(subject: usize, period: u8) -> Option<Dna> {
+        Some(Dna((subject, subject, 0, TimeSlot { day: 0, period })))
+    }
+
+    #[test]
+    fn the_preferred_source_wins() {
+        let context = context();
+        let a = vec![lesson(0, 0), None, lesson(1, 1)];
+        let b = vec![lesson(0, 2), lesson(1, 3), None];
+
+        let (merged, conflicts) =
+            merge_schedules(&context, &a, &b, MergePolicy::PreferFirst).unwrap();
+        assert_eq!(merged, [lesson(0, 0), lesson(1, 3), lesson(1, 1)]);
+        assert_eq!(
+            conflicts,
+            [Conflict::SameLesson {
+                index: 0,
+                kept: lesson(0, 0).unwrap(),
+                dropped: lesson(0, 2).unwrap(),
+            }]
+        );
+
+        let (merged, conflicts) =
+            merge_schedules(&context, &a, &b, MergePolicy::PreferSecond).unwrap();
+        assert_eq!(merged, [lesson(0, 2), lesson(1, 3), lesson(1, 1)]);
+        assert_eq!(
+            conflicts,
+            [Conflict::SameLesson {
+                index: 0,
+                kept: lesson(0, 2).unwrap(),
+                dropped: lesson(0, 0).unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn clashes_are_reported_across_and_within_sources() {
+        let context = context();
+        let slot = TimeSlot { day: 0, period: 1 };
+        let a = vec![None, lesson(1, 1), None];
+        let b = vec![lesson(0, 1), None, lesson(1, 1)];
+        let (merged, conflicts) =
+            merge_schedules(&context, &a, &b, MergePolicy::PreferFirst).unwrap();
+        assert_eq!(merged, [None, lesson(1, 1), None]);
+        assert_eq!(
+            conflicts,
+            [
+                Conflict::GroupClash {
+                    group: 0,
+                    slot,
+                    kept: 1,
+                    dropped: 0,
+                },
+                Conflict::LecturerClash {
+                    lecturer: 1,
+                    slot,
+                    kept: 1,
+                    dropped: 2,
+                },
+            ]
+        );
+
+        // Both lessons of lecturer 1 are in the preferred source, the earlier one is kept.
+        let a = vec![None, lesson(1, 1), lesson(1, 1)];
+        let (merged, conflicts) =
+            merge_schedules(&context, &a, &vec![None; 3], MergePolicy::PreferFirst).unwrap();
+        assert_eq!(merged, [None, lesson(1, 1), None]);
+        assert_eq!(
+            conflicts,
+            [Conflict::LecturerClash {
+                lecturer: 1,
+                slot,
+                kept: 1,
+                dropped: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn sources_of_the_wrong_length_are_rejected() {
+        let context = context();
+        assert_eq!(
+            merge_schedules(
+                &context,
+                &vec![None; 3],
+                &vec![None; 2],
+                MergePolicy::PreferFirst
+            ),
+            Err(MergeError::LengthMismatch {
+                first: 3,
+                second: 2,
+                expected: 3,
+            })
+        );
+    }
+}
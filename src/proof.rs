@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+
+use crate::{Context, Genome, GroupId, LecturerId, RoomId, SubjectId, PERIODS_PER_DAY};
+
+/// A check over every instance of one hard constraint category. `violations` counts the
+/// instances that fail, so a verifier can recompute both from the listed instances.
+fn check(instances: Vec<Value>, violations: usize) -> Value {
+    json!({ "instances": instances, "violations": violations })
+}
+
+/// Builds an audit artifact for a schedule: the problem, every lesson, and for each hard
+/// constraint category the instances checked with how many of them fail. Every instance is
+/// derived from the lessons, so `scripts/verify_proof.py` can recheck feasibility from the
+/// file alone. Categories the problem doesn't use, like rooms without any, are left out.
+pub fn to_proof(context: &Context, genome: &Genome) -> Value {
+    let problem = &context.problem;
+    let group_subjects = &context.group_subjects;
+    let genes_per_week = group_subjects.len() / problem.weeks;
+
+    let mut groups: Vec<_> = problem.group_requirements.iter().collect();
+    groups.sort();
+    let mut lecturers: Vec<_> = problem.lecturer_requirements.iter().collect();
+    lecturers.sort();
+    let mut subjects: Vec<_> = problem.subject_requirements.iter().collect();
+    subjects.sort();
+
+    let mut lessons = vec![];
+    let mut scheduled: BTreeMap<(GroupId, SubjectId, usize), usize> = BTreeMap::new();
+    let mut eligibility = vec![];
+    let mut ineligible = 0;
+    let mut availability = vec![];
+    let mut unavailable = 0;
+    let mut hour_range = vec![];
+    let mut out_of_range = 0;
+    let mut by_group: BTreeMap<(GroupId, usize), Vec<usize>> = BTreeMap::new();
+    let mut by_lecturer: BTreeMap<(LecturerId, usize), Vec<usize>> = BTreeMap::new();
+    let mut by_room: BTreeMap<(RoomId, usize), Vec<usize>> = BTreeMap::new();
+    let mut room_capacity = vec![];
+    let mut undersized = 0;
+    let mut assigned: BTreeMap<(LecturerId, usize), usize> = BTreeMap::new();
+    let mut daily: BTreeMap<(LecturerId, usize), BTreeSet<usize>> = BTreeMap::new();
+    for (id, ((group, subject), dna)) in group_subjects.iter().zip(genome).enumerate() {
+        let (_, lecturer, room, slot) = dna.0;
+        let hour = slot.index();
+        let week = id / genes_per_week;
+        lessons.push(json!({
+            "id": id,
+            "group": group,
+            "subject": subject,
+            "week": week,
+            "lecturer": lecturer,
+            "room": room,
+            "hour": hour,
+        }));
+        *scheduled.entry((*group, *subject, week)).or_default() += 1;
+
+        let eligible = problem
+            .subject_requirements
+            .get(subject)
+            .is_some_and(|suitable| suitable.contains(&lecturer));
+        ineligible += usize::from(!eligible);
+        eligibility.push(json!({
+            "lesson": id,
+            "subject": subject,
+            "lecturer": lecturer,
+            "eligible": eligible,
+        }));
+
+        let available = problem.is_available(lecturer, hour % problem.total_hours);
+        unavailable += usize::from(!available);
+        availability.push(json!({
+            "lesson": id,
+            "lecturer": lecturer,
+            "hour": hour,
+            "available": available,
+        }));
+
+        let in_week = hour / problem.total_hours == week;
+        out_of_range += usize::from(!in_week);
+        hour_range.push(json!({
+            "lesson": id,
+            "week": week,
+            "hour": hour,
+            "in_week": in_week,
+        }));
+
+        by_group.entry((*group, hour)).or_default().push(id);
+        by_lecturer.entry((lecturer, hour)).or_default().push(id);
+        by_room.entry((room, hour)).or_default().push(id);
+
+        let fits = problem.fits(*group, room);
+        undersized += usize::from(!fits);
+        room_capacity.push(json!({
+            "lesson": id,
+            "group": group,
+            "room": room,
+            "size": problem.group_sizes.get(group),
+            "capacity": problem.room_capacities.get(&room),
+            "fits": fits,
+        }));
+        *assigned.entry((lecturer, week)).or_default() += 1;
+        daily
+            .entry((lecturer, hour / PERIODS_PER_DAY))
+            .or_default()
+            .insert(hour);
+    }
+
+    let mut coverage = vec![];
+    let mut uncovered = 0;
+    for (group, requirements) in &groups {
+        for (subject, required) in requirements.iter() {
+            for week in 0..problem.weeks {
+                let count = scheduled
+                    .get(&(**group, *subject, week))
+                    .copied()
+                    .unwrap_or_default();
+                uncovered += usize::from(count != *required);
+                coverage.push(json!({
+                    "group": group,
+                    "subject": subject,
+                    "week": week,
+                    "required": required,
+                    "scheduled": count,
+                }));
+            }
+        }
+    }
+
+    let occupancy = |cells: &BTreeMap<(usize, usize), Vec<usize>>, key: &str| {
+        let violations = cells.values().filter(|ids| ids.len() > 1).count();
+        let instances = cells
+            .iter()
+            .map(|((id, hour), lessons)| json!({ key: id, "hour": hour, "lessons": lessons }))
+            .collect();
+        check(instances, violations)
+    };
+
+    let mut budget = vec![];
+    let mut over_budget = 0;
+    for (lecturer, hours) in &lecturers {
+        for week in 0..problem.weeks {
+            let count = assigned
+                .get(&(**lecturer, week))
+                .copied()
+                .unwrap_or_default();
+            over_budget += usize::from(count > **hours);
+            budget.push(json!({
+                "lecturer": lecturer,
+                "week": week,
+                "assigned": count,
+                "budget": hours,
+            }));
+        }
+    }
+    // Lecturers teaching without any budget entry have a budget of zero.
+    for ((lecturer, week), count) in &assigned {
+        if !problem.lecturer_requirements.contains_key(lecturer) {
+            over_budget += 1;
+            budget.push(json!({
+                "lecturer": lecturer,
+                "week": week,
+                "assigned": count,
+                "budget": 0,
+            }));
+        }
+    }
+
+    let mut checks = json!({
+        "coverage": check(coverage, uncovered),
+        "eligibility": check(eligibility, ineligible),
+        "availability": check(availability, unavailable),
+        "hour_range": check(hour_range, out_of_range),
+        "group_occupancy": occupancy(&by_group, "group"),
+        "lecturer_occupancy": occupancy(&by_lecturer, "lecturer"),
+        "lecturer_budget": check(budget, over_budget),
+    });
+    // Without rooms every lesson is in room 0, which must not count as a clash.
+    if problem.room_count > 0 {
+        checks["room_occupancy"] = occupancy(&by_room, "room");
+        checks["room_capacity"] = check(room_capacity, undersized);
+    }
+    let has_daily_caps =
+        !problem.max_hours_per_day.is_empty() || problem.default_max_hours_per_day.is_some();
+    if has_daily_caps {
+        let mut instances = vec![];
+        let mut overloaded = 0;
+        for ((lecturer, day), hours) in &daily {
+            let Some(cap) = problem.daily_cap(*lecturer) else {
+                continue;
+            };
+            overloaded += usize::from(hours.len() > cap);
+            instances.push(json!({
+                "lecturer": lecturer,
+                "day": day,
+                "hours": hours,
+                "cap": cap,
+            }));
+        }
+        checks["lecturer_daily_hours"] = check(instances, overloaded);
+    }
+    if !problem.max_consecutive.is_empty() {
+        let mut instances = vec![];
+        let mut too_long = 0;
+        for ((lecturer, day), hours) in &daily {
+            let Some(&cap) = problem.max_consecutive.get(lecturer) else {
+                continue;
+            };
+            let (mut longest_run, mut run) = (0, 0);
+            for &hour in hours {
+                let follows = hour > 0 && hours.contains(&(hour - 1));
+                run = if follows { run + 1 } else { 1 };
+                longest_run = longest_run.max(run);
+            }
+            too_long += usize::from(longest_run > cap);
+            instances.push(json!({
+                "lecturer": lecturer,
+                "day": day,
+                "hours": hours,
+                "longest_run": longest_run,
+                "cap": cap,
+            }));
+        }
+        checks["lecturer_consecutive_periods"] = check(instances, too_long);
+    }
+    if !context.pairs.is_empty() {
+        let mut instances = vec![];
+        let mut split = 0;
+        for &first in &context.pairs {
+            let (a, b) = (genome[first].0 .3, genome[first + 1].0 .3);
+            let adjacent = a.day == b.day && a.period + 1 == b.period;
+            split += usize::from(!adjacent);
+            instances.push(json!({ "lessons": [first, first + 1], "adjacent": adjacent }));
+        }
+        checks["double_lessons"] = check(instances, split);
+    }
+    if !problem.pinned.is_empty() {
+        // Identical pins need a matching lesson each.
+        let mut unclaimed: BTreeMap<_, usize> = BTreeMap::new();
+        for ((group, subject), dna) in group_subjects.iter().zip(genome) {
+            let (_, lecturer, _, slot) = dna.0;
+            *unclaimed
+                .entry((*group, *subject, lecturer, slot.index()))
+                .or_default() += 1;
+        }
+        let mut instances = vec![];
+        let mut moved = 0;
+        for &(group, subject, lecturer, hour) in &problem.pinned {
+            let kept = match unclaimed.get_mut(&(group, subject, lecturer, hour)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            };
+            moved += usize::from(!kept);
+            instances.push(json!({
+                "group": group,
+                "subject": subject,
+                "lecturer": lecturer,
+                "hour": hour,
+                "kept": kept,
+            }));
+        }
+        checks["pinned_lessons"] = check(instances, moved);
+    }
+    let feasible = checks
+        .as_object()
+        .unwrap()
+        .values()
+        .all(|check| check["violations"] == 0);
+
+    json!({
+        "hours_per_week": problem.total_hours,
+        "periods_per_day": PERIODS_PER_DAY,
+        "weeks": problem.weeks,
+        "room_count": problem.room_count,
+        "problem": {
+            "default_max_hours_per_day": problem.default_max_hours_per_day,
+            "forbidden_periods": problem.forbidden_periods.iter().collect::<BTreeSet<_>>(),
+            "groups": groups
+                .iter()
+                .map(|(group, requirements)| json!({
+                    "group": group,
+                    "size": problem.group_sizes.get(group),
+                    "subjects": requirements
+                        .iter()
+                        .map(|(subject, hours)| json!({
+                            "subject": subject,
+                            "hours": hours,
+                            "consecutive": problem.consecutive.contains(&(**group, *subject)),
+                        }))
+                        .collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+            "lecturers": lecturers
+                .iter()
+                .map(|(lecturer, hours)| {
+                    let mut unavailable: Vec<_> = problem
+                        .lecturer_unavailable
+                        .get(lecturer)
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                    unavailable.sort();
+                    json!({
+                        "lecturer": lecturer,
+                        "budget_per_week": hours,
+                        "unavailable_hours": unavailable,
+                        "max_hours_per_day": problem.max_hours_per_day.get(lecturer),
+                        "max_consecutive": problem.max_consecutive.get(lecturer),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "subjects": subjects
+                .iter()
+                .map(|(subject, suitable)| json!({ "subject": subject, "lecturers": suitable }))
+                .collect::<Vec<_>>(),
+            "pinned": problem
+                .pinned
+                .iter()
+                .map(|(group, subject, lecturer, hour)| json!({
+                    "group": group,
+                    "subject": subject,
+                    "lecturer": lecturer,
+                    "hour": hour,
+                }))
+                .collect::<Vec<_>>(),
+            "rooms": (0..problem.room_count)
+                .map(|room| json!({ "room": room, "capacity": problem.room_capacities.get(&room) }))
+                .collect::<Vec<_>>(),
+        },
+        "lessons": lessons,
+        "checks": checks,
+        "feasible": feasible,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dna, Problem, TimeSlot};
+
+    /// Group 0 takes subjects 0 and 1, group 1 subject 1, an hour each a week. Lecturer 0
+    /// teaches subject 0, lecturer 1 subject 1 to both groups.
+    fn context() -> Context {
+        Context::new(Problem::new(
+            [(0, vec![(0, 1), (1, 1)]), (1, vec![(1, 1)])].into(),
+            [(0, 1), (1, 2)].into(),
+            [(0, vec![0]), (1, vec![1])].into(),
+        ))
+    }
+
+    fn genome(hours: [usize; 3]) -> Genome {
+        [(0, 0), (1, 1), (1, 1)]
+            .into_iter()
+            .zip(hours)
+            .map(|((subject, lecturer), hour)| {
+                Dna((subject, lecturer, 0, TimeSlot::from_index(hour)))
+            })
+            .collect()
+    }
+
+    fn violations(proof: &Value) -> BTreeMap<&str, u64> {
+        proof["checks"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(name, check)| (name.as_str(), check["violations"].as_u64().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn feasible_schedules_prove_feasible() {
+        let context = context();
+        let genome = genome([0, 1, 2]);
+        assert!(context.is_feasible(&genome));
+
+        let proof = to_proof(&context, &genome);
+        assert_eq!(proof["feasible"], true);
+        assert!(violations(&proof).values().all(|&count| count == 0));
+        assert_eq!(proof["lessons"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn clashing_schedules_fail_the_occupancy_checks() {
+        let context = context();
+        // Lecturer 1 teaches both groups at hour 1.
+        let genome = genome([0, 1, 1]);
+        assert!(!context.is_feasible(&genome));
+
+        let proof = to_proof(&context, &genome);
+        assert_eq!(proof["feasible"], false);
+        let violations = violations(&proof);
+        assert_eq!(violations["lecturer_occupancy"], 1);
+        assert_eq!(violations["group_occupancy"], 0);
+        assert_eq!(
+            violations.values().sum::<u64>(),
+            1,
+            "only the clash: {violations:?}"
+        );
+        let clash = &proof["checks"]["lecturer_occupancy"]["instances"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|cell| cell["lessons"].as_array().unwrap().len() > 1)
+            .unwrap();
+        assert_eq!(clash["lecturer"], 1);
+        assert_eq!(clash["hour"], 1);
+        assert_eq!(clash["lessons"], json!([1, 2]));
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use crate::{Context, Genome, RoomId};
+
+/// What `repair_rooms` did about the lessons in a room too small for their group or taken by
+/// an earlier lesson.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomRepair {
+    pub moved: Vec<(usize, RoomId, RoomId)>, // (gene, from, to) for each lesson given a new room.
+    pub stuck: Vec<usize>, // genes left as they were, no fitting room being free at their hour.
+}
+
+/// Moves each lesson whose room is too small for its group, or already taken at its hour, to
+/// a free room at the same hour that seats the group, the smallest such room so that larger
+/// ones stay free for larger groups. Lessons already in a free fitting room keep it, and of
+/// two lessons sharing a room the earlier gene does. Nothing changes without rooms.
+pub fn repair_rooms(context: &Context, genome: &mut Genome) -> RoomRepair {
+    let problem = &context.problem;
+    let mut repair = RoomRepair::default();
+    if problem.room_count == 0 {
+        return repair;
+    }
+    let mut rooms: Vec<_> = (0..problem.room_count).collect();
+    rooms.sort_by_key(|room| (problem.room_capacities.get(room), *room));
+
+    let mut taken = HashSet::new();
+    let mut misplaced = vec![];
+    for (index, dna) in genome.iter().enumerate() {
+        let (_, _, room, slot) = dna.0;
+        let group = context.group_subjects[index].0;
+        if problem.fits(group, room) && taken.insert((room, slot.index())) {
+            continue;
+        }
+        misplaced.push(index);
+    }
+    for index in misplaced {
+        let (_, _, from, slot) = genome[index].0;
+        let group = context.group_subjects[index].0;
+        let free = rooms
+            .iter()
+            .find(|&&room| problem.fits(group, room) && !taken.contains(&(room, slot.index())));
+        match free {
+            Some(&to) => {
+                taken.insert((to, slot.index()));
+                genome[index].0 .2 = to;
+                repair.moved.push((index, from, to));
+            }
+            None => repair.stuck.push(index),
+        }
+    }
+    repair
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dataset, Dna, TimeSlot};
+
+    #[test]
+    fn lessons_move_to_the_smallest_free_room_that_fits() {
+        let mut problem = dataset::small_example();
+        problem.total_hours = 40;
+        problem.room_count = 3;
+        problem.group_sizes = [(0, 30), (1, 20), (2, 20)].into();
+        problem.room_capacities = [(0, 40), (1, 10), (2, 25)].into();
+        let context = Context::new(problem);
+        let mut genome: Genome = (0..context.group_subjects.len())
+            .map(|index| Dna((0, 0, 0, TimeSlot::from_index(index))))
+            .collect();
+        // Genes 0, 10 and 20 are the first of groups 0, 1 and 2, with 30, 20 and 20 students.
+        let slot = TimeSlot::from_index(39);
+        genome[0] = Dna((0, 3, 1, slot)); // too small.
+        genome[10] = Dna((0, 3, 0, slot));
+        genome[20] = Dna((0, 3, 0, slot)); // taken by gene 10.
+
+        let repair = repair_rooms(&context, &mut genome);
+        // Only room 0 seats group 0, so gene 0 has nowhere to go.
+        assert_eq!(
+            repair,
+            RoomRepair {
+                moved: vec![(20, 0, 2)],
+                stuck: vec![0],
+            }
+        );
+        assert_eq!([0, 10, 20].map(|index| genome[index].0 .2), [1, 0, 2]);
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+
+use crate::analysis::ScheduleUsage;
+use crate::ics::Template;
+use crate::{Context, Genome, GroupId, LecturerId, Problem, RoomId, SubjectId, TimeSlot};
+
+/// One lesson of a decoded schedule. Fields are ordered so that sorting lists lessons by
+/// group, then slot. The slot serializes as separate `day` and `period` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ScheduledLesson {
+    pub group: GroupId,
+    #[serde(flatten)]
+    pub slot: TimeSlot,
+    pub subject: SubjectId,
+    pub lecturer: LecturerId,
+    pub room: RoomId,
+}
+
+/// The `--output-json` document: a summary of the run and every lesson, sorted as
+/// `lessons` sorts them so that outputs of different runs diff cleanly.
+#[derive(Debug, Serialize)]
+pub struct ScheduleOutput<'a> {
+    pub summary: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ScheduleUsage>, // with `--summary`.
+    pub lessons: Vec<NamedLesson<'a>>,
+}
+
+/// A lesson with the display names of its group, subject and lecturer next to their ids,
+/// each left out if the problem has none.
+#[derive(Debug, Serialize)]
+pub struct NamedLesson<'a> {
+    #[serde(flatten)]
+    pub lesson: ScheduledLesson,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lecturer_name: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub fitness: i64,
+    pub highest_possible_fitness: i64,
+}
+
+/// Decodes a genome into its lessons, sorted by group, then slot.
+pub fn lessons(context: &Context, genome: &Genome) -> Vec<ScheduledLesson> {
+    let mut lessons: Vec<_> = context
+        .group_subjects
+        .iter()
+        .zip(genome)
+        .map(|(&(group, subject), dna)| ScheduledLesson {
+            group,
+            slot: dna.0 .3,
+            subject,
+            lecturer: dna.0 .1,
+            room: dna.0 .2,
+        })
+        .collect();
+    lessons.sort();
+    lessons
+}
+
+/// A group's lessons of one subject in consecutive periods of a day, with the same lecturer
+/// and room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub lesson: ScheduledLesson, // the first of them.
+    pub periods: u8,
+}
+
+impl Session {
+    /// The period of the session's last lesson.
+    pub fn last_period(&self) -> u8 {
+        self.lesson.slot.period + self.periods - 1
+    }
+}
+
+/// Collapses a schedule sorted as `lessons` sorts it into sessions, in the same order.
+pub fn sessions(schedule: &[ScheduledLesson]) -> Vec<Session> {
+    let mut sessions: Vec<Session> = vec![];
+    for &lesson in schedule {
+        if let Some(last) = sessions.last_mut() {
+            let first = last.lesson;
+            let continues = (
+                first.group,
+                first.slot.day,
+                first.subject,
+                first.lecturer,
+                first.room,
+            ) == (
+                lesson.group,
+                lesson.slot.day,
+                lesson.subject,
+                lesson.lecturer,
+                lesson.room,
+            ) && last.last_period() + 1 == lesson.slot.period;
+            if continues {
+                last.periods += 1;
+                continue;
+            }
+        }
+        sessions.push(Session { lesson, periods: 1 });
+    }
+    sessions
+}
+
+/// Pairs every lesson with the display names `problem` has for it.
+pub fn named<'a>(problem: &'a Problem, schedule: &[ScheduledLesson]) -> Vec<NamedLesson<'a>> {
+    schedule
+        .iter()
+        .map(|&lesson| NamedLesson {
+            lesson,
+            group_name: problem.group_names.get(&lesson.group).map(String::as_str),
+            subject_name: problem
+                .subject_names
+                .get(&lesson.subject)
+                .map(String::as_str),
+            lecturer_name: problem
+                .lecturer_names
+                .get(&lesson.lecturer)
+                .map(String::as_str),
+        })
+        .collect()
+}
+
+/// One `group,day,period,start,end,subject,lecturer,room` row per lesson, with a header row,
+/// `start` and `end` being the clock times of the period in `template`. Groups, subjects and
+/// lecturers are written by name where `problem` has one, quoted if needed.
+pub fn to_csv(problem: &Problem, schedule: &[ScheduledLesson], template: Template) -> String {
+    let mut csv = String::from("group,day,period,start,end,subject,lecturer,room\n");
+    for lesson in schedule {
+        let (start, end) = template.period_times(lesson.slot.period);
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            csv_field(&problem.group_label(lesson.group)),
+            lesson.slot.day,
+            lesson.slot.period,
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            csv_field(&problem.subject_label(lesson.subject)),
+            csv_field(&problem.lecturer_label(lesson.lecturer)),
+            lesson.room
+        ));
+    }
+    csv
+}
+
+/// Quotes a field that contains a separator, quote or line break, per RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consecutive_lessons_of_a_subject_make_one_session() {
+        let lesson = |day, period, subject| ScheduledLesson {
+            group: 0,
+            slot: TimeSlot { day, period },
+            subject,
+            lecturer: 1,
+            room: 0,
+        };
+        let schedule = [
+            lesson(0, 0, 2),
+            lesson(0, 1, 2),
+            lesson(0, 2, 2),
+            lesson(0, 3, 3),
+            lesson(1, 0, 3),
+            lesson(1, 2, 3),
+        ];
+        let found: Vec<_> = sessions(&schedule)
+            .iter()
+            .map(|session| (session.lesson, session.periods, session.last_period()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (schedule[0], 3, 2),
+                (schedule[3], 1, 3),
+                (schedule[4], 1, 0),
+                (schedule[5], 1, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_rows_carry_the_clock_times_of_the_template() {
+        let mut problem = Problem::new(
+            [(0, vec![(1, 1)])].into(),
+            [(2, 1)].into(),
+            [(1, vec![2])].into(),
+        );
+        problem.subject_names = [(1, "Algebra, part 1".to_string())].into();
+        let lesson = ScheduledLesson {
+            group: 0,
+            slot: TimeSlot { day: 3, period: 1 },
+            subject: 1,
+            lecturer: 2,
+            room: 0,
+        };
+        assert_eq!(
+            to_csv(&problem, &[lesson], Template::School),
+            "group,day,period,start,end,subject,lecturer,room\n\
+             0,3,1,09:25,10:10,\"Algebra, part 1\",2,0\n"
+        );
+    }
+}
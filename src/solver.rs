@@ -0,0 +1,1082 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+
+use genevo::algorithm::{BestSolution, EvaluatedPopulation};
+use genevo::genetic::Parents;
+use genevo::operator::{GeneticOperator, MutationOp, SelectionOp};
+use genevo::prelude::*;
+use genevo::random::{get_rng, random_seed, Prng, SeedableRng};
+use genevo::reinsertion::elitist::ElitistReinserter;
+use genevo::selection::tournament::TournamentSelector;
+use genevo::selection::truncation::MaximizeSelector;
+use genevo::statistic::ProcessingTime;
+
+use crate::cache::{CacheStats, CachingFitness, FitnessCache};
+use crate::hyper::{Crossover, OperatorSelector, DEFAULT_CROSSOVER_POINTS, OPERATORS};
+use crate::i18n::{Labels, Lang};
+use crate::telemetry::GenerationStats;
+use crate::{
+    analysis, Context, Genome, InitHourDist, Mutate, MutationRate, Problem, RandomScheduleBuilder,
+    ScheduleMutator,
+};
+
+/// How fitness evaluation and breeding are spread over threads.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Evaluation {
+    #[default]
+    Auto,
+    Serial,
+    Parallel,
+}
+
+impl Evaluation {
+    /// Below this many genes per generation (genome length × population size) the rayon
+    /// overhead outweighs the work, so `Auto` stays on one thread.
+    const PARALLEL_THRESHOLD: usize = 20_000;
+
+    pub fn is_parallel(self, workload: usize) -> bool {
+        match self {
+            Evaluation::Auto => workload >= Self::PARALLEL_THRESHOLD,
+            Evaluation::Serial => false,
+            Evaluation::Parallel => true,
+        }
+    }
+}
+
+impl FromStr for Evaluation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Evaluation::Auto),
+            "serial" => Ok(Evaluation::Serial),
+            "parallel" => Ok(Evaluation::Parallel),
+            other => Err(format!(
+                "unsupported evaluation `{other}`, expected `auto`, `serial` or `parallel`"
+            )),
+        }
+    }
+}
+
+/// How parents are picked for breeding.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Selection {
+    #[default]
+    Maximize, // the fittest individuals, best first.
+    Tournament, // the winner of each tournament among `tournament_size` random individuals.
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "maximize" => Ok(Selection::Maximize),
+            "tournament" => Ok(Selection::Tournament),
+            other => Err(format!(
+                "unsupported selection `{other}`, expected `maximize` or `tournament`"
+            )),
+        }
+    }
+}
+
+/// A secondary objective picking among genomes of the best fitness, lower being better.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TieBreak {
+    Gaps,   // free periods between a group's lessons of a day.
+    Finish, // the periods up to and including each group's last lesson of a day, summed up.
+}
+
+impl TieBreak {
+    /// How `genome` does on this objective.
+    fn cost(self, context: &Context, genome: &Genome) -> usize {
+        match self {
+            TieBreak::Gaps => {
+                let all: Vec<_> = (0..genome.len()).collect();
+                analysis::evaluate_genes(genome, &all, context).gaps
+            }
+            TieBreak::Finish => {
+                let mut finish = HashMap::new();
+                for ((group, _), dna) in context.group_subjects.iter().zip(genome) {
+                    let slot = dna.0 .3;
+                    let end = finish.entry((group, slot.day)).or_default();
+                    *end = usize::from(slot.period + 1).max(*end);
+                }
+                finish.values().sum()
+            }
+        }
+    }
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gaps" => Ok(TieBreak::Gaps),
+            "finish" => Ok(TieBreak::Finish),
+            other => Err(format!(
+                "unsupported tie-breaker `{other}`, expected `gaps` or `finish`"
+            )),
+        }
+    }
+}
+
+/// Among `best` and the genomes of `population` as fit as it, the best on the tie-breakers
+/// in order, `best` itself unless another one beats it.
+fn break_tie(
+    context: &Context,
+    tie_break: &[TieBreak],
+    population: &EvaluatedPopulation<Genome, i64>,
+    best: &Genome,
+    fitness: i64,
+) -> Genome {
+    if tie_break.is_empty() {
+        return best.clone();
+    }
+    let individuals = population.individuals();
+    let tied = individuals
+        .iter()
+        .zip(population.fitness_values())
+        .filter(|(_, value)| **value == fitness)
+        .map(|(genome, _)| genome);
+    let costs = |genome: &Genome| -> Vec<usize> {
+        tie_break
+            .iter()
+            .map(|objective| objective.cost(context, genome))
+            .collect()
+    };
+    std::iter::once(best)
+        .chain(tied)
+        .min_by_key(|genome| costs(genome))
+        .expect("at least the best genome")
+        .clone()
+}
+
+/// The genevo selector for a `Selection`, so the pipeline has one selector type whatever
+/// the choice. Roulette wheel selection is left out, as fitness can be negative.
+#[derive(Debug, Clone)]
+enum Selector {
+    Maximize(MaximizeSelector),
+    Tournament(TournamentSelector),
+}
+
+impl Selector {
+    fn new(params: &SolverParams) -> Self {
+        let (ratio, count) = (params.selection_ratio, params.selection_count);
+        match params.selection {
+            Selection::Maximize => Selector::Maximize(MaximizeSelector::new(ratio, count)),
+            // With probability 1 every tournament yields exactly its winner.
+            Selection::Tournament => Selector::Tournament(TournamentSelector::new(
+                ratio,
+                count,
+                params.tournament_size,
+                1.0,
+                false,
+            )),
+        }
+    }
+}
+
+impl GeneticOperator for Selector {
+    fn name() -> String {
+        "Configurable-Selection".to_string()
+    }
+}
+
+impl SelectionOp<Genome, i64> for Selector {
+    fn select_from<R>(
+        &self,
+        population: &EvaluatedPopulation<Genome, i64>,
+        rng: &mut R,
+    ) -> Vec<Parents<Genome>>
+    where
+        R: Rng + Sized,
+    {
+        match self {
+            Selector::Maximize(selector) => selector.select_from(population, rng),
+            Selector::Tournament(selector) => selector.select_from(population, rng),
+        }
+    }
+}
+
+/// Generations without a better average before adaptive mutation raises the rate, unless
+/// configured otherwise.
+pub const DEFAULT_MUTATION_PATIENCE: u64 = 5;
+
+/// Raises the mutation rate while the average fitness stagnates, see `MutationController`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveMutation {
+    pub max: f64,      // the rate never rises above this.
+    pub patience: u64, // generations without a better average before the rate rises.
+}
+
+/// Adapts the mutation rate once per generation: after `patience` generations without a
+/// better average fitness it grows by `RISE` each generation, up to `max`, and every
+/// improvement takes it `DECAY` of the way back to the base rate.
+#[derive(Debug)]
+struct MutationController {
+    base: f64,
+    adaptive: AdaptiveMutation,
+    best_average: i64,
+    stalled: u64,
+}
+
+impl MutationController {
+    const RISE: f64 = 1.5;
+    const DECAY: f64 = 0.5;
+
+    fn new(base: f64, adaptive: AdaptiveMutation) -> Self {
+        Self {
+            base,
+            adaptive,
+            best_average: i64::MIN,
+            stalled: 0,
+        }
+    }
+
+    fn update(&mut self, average: i64, rate: &MutationRate) {
+        if average > self.best_average {
+            (self.best_average, self.stalled) = (average, 0);
+            rate.set(self.base + (rate.get() - self.base) * Self::DECAY);
+        } else {
+            self.stalled += 1;
+            if self.stalled >= self.adaptive.patience {
+                // A base rate of 0 would never rise by multiplying alone.
+                let risen = (rate.get() * Self::RISE).max(self.base + 0.01);
+                rate.set(risen.min(self.adaptive.max));
+            }
+        }
+    }
+}
+
+/// genevo refuses to run a generation with fewer individuals than this.
+pub const MIN_POPULATION_SIZE: usize = 6;
+
+/// Genetic algorithm settings and operator options for one solve.
+#[derive(Debug, Clone)]
+pub struct SolverParams {
+    pub population_size: usize,
+    pub generation_limit: u64,
+    pub selection: Selection,
+    pub selection_ratio: f64, // parent sets selected per individual, in `(0, 1]`.
+    pub selection_count: usize, // individuals per parent set, from 2 to the population size.
+    pub tournament_size: usize, // individuals per tournament, from 1 to the population size.
+    pub mutation_rate: f64,   // the base rate with `adaptive_mutation`.
+    pub adaptive_mutation: Option<AdaptiveMutation>,
+    pub reinsertion_ratio: f64, // share of the population replaced by offspring, in `(0, 1]`.
+    pub seed: Option<Seed>, // seeds the initial population and the simulation, random if `None`.
+    pub evaluation: Evaluation,
+    pub mutate: Mutate,
+    pub init_hour_dist: InitHourDist,
+    pub crossover: Crossover,    // the first one with the hyper-heuristic.
+    pub crossover_points: usize, // cuts of the multi-point crossover.
+    pub break_symmetry: bool,    // canonically relabel interchangeable lecturers in offspring.
+    pub hyper_heuristic: bool, // pick the crossover operator per generation from its recent gains.
+    pub stop_on_feasible: bool, // stop as soon as all hard constraints are satisfied.
+    pub patience: Option<u64>, // stop after this many generations without a better best fitness.
+    pub patience_epsilon: u64, // gains in the best fitness of at most this don't count as better.
+    pub cache_fitness: bool,   // remember the fitness of every genome seen, see `CachingFitness`.
+    pub soft_deadline: Option<u64>, // generations weighing hard constraints only, see `solve_from`.
+    pub tie_break: Vec<TieBreak>, // objectives picking the reported genome among equally fit ones.
+}
+
+impl Default for SolverParams {
+    fn default() -> Self {
+        Self {
+            population_size: 200,
+            generation_limit: 100,
+            selection: Selection::default(),
+            selection_ratio: 0.85,
+            selection_count: 20,
+            tournament_size: 4,
+            mutation_rate: 0.2,
+            adaptive_mutation: None,
+            reinsertion_ratio: 0.85,
+            seed: None,
+            evaluation: Evaluation::default(),
+            mutate: Mutate::default(),
+            init_hour_dist: InitHourDist::default(),
+            crossover: Crossover::default(),
+            crossover_points: DEFAULT_CROSSOVER_POINTS,
+            break_symmetry: false,
+            hyper_heuristic: false,
+            stop_on_feasible: false,
+            patience: None,
+            patience_epsilon: 0,
+            cache_fitness: false,
+            soft_deadline: None,
+            tie_break: vec![],
+        }
+    }
+}
+
+/// One evaluated generation, as passed to the `solve_with` callback.
+pub struct Step<'a> {
+    pub iteration: u64,
+    pub population: &'a EvaluatedPopulation<Genome, i64>,
+    pub best: &'a BestSolution<Genome, i64>,
+    pub duration: chrono::Duration,
+    pub processing_time: ProcessingTime,
+    pub is_final: bool, // the generation the termination condition ended the run on.
+    pub next_operator: Option<&'static str>, // crossover chosen by the hyper-heuristic.
+    pub mutation_rate: Option<f64>, // the rate for the next generation with adaptive mutation.
+}
+
+/// Why a run ended.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StopReason {
+    GenerationLimit,
+    Optimum,                   // the best fitness reached the highest possible one.
+    Plateau { patience: u64 }, // the best fitness didn't improve for `patience` generations.
+    Feasible,                  // `stop_on_feasible` and all hard constraints are satisfied.
+    Cancelled,                 // the caller of `solve_iter` stopped pulling generations.
+}
+
+impl StopReason {
+    /// Why the run stopped, in the language of `labels`.
+    pub fn describe(&self, labels: &Labels) -> String {
+        match self {
+            StopReason::GenerationLimit => labels.stop_generation_limit.to_string(),
+            StopReason::Optimum => labels.stop_optimum.to_string(),
+            StopReason::Plateau { patience } => {
+                format!("{} {patience} {}", labels.stop_plateau, labels.generations)
+            }
+            StopReason::Feasible => labels.stop_feasible.to_string(),
+            StopReason::Cancelled => labels.stop_cancelled.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.describe(Lang::En.labels()))
+    }
+}
+
+/// How a run that went on until its termination condition ended.
+#[derive(Debug, Clone)]
+pub struct Termination {
+    pub stop_reason: String,
+    pub duration: chrono::Duration,
+    pub processing_time: ProcessingTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub genome: Genome,
+    pub fitness: i64,
+    pub generation: u64, // the generation the run ended on.
+    pub found_in_generation: u64,
+    pub stop_reason: StopReason,
+    pub termination: Option<Termination>, // `None` when stopped before the generation limit.
+    pub operator_uses: Option<[usize; 3]>, // generations per crossover with the hyper-heuristic.
+    pub cache_stats: Option<CacheStats>,  // fitness cache lookups with `cache_fitness`.
+    pub history: Vec<GenerationStats>,    // best and average fitness of every generation.
+}
+
+/// Expands a numeric seed, as given on the command line, to a full `Seed`.
+pub fn seed_from_u64(seed: u64) -> Seed {
+    Prng::seed_from_u64(seed).gen()
+}
+
+impl Solution {
+    /// Carries on with `next`, a run that started from the final population of this one,
+    /// renumbering its generations to follow on.
+    fn continue_with(&mut self, next: Solution) {
+        let offset = self.generation;
+        if next.fitness > self.fitness {
+            self.genome = next.genome;
+            self.fitness = next.fitness;
+            self.found_in_generation = offset + next.found_in_generation;
+        }
+        self.generation = offset + next.generation;
+        self.stop_reason = next.stop_reason;
+        self.termination = match (self.termination.take(), next.termination) {
+            (Some(before), Some(next)) => Some(Termination {
+                duration: before.duration + next.duration,
+                processing_time: before.processing_time + next.processing_time,
+                ..next
+            }),
+            (_, next) => next,
+        };
+        if let (Some(uses), Some(next)) = (&mut self.operator_uses, next.operator_uses) {
+            for (uses, next) in uses.iter_mut().zip(next) {
+                *uses += next;
+            }
+        }
+        if let (Some(stats), Some(next)) = (&mut self.cache_stats, next.cache_stats) {
+            stats.hits += next.hits;
+            stats.misses += next.misses;
+        }
+        self.history
+            .extend(next.history.into_iter().map(|stats| GenerationStats {
+                generation: offset + stats.generation,
+                ..stats
+            }));
+    }
+}
+
+/// Solves `problem` and returns the best genome found, with how the run got there.
+pub fn solve(problem: &Problem, params: SolverParams) -> Solution {
+    solve_with(&Context::new(problem.clone()), &params, |_| {})
+}
+
+/// Runs the genetic algorithm on `context`, calling `on_step` after every generation.
+pub fn solve_with(
+    context: &Context,
+    params: &SolverParams,
+    on_step: impl FnMut(&Step) + Send,
+) -> Solution {
+    solve_from(context, params, None, None, on_step)
+}
+
+/// `solve_with`, starting from a population of genomes by `builder` instead of random ones,
+/// for seeding the search with a strategy of the caller's own.
+pub fn solve_with_builder<B>(
+    context: &Context,
+    params: &SolverParams,
+    builder: B,
+    on_step: impl FnMut(&Step) + Send,
+) -> Solution
+where
+    B: GenomeBuilder<Genome>,
+{
+    let population_builder = build_population()
+        .with_genome_builder(builder)
+        .of_size(params.population_size);
+    let initial = match params.seed {
+        Some(seed) => population_builder.using_seed(seed),
+        None => population_builder.uniform_at_random(),
+    };
+    solve_from(context, params, Some(initial), None, on_step)
+}
+
+/// One generation of `solve_iter`.
+#[derive(Debug, Clone)]
+pub struct Generation {
+    pub stats: GenerationStats,
+    pub best: Genome, // the best genome so far, the solution in the last generation.
+}
+
+/// Solves `problem` on a thread of its own, yielding every generation as the caller pulls
+/// it. The solver waits for each generation to be taken, and stops once the iterator is
+/// dropped.
+pub fn solve_iter(problem: &Problem, params: SolverParams) -> impl Iterator<Item = Generation> {
+    let problem = problem.clone();
+    let (sender, receiver) = mpsc::sync_channel(0);
+    std::thread::spawn(move || {
+        let context = Context::new(problem);
+        let cancelled = AtomicBool::new(false);
+        solve_from(&context, &params, None, Some(&cancelled), |step| {
+            let generation = Generation {
+                stats: GenerationStats {
+                    generation: step.iteration,
+                    best: step.best.solution.fitness,
+                    average: *step.population.average_fitness(),
+                },
+                best: step.best.solution.genome.clone(),
+            };
+            if sender.send(generation).is_err() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+    });
+    receiver.into_iter()
+}
+
+/// `solve_with`, starting from `initial` rather than a random population if given and
+/// stopping once `cancelled` is set.
+///
+/// With a `soft_deadline` below the generation limit, the run is split in two: up to the
+/// deadline the soft terms weigh nothing, so the search only looks for a feasible schedule,
+/// after it the run goes on from the final population with the weights of the problem. The
+/// history shows the best and average fitness drop where the soft terms start to count, and
+/// adaptive mutation and the hyper-heuristic start over with the second run. With
+/// `stop_on_feasible` a feasible schedule before the deadline ends the run.
+fn solve_from(
+    context: &Context,
+    params: &SolverParams,
+    initial: Option<Population<Genome>>,
+    cancelled: Option<&AtomicBool>,
+    mut on_step: impl FnMut(&Step) + Send,
+) -> Solution {
+    let Some(deadline) = params
+        .soft_deadline
+        .filter(|deadline| (1..params.generation_limit).contains(deadline))
+    else {
+        return solve_cached(context, params, initial, cancelled, on_step);
+    };
+    let mut problem = context.problem.clone();
+    problem.weights = problem.weights.hard_only();
+    let hard_only = Context::new(problem);
+    let hard_params = SolverParams {
+        generation_limit: deadline,
+        ..params.clone()
+    };
+    let mut population = vec![];
+    let mut solution = solve_cached(&hard_only, &hard_params, initial, cancelled, |step| {
+        population = step.population.individuals().to_vec();
+        on_step(&Step {
+            is_final: false,
+            ..*step
+        });
+    });
+    solution.fitness = context.fitness_of(&solution.genome);
+    if matches!(
+        solution.stop_reason,
+        StopReason::Feasible | StopReason::Cancelled
+    ) {
+        return solution;
+    }
+
+    let offset = solution.generation;
+    let soft_params = SolverParams {
+        generation_limit: params.generation_limit - offset,
+        seed: params.seed.map(|seed| Prng::from_seed(seed).gen()),
+        ..params.clone()
+    };
+    let next = solve_cached(
+        context,
+        &soft_params,
+        Some(Population::with_individuals(population)),
+        cancelled,
+        |step| {
+            on_step(&Step {
+                iteration: offset + step.iteration,
+                ..*step
+            })
+        },
+    );
+    solution.continue_with(next);
+    solution
+}
+
+/// `solve_from` in a single run, caching fitness values if `params` asks for it.
+fn solve_cached(
+    context: &Context,
+    params: &SolverParams,
+    initial: Option<Population<Genome>>,
+    cancelled: Option<&AtomicBool>,
+    on_step: impl FnMut(&Step) + Send,
+) -> Solution {
+    if !params.cache_fitness {
+        return run(context, context, params, initial, cancelled, on_step);
+    }
+    let cache = FitnessCache::default();
+    let mut solution = run(
+        context,
+        CachingFitness(context, &cache),
+        params,
+        initial,
+        cancelled,
+        on_step,
+    );
+    solution.cache_stats = Some(cache.stats());
+    solution
+}
+
+/// The runs of `solve_restarts`, in order, and which of them found the best genome.
+#[derive(Debug)]
+pub struct Restarts {
+    pub runs: Vec<Solution>,
+    pub best: usize, // the first run with the highest fitness.
+}
+
+impl Restarts {
+    pub fn into_best(mut self) -> Solution {
+        self.runs.swap_remove(self.best)
+    }
+}
+
+/// Runs `solve_with` `restarts` times, independently, calling `on_step` with the index of the
+/// run. The first run uses `params.seed`, so a single restart is a plain `solve_with`; the
+/// others are seeded from it, or at random without one.
+pub fn solve_restarts(
+    context: &Context,
+    params: &SolverParams,
+    restarts: usize,
+    mut on_step: impl FnMut(usize, &Step) + Send,
+) -> Restarts {
+    assert!(restarts > 0, "at least one run is needed");
+    let mut seeds = params.seed.map(Prng::from_seed);
+    let mut runs: Vec<Solution> = Vec::with_capacity(restarts);
+    for restart in 0..restarts {
+        let seed = match &mut seeds {
+            Some(_) if restart == 0 => params.seed,
+            Some(rng) => Some(rng.gen()),
+            None => None,
+        };
+        let params = SolverParams {
+            seed,
+            ..params.clone()
+        };
+        runs.push(solve_with(context, &params, |step| on_step(restart, step)));
+    }
+    // `max_by_key` keeps the last of equal maxima, so the runs are searched backwards.
+    let best = (0..runs.len())
+        .rev()
+        .max_by_key(|&run| runs[run].fitness)
+        .expect("at least one run");
+    Restarts { runs, best }
+}
+
+/// Builds the first genome of a population as `accepted` and every other one as `accepted`
+/// with a few genes mutated like `ScheduleMutator` does at `mutation_rate`, for searching
+/// close to a schedule already accepted.
+#[derive(Debug)]
+pub struct AroundAccepted<'a> {
+    pub context: &'a Context,
+    pub accepted: &'a Genome,
+    pub mutation_rate: f64,
+    pub mutate: Mutate,
+}
+
+impl GenomeBuilder<Genome> for AroundAccepted<'_> {
+    fn build_genome<R>(&self, index: usize, rng: &mut R) -> Genome
+    where
+        R: Rng + Sized,
+    {
+        if index == 0 {
+            return self.accepted.clone();
+        }
+        let mutation_rate = MutationRate::new(self.mutation_rate);
+        ScheduleMutator::new(self.context, &mutation_rate, self.mutate)
+            .mutate(self.accepted.clone(), rng)
+    }
+}
+
+/// Runs the search `count` times from populations `AroundAccepted` builds around `accepted`,
+/// a hard-feasible schedule, seeded apart from `params.seed` like `solve_restarts`. Each run
+/// keeps the fittest schedule it saw that breaks no hard constraint, `accepted` if none beat
+/// it, so the variations only ever differ from it in what the soft terms score.
+pub fn variations(
+    context: &Context,
+    accepted: &Genome,
+    params: &SolverParams,
+    count: usize,
+    mut on_step: impl FnMut(usize, &Step) + Send,
+) -> Restarts {
+    assert!(count > 0, "at least one variation is needed");
+    assert!(
+        context.is_feasible(accepted),
+        "the accepted schedule breaks hard constraints"
+    );
+    let mut seeds = params.seed.map(Prng::from_seed);
+    let mut runs: Vec<Solution> = Vec::with_capacity(count);
+    for run in 0..count {
+        let params = SolverParams {
+            seed: seeds.as_mut().map(|rng| rng.gen()),
+            ..params.clone()
+        };
+        let builder = AroundAccepted {
+            context,
+            accepted,
+            mutation_rate: params.mutation_rate,
+            mutate: params.mutate,
+        };
+        let mut kept = (accepted.clone(), context.fitness_of(accepted), 0);
+        let mut solution = solve_with_builder(context, &params, builder, |step| {
+            let best = &step.best.solution;
+            if best.fitness > kept.1 && context.is_feasible(&best.genome) {
+                kept = (best.genome.clone(), best.fitness, step.iteration);
+            }
+            on_step(run, step);
+        });
+        (
+            solution.genome,
+            solution.fitness,
+            solution.found_in_generation,
+        ) = kept;
+        runs.push(solution);
+    }
+    let best = (0..runs.len())
+        .rev()
+        .max_by_key(|&run| runs[run].fitness)
+        .expect("at least one run");
+    Restarts { runs, best }
+}
+
+/// When the runs of `sweep_feasibility` first had a feasible schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeasibilitySweep {
+    pub generations: Vec<Option<u64>>, // per run, `None` if it never got there.
+    pub generation_limit: u64,
+}
+
+impl FeasibilitySweep {
+    /// The median generation of the runs that reached feasibility, the lower of the middle
+    /// two for an even number of them, `None` if none did.
+    pub fn median(&self) -> Option<u64> {
+        let mut reached: Vec<_> = self.generations.iter().flatten().copied().collect();
+        reached.sort_unstable();
+        reached.get(reached.len().checked_sub(1)? / 2).copied()
+    }
+
+    /// The share of runs that never reached feasibility, from 0 to 1.
+    pub fn never_feasible(&self) -> f64 {
+        let never = self.generations.iter().filter(|run| run.is_none()).count();
+        never as f64 / self.generations.len() as f64
+    }
+}
+
+/// Runs `solve_restarts` with `runs` runs, each stopping at its first feasible best schedule
+/// as with `stop_on_feasible`, for how many generations reaching feasibility takes.
+pub fn sweep_feasibility(
+    context: &Context,
+    params: &SolverParams,
+    runs: usize,
+    on_step: impl FnMut(usize, &Step) + Send,
+) -> FeasibilitySweep {
+    let params = SolverParams {
+        stop_on_feasible: true,
+        ..params.clone()
+    };
+    let restarts = solve_restarts(context, &params, runs, on_step);
+    FeasibilitySweep {
+        generations: restarts
+            .runs
+            .iter()
+            .map(|run| {
+                let feasible =
+                    matches!(run.stop_reason, StopReason::Feasible | StopReason::Optimum);
+                feasible.then_some(run.generation)
+            })
+            .collect(),
+        generation_limit: params.generation_limit,
+    }
+}
+
+/// Genomes each island sends on to the next one after every migration interval.
+pub const MIGRANTS: usize = 2;
+
+/// Generations between migrations, unless configured otherwise.
+pub const DEFAULT_MIGRATION_INTERVAL: u64 = 10;
+
+/// The islands of `solve_islands`, in order, and which of them holds the best genome.
+#[derive(Debug)]
+pub struct Islands {
+    pub islands: Vec<Solution>,
+    pub best: usize, // the first island with the highest fitness.
+}
+
+impl Islands {
+    pub fn into_best(mut self) -> Solution {
+        self.islands.swap_remove(self.best)
+    }
+}
+
+/// Runs the island model: `islands` populations of `params.population_size / islands`
+/// genomes each evolve on their own for `interval` generations at a time, after which the
+/// `MIGRANTS` best genomes of every island replace the worst of the next one, in a ring,
+/// up to `params.generation_limit` generations in all. `on_step` gets the index of the
+/// island and generations counted from the start.
+///
+/// Each interval is a run of its own, seeded from `params.seed`, so adaptive mutation and
+/// the hyper-heuristic start over with it. Selection counts and tournaments are capped at
+/// the island size. All islands stop once one of them reaches the highest possible fitness
+/// or, with `stop_on_feasible`, a feasible schedule; `patience` and `soft_deadline` are
+/// ignored.
+pub fn solve_islands(
+    context: &Context,
+    params: &SolverParams,
+    islands: usize,
+    interval: u64,
+    mut on_step: impl FnMut(usize, &Step) + Send,
+) -> Islands {
+    assert!(
+        islands > 0 && interval > 0,
+        "at least one island and generation"
+    );
+    let size = params.population_size / islands;
+    assert!(
+        size >= MIN_POPULATION_SIZE,
+        "islands of {size} genomes are too small"
+    );
+    let mut seeds = Prng::from_seed(params.seed.unwrap_or_else(random_seed));
+    let mut populations: Vec<Option<Population<Genome>>> = vec![None; islands];
+    let mut solutions: Vec<Solution> = Vec::with_capacity(islands);
+    let mut generation = 0;
+    while generation < params.generation_limit {
+        let generations = interval.min(params.generation_limit - generation);
+        let last_interval = generation + generations == params.generation_limit;
+        let mut finals = Vec::with_capacity(islands);
+        let mut stop = false;
+        for (island, population) in populations.iter_mut().enumerate() {
+            let island_params = SolverParams {
+                population_size: size,
+                generation_limit: generations,
+                selection_count: params.selection_count.min(size),
+                tournament_size: params.tournament_size.min(size),
+                seed: Some(seeds.gen()),
+                patience: None,
+                soft_deadline: None,
+                ..params.clone()
+            };
+            let mut last = None;
+            let solution = solve_from(context, &island_params, population.take(), None, |step| {
+                if step.is_final {
+                    let population = step.population;
+                    last = Some((
+                        population.individuals().to_vec(),
+                        population.fitness_values().to_vec(),
+                    ));
+                }
+                on_step(
+                    island,
+                    &Step {
+                        iteration: generation + step.iteration,
+                        is_final: step.is_final && last_interval,
+                        ..*step
+                    },
+                );
+            });
+            stop |= matches!(
+                solution.stop_reason,
+                StopReason::Optimum | StopReason::Feasible
+            );
+            match solutions.get_mut(island) {
+                Some(before) => before.continue_with(solution),
+                None => solutions.push(solution),
+            }
+            finals.push(last);
+        }
+        generation += generations;
+        if stop {
+            break;
+        }
+        populations = migrate(finals.into_iter().flatten().collect())
+            .into_iter()
+            .map(|individuals| Some(Population::with_individuals(individuals)))
+            .collect();
+    }
+    let best = (0..solutions.len())
+        .rev()
+        .max_by_key(|&island| solutions[island].fitness)
+        .expect("at least one island");
+    Islands {
+        islands: solutions,
+        best,
+    }
+}
+
+/// The populations of the islands, each given with its fitness values, after the `MIGRANTS`
+/// best genomes of every island replaced the worst of the next one.
+fn migrate(islands: Vec<(Vec<Genome>, Vec<i64>)>) -> Vec<Vec<Genome>> {
+    let by_fitness: Vec<Vec<usize>> = islands
+        .iter()
+        .map(|(_, fitness)| {
+            let mut order: Vec<_> = (0..fitness.len()).collect();
+            order.sort_by_key(|&index| std::cmp::Reverse(fitness[index]));
+            order
+        })
+        .collect();
+    let count = islands.len();
+    let mut populations: Vec<Vec<Genome>> =
+        islands.iter().map(|(genomes, _)| genomes.clone()).collect();
+    if count < 2 {
+        return populations;
+    }
+    for (island, population) in populations.iter_mut().enumerate() {
+        let from = (island + count - 1) % count;
+        let migrants = by_fitness[from].iter().take(MIGRANTS);
+        let worst = by_fitness[island].iter().rev();
+        for (&migrant, &replaced) in migrants.zip(worst) {
+            population[replaced] = islands[from].0[migrant].clone();
+        }
+    }
+    populations
+}
+
+/// `solve_from`, scoring genomes with `evaluator`.
+fn run<E>(
+    context: &Context,
+    evaluator: E,
+    params: &SolverParams,
+    initial: Option<Population<Genome>>,
+    cancelled: Option<&AtomicBool>,
+    mut on_step: impl FnMut(&Step) + Send,
+) -> Solution
+where
+    E: FitnessFunction<Genome, i64> + std::fmt::Debug + Send + Sync,
+{
+    let population_builder = build_population()
+        .with_genome_builder(RandomScheduleBuilder(context, params.init_hour_dist))
+        .of_size(params.population_size);
+    let initial_population: Population<Genome> = match (initial, params.seed) {
+        (Some(population), _) => population,
+        (None, Some(seed)) => population_builder.using_seed(seed),
+        (None, None) => population_builder.uniform_at_random(),
+    };
+
+    // Without the hyper-heuristic the selector is never updated and the breeder keeps using
+    // `params.crossover`.
+    let (mut operator_selector, mut breeder) =
+        OperatorSelector::new(params.crossover, params.crossover_points);
+    if params.break_symmetry {
+        breeder = breeder.breaking_symmetry(analysis::interchangeable_lecturers(&context.problem));
+    }
+    let mut operator_rng = get_rng(params.seed.unwrap_or_else(random_seed));
+    let highest_fitness = evaluator.highest_possible_fitness();
+    let mutation_rate = MutationRate::new(params.mutation_rate);
+    let mut mutation_controller = params
+        .adaptive_mutation
+        .map(|adaptive| MutationController::new(params.mutation_rate, adaptive));
+    // The best fitness so far and the generations since it last improved by more than
+    // `patience_epsilon`, small gains adding up until they do.
+    let (mut best_so_far, mut stalled) = (None, 0);
+    let mut history = vec![];
+
+    // genevo evaluates and breeds on the rayon pool it runs in, so a single-threaded pool
+    // makes the whole run serial.
+    let parallel = params
+        .evaluation
+        .is_parallel(context.group_subjects.len() * params.population_size);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(if parallel { 0 } else { 1 })
+        .build()
+        .expect("failed to build the evaluation thread pool");
+    pool.install(|| {
+        let simulation_builder = simulate(
+            genetic_algorithm()
+                .with_evaluation(evaluator.clone())
+                .with_selection(Selector::new(params))
+                .with_crossover(breeder)
+                .with_mutation(ScheduleMutator::new(context, &mutation_rate, params.mutate))
+                // Offspring never take precedence over fitter parents: the best fitness of a
+                // generation never drops, which `patience` and the history rely on.
+                .with_reinsertion(ElitistReinserter::new(
+                    evaluator,
+                    false,
+                    params.reinsertion_ratio,
+                ))
+                .with_initial_population(initial_population)
+                .build(),
+        )
+        .until(GenerationLimit::new(params.generation_limit));
+        let mut simulation = match params.seed {
+            Some(seed) => simulation_builder.build_with_seed(seed),
+            None => simulation_builder.build(),
+        };
+
+        loop {
+            match simulation.step() {
+                Ok(SimResult::Intermediate(step)) => {
+                    let best = &step.result.best_solution;
+                    history.push(GenerationStats {
+                        generation: step.iteration,
+                        best: best.solution.fitness,
+                        average: *step.result.evaluated_population.average_fitness(),
+                    });
+                    let next_operator = params.hyper_heuristic.then(|| {
+                        let average = *step.result.evaluated_population.average_fitness();
+                        OPERATORS[operator_selector.update(average, &mut operator_rng)]
+                    });
+                    let next_mutation_rate = mutation_controller.as_mut().map(|controller| {
+                        let average = *step.result.evaluated_population.average_fitness();
+                        controller.update(average, &mutation_rate);
+                        mutation_rate.get()
+                    });
+                    on_step(&Step {
+                        iteration: step.iteration,
+                        population: &step.result.evaluated_population,
+                        best,
+                        duration: step.duration,
+                        processing_time: step.processing_time,
+                        is_final: false,
+                        next_operator,
+                        mutation_rate: next_mutation_rate,
+                    });
+
+                    // By default the run goes on to the generation limit, as a feasible schedule
+                    // can still be improved on anything fitness rewards beyond hard constraints.
+                    let fitness = best.solution.fitness;
+                    let epsilon = i64::try_from(params.patience_epsilon).unwrap_or(i64::MAX);
+                    if best_so_far.is_none_or(|best: i64| fitness > best.saturating_add(epsilon)) {
+                        (best_so_far, stalled) = (Some(fitness), 0);
+                    } else {
+                        stalled += 1;
+                    }
+                    let stop_reason = if params.stop_on_feasible
+                        && context.is_feasible(&best.solution.genome)
+                    {
+                        Some(StopReason::Feasible)
+                    } else if fitness >= highest_fitness {
+                        Some(StopReason::Optimum)
+                    } else if cancelled.is_some_and(|cancelled| cancelled.load(Ordering::Relaxed)) {
+                        Some(StopReason::Cancelled)
+                    } else {
+                        params
+                            .patience
+                            .filter(|patience| stalled >= *patience)
+                            .map(|patience| StopReason::Plateau { patience })
+                    };
+                    if let Some(stop_reason) = stop_reason {
+                        break Solution {
+                            genome: break_tie(
+                                context,
+                                &params.tie_break,
+                                &step.result.evaluated_population,
+                                &best.solution.genome,
+                                fitness,
+                            ),
+                            fitness,
+                            generation: step.iteration,
+                            found_in_generation: best.generation,
+                            stop_reason,
+                            termination: None,
+                            operator_uses: params.hyper_heuristic.then_some(operator_selector.uses),
+                            cache_stats: None,
+                            history,
+                        };
+                    }
+                }
+                Ok(SimResult::Final(step, processing_time, duration, stop_reason)) => {
+                    let best = &step.result.best_solution;
+                    history.push(GenerationStats {
+                        generation: step.iteration,
+                        best: best.solution.fitness,
+                        average: *step.result.evaluated_population.average_fitness(),
+                    });
+                    on_step(&Step {
+                        iteration: step.iteration,
+                        population: &step.result.evaluated_population,
+                        best,
+                        duration: step.duration,
+                        processing_time: step.processing_time,
+                        is_final: true,
+                        next_operator: None,
+                        mutation_rate: None,
+                    });
+
+                    break Solution {
+                        genome: break_tie(
+                            context,
+                            &params.tie_break,
+                            &step.result.evaluated_population,
+                            &best.solution.genome,
+                            best.solution.fitness,
+                        ),
+                        fitness: best.solution.fitness,
+                        generation: step.iteration,
+                        found_in_generation: best.generation,
+                        stop_reason: StopReason::GenerationLimit,
+                        termination: Some(Termination {
+                            stop_reason: stop_reason.to_string(),
+                            duration,
+                            processing_time,
+                        }),
+                        operator_uses: params.hyper_heuristic.then_some(operator_selector.uses),
+                        cache_stats: None,
+                        history,
+                    };
+                }
+                Err(error) => {
+                    panic!("{}", error);
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+/// Best and average fitness of one generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub best: i64,
+    pub average: i64,
+}
+
+/// The convergence history of a run as `generation,best,average` CSV.
+pub fn stats_csv(stats: &[GenerationStats]) -> String {
+    let mut csv = String::from(
+        "generation,best,average
+",
+    );
+    for step in stats {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            step.generation, step.best, step.average
+        ));
+    }
+    csv
+}
+
+/// Summary of one run, as stored in the `runs` table.
+#[derive(Debug)]
+pub struct RunRecord<'a> {
+    pub config: String,
+    pub fingerprint: u64,
+    pub final_fitness: i64,
+    pub highest_fitness: i64,
+    pub feasible: bool, // the final schedule satisfies every hard constraint.
+    pub generations: &'a [GenerationStats], // every step of the run.
+}
+
+/// Appends a run and its generation trajectory to the SQLite database at `path`, creating
+/// the tables on first use. Returns the id of the new run.
+pub fn record_run(path: &Path, record: &RunRecord) -> rusqlite::Result<i64> {
+    let mut connection = Connection::open(path)?;
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS runs (
+             id INTEGER PRIMARY KEY,
+             recorded_at TEXT NOT NULL DEFAULT (datetime('now')),
+             config TEXT NOT NULL,
+             fingerprint TEXT NOT NULL,
+             final_fitness INTEGER NOT NULL,
+             highest_fitness INTEGER NOT NULL,
+             feasible INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS generations (
+             run_id INTEGER NOT NULL REFERENCES runs(id),
+             generation INTEGER NOT NULL,
+             best_fitness INTEGER NOT NULL,
+             average_fitness INTEGER NOT NULL,
+             PRIMARY KEY (run_id, generation)
+         );",
+    )?;
+
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "INSERT INTO runs (config, fingerprint, final_fitness, highest_fitness, feasible)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            record.config,
+            format!("{:016x}", record.fingerprint),
+            record.final_fitness,
+            record.highest_fitness,
+            record.feasible,
+        ],
+    )?;
+    let run_id = transaction.last_insert_rowid();
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO generations (run_id, generation, best_fitness, average_fitness)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for step in record.generations {
+            insert.execute(params![
+                run_id,
+                step.generation as i64,
+                step.best,
+                step.average
+            ])?;
+        }
+    }
+    transaction.commit()?;
+
+    Ok(run_id)
+}
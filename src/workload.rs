@@ -0,0 +1,69 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+
+use crate::analysis::Utilization;
+use crate::{Genome, LecturerId, Problem};
+
+/// Builds a JSON workload record for every lecturer from the final schedule: assigned hours
+/// against the weekly budget, overall and per week, and how many distinct hours they teach in.
+/// Lecturers without any lessons are listed too, so the report covers the whole staff, and
+/// those with a display name get a `lecturer_name` field.
+pub fn lecturer_report(problem: &Problem, genome: &Genome) -> Value {
+    let mut hours: BTreeMap<LecturerId, Vec<usize>> = problem
+        .lecturer_requirements
+        .keys()
+        .map(|lecturer| (*lecturer, vec![]))
+        .collect();
+    for dna in genome {
+        let (_, lecturer, _, slot) = dna.0;
+        hours.entry(lecturer).or_default().push(slot.index());
+    }
+
+    let lecturers: Vec<_> = hours
+        .into_iter()
+        .map(|(lecturer, hours)| {
+            let budget = problem
+                .lecturer_requirements
+                .get(&lecturer)
+                .copied()
+                .unwrap_or_default();
+            let weeks: Vec<_> = (0..problem.weeks)
+                .map(|week| {
+                    let taught: Vec<_> =
+                        hours.iter().filter(|hour| *hour / problem.total_hours == week).collect();
+                    let distinct: BTreeSet<_> = taught.iter().collect();
+                    let utilization = Utilization {
+                        demand: taught.len(),
+                        capacity: budget,
+                    };
+                    json!({
+                        "week": week,
+                        "assigned_hours": taught.len(),
+                        "distinct_hours": distinct.len(),
+                        "utilization_percent": utilization.percent(),
+                    })
+                })
+                .collect();
+            let utilization = Utilization {
+                demand: hours.len(),
+                capacity: budget * problem.weeks,
+            };
+            let mut record = json!({
+                "lecturer": lecturer,
+                "budget_per_week": budget,
+                "assigned_hours": hours.len(),
+                "utilization_percent": utilization.percent(),
+                "over_budget": (0..problem.weeks)
+                    .any(|week| hours.iter().filter(|hour| *hour / problem.total_hours == week).count() > budget),
+                "weeks": weeks,
+            });
+            if let Some(name) = problem.lecturer_names.get(&lecturer) {
+                record["lecturer_name"] = json!(name);
+            }
+            record
+        })
+        .collect();
+
+    json!({ "lecturers": lecturers })
+}
@@ -0,0 +1,590 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use genevo::prelude::{FitnessFunction, GenomeBuilder, Rng};
+use islab3::hyper::Crossover;
+use islab3::solver::{Evaluation, Selection, StopReason};
+use islab3::{analysis, dataset, solve, solver, Context, Genome, Problem, SolverParams};
+
+#[test]
+fn seeded_small_example_finds_a_feasible_schedule() {
+    let problem = dataset::small_example();
+    let params = SolverParams {
+        seed: Some([1; 32]),
+        ..SolverParams::default()
+    };
+    let genome = solve(&problem, params).genome;
+
+    let context = Context::new(problem);
+    assert!(analysis::validate_genome(&context, &genome).is_empty());
+    assert!(context.is_feasible(&genome));
+    assert!(analysis::diagnose(&context, &genome).is_empty());
+}
+
+#[test]
+fn different_problems_solve_side_by_side() {
+    let params = SolverParams {
+        generation_limit: 10,
+        ..SolverParams::default()
+    };
+    let problems = [dataset::small_example(), dataset::medium_example()];
+    let genomes: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = problems
+            .iter()
+            .map(|problem| scope.spawn(|| solve(problem, params.clone()).genome))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    for (problem, genome) in problems.into_iter().zip(genomes) {
+        let context = Context::new(problem);
+        assert_eq!(genome.len(), context.group_subjects.len());
+        assert!(analysis::validate_genome(&context, &genome).is_empty());
+    }
+}
+
+#[test]
+fn solution_records_every_generation() {
+    let params = SolverParams {
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(42)),
+        ..SolverParams::default()
+    };
+    let solution = solve(&dataset::small_example(), params);
+
+    let generations: Vec<_> = solution
+        .history
+        .iter()
+        .map(|stats| stats.generation)
+        .collect();
+    assert_eq!(generations, (1..=10).collect::<Vec<_>>());
+    assert_eq!(solution.generation, 10);
+    assert_eq!(solution.stop_reason, StopReason::GenerationLimit);
+    let best = solution.history.iter().map(|stats| stats.best).max();
+    assert_eq!(best, Some(solution.fitness));
+    assert!(solution
+        .history
+        .iter()
+        .all(|stats| stats.average <= stats.best));
+}
+
+#[test]
+fn same_seed_gives_the_same_schedule() {
+    let params = SolverParams {
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(42)),
+        ..SolverParams::default()
+    };
+    let problem = dataset::medium_example();
+    assert_eq!(
+        solve(&problem, params.clone()).genome,
+        solve(&problem, params).genome
+    );
+}
+
+#[test]
+fn parallel_evaluation_matches_serial() {
+    let params = SolverParams {
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(7)),
+        ..SolverParams::default()
+    };
+    let problem = dataset::medium_example();
+    let serial = SolverParams {
+        evaluation: Evaluation::Serial,
+        ..params.clone()
+    };
+    let parallel = SolverParams {
+        evaluation: Evaluation::Parallel,
+        ..params
+    };
+    assert_eq!(
+        solve(&problem, serial).genome,
+        solve(&problem, parallel).genome
+    );
+}
+
+#[test]
+fn crossover_choice_changes_the_search() {
+    let params = SolverParams {
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(7)),
+        ..SolverParams::default()
+    };
+    let problem = dataset::medium_example();
+    let genomes: Vec<_> = [
+        Crossover::SinglePoint,
+        Crossover::MultiPoint,
+        Crossover::Uniform,
+    ]
+    .into_iter()
+    .map(|crossover| {
+        let params = SolverParams {
+            crossover,
+            ..params.clone()
+        };
+        solve(&problem, params).genome
+    })
+    .collect();
+    assert_ne!(genomes[0], genomes[1]);
+    assert_ne!(genomes[1], genomes[2]);
+    assert_ne!(genomes[0], genomes[2]);
+}
+
+#[test]
+fn tournament_selection_runs() {
+    let params = SolverParams {
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(7)),
+        selection: Selection::Tournament,
+        selection_count: 2,
+        ..SolverParams::default()
+    };
+    let problem = dataset::small_example();
+    let genome = solve(&problem, params.clone()).genome;
+
+    let context = Context::new(problem.clone());
+    assert!(analysis::validate_genome(&context, &genome).is_empty());
+    let maximize = SolverParams {
+        selection: Selection::Maximize,
+        ..params
+    };
+    assert_ne!(genome, solve(&problem, maximize).genome);
+}
+
+#[test]
+fn patience_stops_a_stalled_run() {
+    let context = Context::new(dataset::small_example());
+    let params = SolverParams {
+        generation_limit: 1000,
+        seed: Some([2; 32]),
+        patience: Some(5),
+        ..SolverParams::default()
+    };
+    let mut best = vec![];
+    let solution = solver::solve_with(&context, &params, |step| {
+        best.push(step.best.solution.fitness)
+    });
+
+    assert_eq!(solution.stop_reason, StopReason::Plateau { patience: 5 });
+    assert!(solution.termination.is_none());
+    // The run ends on the fifth generation in a row that doesn't beat the best before it.
+    let (earlier, stalled) = best.split_at(best.len() - 5);
+    let best_before = *earlier.iter().max().unwrap();
+    assert!(stalled.iter().all(|&fitness| fitness <= best_before));
+    assert!(best.len() < 1000);
+}
+
+#[test]
+fn a_custom_builder_seeds_the_initial_population() {
+    /// Builds every genome from the small example's seeded solution.
+    struct Known<'a>(&'a Genome, &'a AtomicUsize);
+
+    impl GenomeBuilder<Genome> for Known<'_> {
+        fn build_genome<R: Rng + Sized>(&self, _: usize, _: &mut R) -> Genome {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            self.0.clone()
+        }
+    }
+
+    let problem = dataset::small_example();
+    let known = solve(
+        &problem,
+        SolverParams {
+            seed: Some([1; 32]),
+            ..SolverParams::default()
+        },
+    );
+    let context = Context::new(problem);
+    let params = SolverParams {
+        generation_limit: 1,
+        seed: Some([3; 32]),
+        ..SolverParams::default()
+    };
+    let built = AtomicUsize::new(0);
+    let solution =
+        solver::solve_with_builder(&context, &params, Known(&known.genome, &built), |_| {});
+
+    assert_eq!(built.into_inner(), params.population_size);
+    assert!(solution.fitness >= known.fitness);
+}
+
+#[test]
+fn solve_iter_yields_every_generation_and_ends_on_the_solution() {
+    let problem = dataset::small_example();
+    let params = SolverParams {
+        generation_limit: 30,
+        seed: Some([2; 32]),
+        ..SolverParams::default()
+    };
+    let generations: Vec<_> = solver::solve_iter(&problem, params.clone()).collect();
+    let solution = solver::solve(&problem, params.clone());
+
+    assert_eq!(generations.len(), 30);
+    let last = generations.last().unwrap();
+    assert_eq!(last.stats, *solution.history.last().unwrap());
+    assert_eq!(last.best, solution.genome);
+
+    // Stopping early just drops the iterator.
+    let first_five: Vec<_> = solver::solve_iter(&problem, params).take(5).collect();
+    assert_eq!(first_five.len(), 5);
+    assert_eq!(first_five[4].stats, generations[4].stats);
+}
+
+#[test]
+fn gains_within_the_patience_epsilon_dont_reset_the_patience() {
+    let context = Context::new(dataset::small_example());
+    let params = SolverParams {
+        generation_limit: 1000,
+        seed: Some([2; 32]),
+        patience: Some(5),
+        // No gain is large enough, so only the first generation counts.
+        patience_epsilon: u64::MAX,
+        ..SolverParams::default()
+    };
+    let solution = solver::solve_with(&context, &params, |_| {});
+
+    assert_eq!(solution.stop_reason, StopReason::Plateau { patience: 5 });
+    assert_eq!(solution.generation, 6);
+}
+
+#[test]
+fn restarts_keep_the_best_run() {
+    let context = Context::new(dataset::small_example());
+    let params = SolverParams {
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(3)),
+        ..SolverParams::default()
+    };
+    let mut steps = [0; 3];
+    let restarts = solver::solve_restarts(&context, &params, 3, |restart, _| steps[restart] += 1);
+
+    assert!(steps.iter().all(|&count| count > 0));
+    let fitnesses: Vec<_> = restarts.runs.iter().map(|run| run.fitness).collect();
+    assert_eq!(fitnesses[restarts.best], *fitnesses.iter().max().unwrap());
+    // The first run is the plain seeded run, the others are seeded apart from it.
+    let single = solver::solve_with(&context, &params, |_| {});
+    assert_eq!(restarts.runs[0].genome, single.genome);
+    assert_ne!(restarts.runs[1].genome, restarts.runs[0].genome);
+    let best = restarts.into_best();
+    assert_eq!(best.fitness, *fitnesses.iter().max().unwrap());
+}
+
+#[test]
+fn islands_evolve_side_by_side_to_the_generation_limit() {
+    let context = Context::new(dataset::small_example());
+    let params = SolverParams {
+        population_size: 60,
+        generation_limit: 10,
+        seed: Some(solver::seed_from_u64(4)),
+        ..SolverParams::default()
+    };
+    // Intervals of 4, 4 and 2 generations.
+    let mut steps = vec![vec![]; 3];
+    let islands = solver::solve_islands(&context, &params, 3, 4, |island, step| {
+        steps[island].push((step.iteration, step.is_final));
+    });
+
+    let expected: Vec<_> = (1..=10)
+        .map(|generation| (generation, generation == 10))
+        .collect();
+    assert!(steps.iter().all(|island| *island == expected));
+    for island in &islands.islands {
+        assert_eq!(island.generation, 10);
+        let generations: Vec<_> = island
+            .history
+            .iter()
+            .map(|stats| stats.generation)
+            .collect();
+        assert_eq!(generations, (1..=10).collect::<Vec<_>>());
+    }
+    let fitnesses: Vec<_> = islands
+        .islands
+        .iter()
+        .map(|island| island.fitness)
+        .collect();
+    assert_eq!(fitnesses[islands.best], *fitnesses.iter().max().unwrap());
+
+    let again = solver::solve_islands(&context, &params, 3, 4, |_, _| {});
+    assert_eq!(again.into_best().genome, islands.into_best().genome);
+}
+
+#[test]
+fn pinned_lessons_stay_put() {
+    let mut problem = dataset::small_example();
+    problem.pinned = vec![(0, 1, 2, 7)];
+    assert_eq!(problem.validate(), Ok(()));
+    let params = SolverParams {
+        generation_limit: 20,
+        seed: Some(solver::seed_from_u64(5)),
+        ..SolverParams::default()
+    };
+    let genome = solve(&problem, params).genome;
+
+    // Gene 2 is the first hour of group 0's subject 1.
+    let context = Context::new(problem);
+    let pin = context.pin(2).unwrap();
+    assert_eq!((genome[2].0 .1, genome[2].0 .3), pin);
+    assert_eq!(pin.1.index(), 7);
+    assert!(analysis::diagnose(&context, &genome)
+        .iter()
+        .all(|violation| violation.kind != analysis::ViolationKind::MovedPin));
+}
+
+#[test]
+fn adaptive_mutation_stays_between_base_and_max() {
+    let context = Context::new(dataset::small_example());
+    let params = SolverParams {
+        generation_limit: 40,
+        seed: Some([2; 32]),
+        mutation_rate: 0.1,
+        adaptive_mutation: Some(solver::AdaptiveMutation {
+            max: 0.6,
+            patience: 1,
+        }),
+        ..SolverParams::default()
+    };
+    let mut rates = vec![];
+    solver::solve_with(&context, &params, |step| {
+        if !step.is_final {
+            rates.push(step.mutation_rate.unwrap());
+        }
+    });
+
+    assert!(rates.iter().all(|rate| (0.1..=0.6).contains(rate)));
+    assert!(
+        rates.iter().any(|&rate| rate > 0.1),
+        "the rate rose at some point"
+    );
+}
+
+#[test]
+fn weighting_early_starts_makes_fewer_of_them() {
+    let mut problem = dataset::small_example();
+    let params = SolverParams {
+        generation_limit: 50,
+        seed: Some(solver::seed_from_u64(6)),
+        ..SolverParams::default()
+    };
+    let mut early_starts = vec![];
+    for weight in [0, 5] {
+        problem.weights.early_start = weight;
+        let context = Context::new(problem.clone());
+        let solution = solver::solve_with(&context, &params, |_| {});
+        let genome = &solution.genome;
+        let all: Vec<_> = (0..genome.len()).collect();
+        early_starts.push(analysis::evaluate_genes(genome, &all, &context).early_starts);
+    }
+    assert!(early_starts[1] < early_starts[0], "{early_starts:?}");
+}
+
+#[test]
+fn groups_get_fewer_lessons_at_hours_they_dislike() {
+    let mut problem = dataset::small_example();
+    // Group 0 would rather keep its afternoons free, 10 of the 20 hours of the week.
+    problem.group_disliked_hours = [(0, (0..20).filter(|hour| hour % 4 >= 2).collect())].into();
+    let params = SolverParams {
+        generation_limit: 50,
+        seed: Some(solver::seed_from_u64(6)),
+        ..SolverParams::default()
+    };
+    let mut disliked = vec![];
+    for weight in [0, 5] {
+        problem.weights.disliked_hour = weight;
+        let context = Context::new(problem.clone());
+        let solution = solver::solve_with(&context, &params, |_| {});
+        let genome = &solution.genome;
+        let all: Vec<_> = (0..genome.len()).collect();
+        disliked.push(analysis::evaluate_genes(genome, &all, &context).disliked_lessons);
+    }
+    assert!(disliked[1] < disliked[0], "{disliked:?}");
+}
+
+#[test]
+fn soft_terms_start_to_count_at_the_soft_deadline() {
+    let context = Context::new(dataset::small_example());
+    let params = SolverParams {
+        generation_limit: 40,
+        seed: Some(solver::seed_from_u64(3)),
+        soft_deadline: Some(20),
+        ..SolverParams::default()
+    };
+    let solution = solver::solve_with(&context, &params, |_| {});
+
+    let generations: Vec<_> = solution
+        .history
+        .iter()
+        .map(|stats| stats.generation)
+        .collect();
+    assert_eq!(generations, (1..=40).collect::<Vec<_>>());
+    // The same population scores lower once gaps and repeated subjects cost something.
+    let (before, after) = (&solution.history[19], &solution.history[20]);
+    assert!(after.average < before.average, "{before:?} {after:?}");
+    assert_eq!(solution.fitness, (&context).fitness_of(&solution.genome));
+}
+
+#[test]
+fn lecturers_capped_at_three_periods_in_a_row_never_teach_four() {
+    // Six lessons over two days of four periods fit in a row only as three and three.
+    let mut problem = Problem::new(
+        [(0, vec![(0, 6)])].into(),
+        [(0, 6)].into(),
+        [(0, vec![0])].into(),
+    );
+    problem.total_hours = 8;
+    problem.max_consecutive = [(0, 3)].into();
+    let params = SolverParams {
+        seed: Some(solver::seed_from_u64(1)),
+        ..SolverParams::default()
+    };
+    let context = Context::new(problem);
+    let solution = solver::solve_with(&context, &params, |_| {});
+
+    let mut periods = std::collections::HashMap::<_, Vec<_>>::new();
+    for dna in &solution.genome {
+        let (_, lecturer, _, slot) = dna.0;
+        periods
+            .entry((lecturer, slot.day))
+            .or_default()
+            .push(slot.period);
+    }
+    for ((lecturer, day), mut day_periods) in periods {
+        day_periods.sort();
+        day_periods.dedup();
+        let longest = day_periods
+            .chunk_by(|first, second| first + 1 == *second)
+            .map(|run| run.len())
+            .max()
+            .unwrap();
+        assert!(
+            longest <= 3,
+            "lecturer {lecturer} on day {day}: {day_periods:?}"
+        );
+    }
+    assert!(!analysis::diagnose(&context, &solution.genome)
+        .iter()
+        .any(|violation| matches!(violation.kind, analysis::ViolationKind::LongRun { .. })));
+}
+
+#[test]
+fn feasibility_sweeps_time_every_run() {
+    // Two groups sharing a lecturer in a single day.
+    let mut problem = Problem::new(
+        [(0, vec![(0, 2)]), (1, vec![(0, 2)])].into(),
+        [(0, 4)].into(),
+        [(0, vec![0])].into(),
+    );
+    problem.total_hours = 4;
+    let params = SolverParams {
+        population_size: 50,
+        seed: Some(solver::seed_from_u64(5)),
+        ..SolverParams::default()
+    };
+    let context = Context::new(problem);
+    let sweep = solver::sweep_feasibility(&context, &params, 3, |_, _| {});
+    assert_eq!(sweep.generations.len(), 3);
+    assert_eq!(sweep.generation_limit, params.generation_limit);
+    assert!(sweep
+        .generations
+        .iter()
+        .all(|run| run.is_some_and(|generation| generation <= params.generation_limit)));
+    assert_eq!(sweep.never_feasible(), 0.0);
+
+    let sweep = solver::FeasibilitySweep {
+        generations: vec![Some(9), None, Some(3), Some(5), Some(12), None],
+        generation_limit: 100,
+    };
+    assert_eq!(sweep.median(), Some(5));
+    assert_eq!(sweep.never_feasible(), 2.0 / 6.0);
+}
+
+#[test]
+fn ties_go_to_the_schedule_best_on_the_tie_breakers() {
+    // Without soft terms, schedules breaking the same hard constraints tie.
+    let mut problem = dataset::small_example();
+    problem.weights = problem.weights.hard_only();
+    let context = Context::new(problem);
+    let params = SolverParams {
+        generation_limit: 30,
+        seed: Some(solver::seed_from_u64(9)),
+        tie_break: vec![solver::TieBreak::Finish, solver::TieBreak::Gaps],
+        ..SolverParams::default()
+    };
+    let mut last = vec![];
+    let solution = solver::solve_with(&context, &params, |step| {
+        if step.is_final {
+            let population = step.population;
+            last = population
+                .individuals()
+                .iter()
+                .zip(population.fitness_values())
+                .filter(|(_, fitness)| **fitness == step.best.solution.fitness)
+                .map(|(genome, _)| genome.clone())
+                .collect();
+        }
+    });
+    assert_eq!(solution.fitness, (&context).fitness_of(&solution.genome));
+
+    let finish = |genome: &Genome| {
+        let mut days = std::collections::HashMap::new();
+        for ((group, _), dna) in context.group_subjects.iter().zip(genome) {
+            let end = days.entry((group, dna.0 .3.day)).or_insert(0);
+            *end = (dna.0 .3.period + 1).max(*end);
+        }
+        days.values().map(|&end| usize::from(end)).sum::<usize>()
+    };
+    assert!(!last.is_empty());
+    assert!(last
+        .iter()
+        .all(|genome| finish(&solution.genome) <= finish(genome)));
+
+    // The same run without tie-breakers keeps a tied schedule finishing later.
+    let plain = solver::solve_with(
+        &context,
+        &SolverParams {
+            tie_break: vec![],
+            ..params
+        },
+        |_| {},
+    );
+    assert_eq!(plain.fitness, solution.fitness);
+    assert!(finish(&solution.genome) < finish(&plain.genome));
+}
+
+#[test]
+fn variations_of_an_accepted_schedule_stay_feasible() {
+    let problem = dataset::small_example();
+    let accepted = solve(
+        &problem,
+        SolverParams {
+            seed: Some([1; 32]),
+            ..SolverParams::default()
+        },
+    )
+    .genome;
+    let context = Context::new(problem.clone());
+    let params = SolverParams {
+        generation_limit: 20,
+        seed: Some(solver::seed_from_u64(4)),
+        ..SolverParams::default()
+    };
+    let variations = solver::variations(&context, &accepted, &params, 3, |_, _| {});
+
+    // Only soft terms tell the variations from the accepted schedule.
+    let hard = Context::new(Problem {
+        weights: problem.weights.hard_only(),
+        ..problem
+    });
+    assert_eq!(variations.runs.len(), 3);
+    for run in &variations.runs {
+        assert!(context.is_feasible(&run.genome));
+        assert!(run.fitness >= (&context).fitness_of(&accepted));
+        assert_eq!(
+            (&hard).fitness_of(&run.genome),
+            (&hard).fitness_of(&accepted)
+        );
+    }
+    assert!(variations.runs.iter().any(|run| run.genome != accepted));
+}